@@ -0,0 +1,759 @@
+//! Statistics over a course's threads, for staff keeping an eye on the forum.
+//!
+//! A [`ForumReport`] is computed from fully fetched threads, most easily with
+//! [`Client::forum_report`](crate::Client::forum_report), and can be exported with
+//! [`to_json`](ForumReport::to_json) or one table at a time with [`to_csv`](ForumReport::to_csv).
+//!
+//! "Staff" here means tutors and mentors as well as staff and admins. Anonymous posts only count
+//! towards a contributor when [`AnalyticsOptions::reveal_anonymous`] is set and the response
+//! reveals their author, and timestamps which fail to parse are left out of time-based figures.
+
+use std::collections::{BTreeMap, HashMap};
+
+use jiff::{Timestamp, tz::TimeZone};
+use serde::Serialize;
+
+use crate::{
+    anonymity::{AnonymousResolver, AuthorKey, ResolvedAuthor},
+    csv::write_row,
+    model::{
+        course::{CourseRoleLabels, Role},
+        thread::{ReplyType, ThreadResponse, ThreadType},
+    },
+    opts::AnalyticsOptions,
+    participants::role_label,
+};
+
+const WEEKDAYS: [&str; 7] = [
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+    "Sunday",
+];
+
+/// Summary statistics of some measurements.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Summary {
+    /// How many measurements there were.
+    pub count: usize,
+    /// The smallest.
+    pub min: f64,
+    /// The largest.
+    pub max: f64,
+    /// The mean.
+    pub mean: f64,
+    /// The median.
+    pub median: f64,
+    /// The 90th percentile, by nearest rank.
+    pub p90: f64,
+}
+
+impl Summary {
+    fn new(mut values: Vec<f64>) -> Option<Self> {
+        if values.is_empty() {
+            return None;
+        }
+        values.sort_by(f64::total_cmp);
+
+        let count = values.len();
+        let median = if count.is_multiple_of(2) {
+            (values[count / 2 - 1] + values[count / 2]) / 2.0
+        } else {
+            values[count / 2]
+        };
+
+        Some(Self {
+            count,
+            min: values[0],
+            max: values[count - 1],
+            mean: values.iter().sum::<f64>() / count as f64,
+            median,
+            p90: values[(count * 9).div_ceil(10) - 1],
+        })
+    }
+
+    fn csv_fields(summary: Option<&Self>) -> Vec<String> {
+        let Some(s) = summary else {
+            let mut fields = vec![String::new(); 6];
+            fields[0] = String::from("0");
+            return fields;
+        };
+        let mut fields = vec![s.count.to_string()];
+        fields.extend([s.min, s.max, s.mean, s.median, s.p90].map(|v| format!("{v:.2}")));
+        fields
+    }
+}
+
+/// How quickly staff respond to threads in one category.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ResponseTimes {
+    /// The top-level category.
+    pub category: String,
+    /// How many threads could get a response, i.e. those which are not announcements and were not
+    /// posted by staff.
+    pub threads: usize,
+    /// Minutes until the first staff answer, or for posts the first staff reply, over the
+    /// threads which have one.
+    pub minutes: Option<Summary>,
+}
+
+/// A thread still waiting on someone, in a [`Backlog`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct BacklogThread {
+    /// The thread ID.
+    pub id: u64,
+    /// The thread number, as shown in the UI.
+    pub number: u64,
+    /// The title.
+    pub title: String,
+    /// The top-level category.
+    pub category: String,
+    /// When the thread was posted.
+    pub created_at: String,
+    /// How many replies the thread has.
+    pub reply_count: u64,
+}
+
+/// Threads still waiting on someone, oldest first.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct Backlog {
+    /// Questions with no answer.
+    pub unanswered: Vec<BacklogThread>,
+    /// Threads with unresolved comments.
+    pub unresolved: Vec<BacklogThread>,
+}
+
+/// Someone's activity, in [`ForumReport::top_contributors`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Contributor {
+    /// The user ID.
+    pub user_id: u64,
+    /// Their name.
+    pub name: String,
+    /// The name shown for their role in the course.
+    pub role: String,
+    /// How many threads they posted.
+    pub threads: usize,
+    /// How many answers and comments they posted.
+    pub replies: usize,
+    /// How many of those threads and replies were endorsed.
+    pub endorsed: usize,
+}
+
+/// How much of something was endorsed.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct Rate {
+    /// How many there were.
+    pub total: usize,
+    /// How many were endorsed.
+    pub endorsed: usize,
+    /// `endorsed / total`, if there were any.
+    pub rate: Option<f64>,
+}
+
+impl Rate {
+    fn add(&mut self, endorsed: bool) {
+        self.total += 1;
+        self.endorsed += usize::from(endorsed);
+        self.rate = Some(self.endorsed as f64 / self.total as f64);
+    }
+}
+
+/// Endorsement rates by kind of post.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct Endorsements {
+    /// Threads.
+    pub threads: Rate,
+    /// Answers.
+    pub answers: Rate,
+    /// Comments, at any depth.
+    pub comments: Rate,
+}
+
+/// How many threads and replies were posted at each time, in [`Activity::tz`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct Activity {
+    /// The tz database identifier the times are in.
+    pub tz: String,
+    /// Posts by day of the week, Monday first.
+    pub by_weekday: [usize; 7],
+    /// Posts by hour of the day.
+    pub by_hour: [usize; 24],
+}
+
+/// How views and votes are spread across a course.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct Distributions {
+    /// Views per thread.
+    pub views: Option<Summary>,
+    /// Unique viewers per thread.
+    pub unique_views: Option<Summary>,
+    /// Votes per thread.
+    pub thread_votes: Option<Summary>,
+    /// Votes per answer or comment.
+    pub reply_votes: Option<Summary>,
+}
+
+/// A table of a [`ForumReport`], for [`ForumReport::to_csv`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReportTable {
+    /// [`ForumReport::response_times`].
+    ResponseTimes,
+    /// [`ForumReport::backlog`].
+    Backlog,
+    /// [`ForumReport::top_contributors`].
+    Contributors,
+    /// [`ForumReport::endorsements`].
+    Endorsements,
+    /// [`ForumReport::activity`].
+    Activity,
+    /// [`ForumReport::distributions`].
+    Distributions,
+}
+
+/// Statistics over a set of threads. See the [module docs](self).
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ForumReport {
+    /// Response times by category, in category order.
+    pub response_times: Vec<ResponseTimes>,
+    /// Threads still waiting on someone.
+    pub backlog: Backlog,
+    /// The most active contributors for each role label, most active first.
+    pub top_contributors: BTreeMap<String, Vec<Contributor>>,
+    /// Endorsement rates.
+    pub endorsements: Endorsements,
+    /// When threads and replies were posted.
+    pub activity: Activity,
+    /// View and vote distributions.
+    pub distributions: Distributions,
+}
+
+fn is_staff(author: &ResolvedAuthor) -> bool {
+    author
+        .participant
+        .as_ref()
+        .and_then(|p| p.course_role().as_ref())
+        .is_some_and(Role::is_staff)
+}
+
+fn contributor<'a>(
+    contributors: &'a mut HashMap<u64, Contributor>,
+    author: &ResolvedAuthor,
+    labels: Option<&CourseRoleLabels>,
+) -> Option<&'a mut Contributor> {
+    let (AuthorKey::User(id), Some(participant)) = (author.key, &author.participant) else {
+        return None;
+    };
+    Some(contributors.entry(id.into()).or_insert_with(|| {
+        Contributor {
+            user_id: id.into(),
+            name: participant.name().clone(),
+            role: participant
+                .course_role()
+                .as_ref()
+                .map_or("Unknown", |r| role_label(r, labels))
+                .to_owned(),
+            threads: 0,
+            replies: 0,
+            endorsed: 0,
+        }
+    }))
+}
+
+impl Activity {
+    fn add(&mut self, tz: &TimeZone, at: Timestamp) {
+        let local = at.to_zoned(tz.clone());
+        self.by_weekday[local.weekday().to_monday_zero_offset() as usize] += 1;
+        self.by_hour[local.hour() as usize] += 1;
+    }
+}
+
+fn backlog_thread(response: &ThreadResponse) -> BacklogThread {
+    let thread = response.thread();
+    BacklogThread {
+        id: (*thread.id()).into(),
+        number: *thread.number(),
+        title: thread.title().clone(),
+        category: thread.category().clone(),
+        created_at: thread.created_at().clone(),
+        reply_count: *thread.reply_count(),
+    }
+}
+
+impl ForumReport {
+    /// Compute statistics over `threads`, using a course's names for roles if `labels` are
+    /// given.
+    ///
+    /// Fails with [`Error::Time`](crate::Error::Time) if `options.tz` is not a known time zone.
+    pub fn new(
+        threads: &[ThreadResponse],
+        labels: Option<&CourseRoleLabels>,
+        options: &AnalyticsOptions,
+    ) -> crate::Result<Self> {
+        let tz = TimeZone::get(&options.tz)?;
+
+        let mut response_times: BTreeMap<&str, (usize, Vec<f64>)> = BTreeMap::new();
+        let mut backlog = Backlog::default();
+        let mut contributors: HashMap<u64, Contributor> = HashMap::new();
+        let mut endorsements = Endorsements::default();
+        let mut activity = Activity {
+            tz: options.tz.clone(),
+            ..Default::default()
+        };
+        let (mut views, mut unique_views, mut thread_votes, mut reply_votes) =
+            (Vec::new(), Vec::new(), Vec::new(), Vec::new());
+
+        for response in threads {
+            let thread = response.thread();
+            let resolver =
+                AnonymousResolver::new(thread, response.users(), options.reveal_anonymous);
+            let author = resolver.thread_author(thread);
+            let posted_at = thread.created_at().parse::<Timestamp>().ok();
+
+            if let Some(at) = posted_at {
+                activity.add(&tz, at);
+            }
+            if let Some(c) = contributor(&mut contributors, &author, labels) {
+                c.threads += 1;
+                c.endorsed += usize::from(*thread.is_endorsed());
+            }
+            endorsements.threads.add(*thread.is_endorsed());
+            views.push(*thread.view_count() as f64);
+            unique_views.push(*thread.unique_view_count() as f64);
+            thread_votes.push(*thread.vote_count() as f64);
+
+            let mut first_response: Option<Timestamp> = None;
+            for (_, reply) in thread.replies() {
+                let reply_author = resolver.reply_author(reply);
+                let replied_at = reply.created_at().parse::<Timestamp>().ok();
+
+                if let Some(at) = replied_at {
+                    activity.add(&tz, at);
+                }
+                if let Some(c) = contributor(&mut contributors, &reply_author, labels) {
+                    c.replies += 1;
+                    c.endorsed += usize::from(*reply.is_endorsed());
+                }
+                match reply.type_() {
+                    ReplyType::Answer => endorsements.answers.add(*reply.is_endorsed()),
+                    ReplyType::Comment => endorsements.comments.add(*reply.is_endorsed()),
+                }
+                reply_votes.push(*reply.vote_count() as f64);
+
+                let counts =
+                    *thread.type_() != ThreadType::Question || *reply.type_() == ReplyType::Answer;
+                if let Some(at) = replied_at.filter(|_| counts && is_staff(&reply_author)) {
+                    first_response = Some(first_response.map_or(at, |first| first.min(at)));
+                }
+            }
+
+            if *thread.type_() != ThreadType::Announcement && !is_staff(&author) {
+                let (count, minutes) = response_times.entry(thread.category()).or_default();
+                *count += 1;
+                if let (Some(posted_at), Some(first_response)) = (posted_at, first_response) {
+                    minutes.push(first_response.duration_since(posted_at).as_secs_f64() / 60.0);
+                }
+            }
+
+            if *thread.type_() == ThreadType::Question && thread.answers().is_empty() {
+                backlog.unanswered.push(backlog_thread(response));
+            }
+            if *thread.unresolved_count() > 0 {
+                backlog.unresolved.push(backlog_thread(response));
+            }
+        }
+
+        backlog
+            .unanswered
+            .sort_by(|a, b| a.created_at.cmp(&b.created_at));
+        backlog
+            .unresolved
+            .sort_by(|a, b| a.created_at.cmp(&b.created_at));
+
+        let mut top_contributors: BTreeMap<String, Vec<Contributor>> = BTreeMap::new();
+        for c in contributors.into_values() {
+            top_contributors.entry(c.role.clone()).or_default().push(c);
+        }
+        for by_role in top_contributors.values_mut() {
+            by_role.sort_by(|a, b| {
+                (b.threads + b.replies)
+                    .cmp(&(a.threads + a.replies))
+                    .then_with(|| a.name.cmp(&b.name))
+            });
+            by_role.truncate(options.top_contributors);
+        }
+
+        Ok(Self {
+            response_times: response_times
+                .into_iter()
+                .map(|(category, (threads, minutes))| ResponseTimes {
+                    category: String::from(category),
+                    threads,
+                    minutes: Summary::new(minutes),
+                })
+                .collect(),
+            backlog,
+            top_contributors,
+            endorsements,
+            activity,
+            distributions: Distributions {
+                views: Summary::new(views),
+                unique_views: Summary::new(unique_views),
+                thread_votes: Summary::new(thread_votes),
+                reply_votes: Summary::new(reply_votes),
+            },
+        })
+    }
+
+    /// The whole report as JSON.
+    pub fn to_json(&self) -> crate::Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// One table of the report as CSV, with a header row.
+    pub fn to_csv(&self, table: ReportTable) -> String {
+        let mut out = String::new();
+        let summary_header = ["count", "min", "max", "mean", "median", "p90"];
+
+        match table {
+            ReportTable::ResponseTimes => {
+                write_row(
+                    &mut out,
+                    ["category", "threads"].into_iter().chain(summary_header),
+                );
+                for r in &self.response_times {
+                    write_row(
+                        &mut out,
+                        [r.category.clone(), r.threads.to_string()]
+                            .into_iter()
+                            .chain(Summary::csv_fields(r.minutes.as_ref())),
+                    );
+                }
+            }
+            ReportTable::Backlog => {
+                write_row(
+                    &mut out,
+                    [
+                        "kind",
+                        "id",
+                        "number",
+                        "title",
+                        "category",
+                        "created_at",
+                        "reply_count",
+                    ],
+                );
+                let kinds = [
+                    ("unanswered", &self.backlog.unanswered),
+                    ("unresolved", &self.backlog.unresolved),
+                ];
+                for (kind, threads) in kinds {
+                    for t in threads {
+                        write_row(
+                            &mut out,
+                            [
+                                String::from(kind),
+                                t.id.to_string(),
+                                t.number.to_string(),
+                                t.title.clone(),
+                                t.category.clone(),
+                                t.created_at.clone(),
+                                t.reply_count.to_string(),
+                            ],
+                        );
+                    }
+                }
+            }
+            ReportTable::Contributors => {
+                write_row(
+                    &mut out,
+                    ["role", "user_id", "name", "threads", "replies", "endorsed"],
+                );
+                for c in self.top_contributors.values().flatten() {
+                    write_row(
+                        &mut out,
+                        [
+                            c.role.clone(),
+                            c.user_id.to_string(),
+                            c.name.clone(),
+                            c.threads.to_string(),
+                            c.replies.to_string(),
+                            c.endorsed.to_string(),
+                        ],
+                    );
+                }
+            }
+            ReportTable::Endorsements => {
+                write_row(&mut out, ["kind", "total", "endorsed", "rate"]);
+                let kinds = [
+                    ("threads", &self.endorsements.threads),
+                    ("answers", &self.endorsements.answers),
+                    ("comments", &self.endorsements.comments),
+                ];
+                for (kind, rate) in kinds {
+                    write_row(
+                        &mut out,
+                        [
+                            String::from(kind),
+                            rate.total.to_string(),
+                            rate.endorsed.to_string(),
+                            rate.rate.map(|r| format!("{r:.4}")).unwrap_or_default(),
+                        ],
+                    );
+                }
+            }
+            ReportTable::Activity => {
+                write_row(&mut out, ["dimension", "bucket", "count"]);
+                for (day, count) in WEEKDAYS.iter().zip(self.activity.by_weekday) {
+                    write_row(&mut out, ["weekday", day, &count.to_string()]);
+                }
+                for (hour, count) in self.activity.by_hour.iter().enumerate() {
+                    write_row(&mut out, ["hour", &hour.to_string(), &count.to_string()]);
+                }
+            }
+            ReportTable::Distributions => {
+                write_row(&mut out, ["measure"].into_iter().chain(summary_header));
+                let measures = [
+                    ("views", &self.distributions.views),
+                    ("unique_views", &self.distributions.unique_views),
+                    ("thread_votes", &self.distributions.thread_votes),
+                    ("reply_votes", &self.distributions.reply_votes),
+                ];
+                for (measure, summary) in measures {
+                    write_row(
+                        &mut out,
+                        std::iter::once(String::from(measure))
+                            .chain(Summary::csv_fields(summary.as_ref())),
+                    );
+                }
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summarises_values() {
+        assert_eq!(Summary::new(Vec::new()), None);
+        assert_eq!(
+            Summary::new(vec![4.0, 1.0, 3.0, 2.0]),
+            Some(Summary {
+                count: 4,
+                min: 1.0,
+                max: 4.0,
+                mean: 2.5,
+                median: 2.5,
+                p90: 4.0,
+            })
+        );
+
+        let summary = Summary::new((1..=20).rev().map(f64::from).collect()).unwrap();
+        assert_eq!(summary.median, 10.5);
+        assert_eq!(summary.p90, 18.0);
+        assert_eq!(Summary::new(vec![7.0]).unwrap().p90, 7.0);
+    }
+
+    #[test]
+    fn summary_csv_fields() {
+        assert_eq!(Summary::csv_fields(None), ["0", "", "", "", "", ""]);
+        let summary = Summary::new(vec![1.0, 2.0]).unwrap();
+        assert_eq!(
+            Summary::csv_fields(Some(&summary)),
+            ["2", "1.00", "2.00", "1.50", "1.50", "2.00"]
+        );
+    }
+
+    mod report {
+        use serde_json::{Value, json};
+
+        use super::super::*;
+
+        fn participant(id: u64, name: &str, course_role: &str) -> Value {
+            json!({
+                "id": id,
+                "role": "user",
+                "name": name,
+                "avatar": null,
+                "course_role": course_role,
+                "tutorials": {},
+            })
+        }
+
+        /// An endorsed answer by the tutor.
+        fn answer(created_at: &str) -> Value {
+            json!({
+                "id": 10,
+                "user_id": 3,
+                "course_id": 1,
+                "thread_id": 1,
+                "original_id": null,
+                "parent_id": null,
+                "editor_id": null,
+                "number": 1,
+                "type": "answer",
+                "kind": "normal",
+                "content": "<document version=\"2.0\"><paragraph>yes</paragraph></document>",
+                "document": "yes",
+                "flag_count": 0,
+                "vote_count": 0,
+                "is_endorsed": true,
+                "is_anonymous": false,
+                "is_private": false,
+                "is_resolved": false,
+                "created_at": created_at,
+                "updated_at": null,
+                "deleted_at": null,
+                "anonymous_id": 0,
+                "vote": 0,
+                "comments": [],
+            })
+        }
+
+        fn response(
+            id: u64,
+            user_id: u64,
+            thread_type: &str,
+            created_at: &str,
+            unresolved_count: u64,
+            answers: Vec<Value>,
+        ) -> ThreadResponse {
+            serde_json::from_value(json!({
+                "thread": {
+                    "id": id,
+                    "user_id": user_id,
+                    "course_id": 1,
+                    "original_id": null,
+                    "editor_id": null,
+                    "accepted_id": null,
+                    "duplicate_id": null,
+                    "number": id,
+                    "type": thread_type,
+                    "title": "Help",
+                    "content": "<document version=\"2.0\"><paragraph>help</paragraph></document>",
+                    "document": "help",
+                    "category": "General",
+                    "subcategory": "",
+                    "subsubcategory": "",
+                    "flag_count": 0,
+                    "star_count": 0,
+                    "view_count": 0,
+                    "unique_view_count": 0,
+                    "vote_count": 0,
+                    "reply_count": answers.len(),
+                    "unresolved_count": unresolved_count,
+                    "is_locked": false,
+                    "is_pinned": false,
+                    "is_private": false,
+                    "is_endorsed": false,
+                    "is_student_answered": false,
+                    "is_staff_answered": false,
+                    "is_archived": false,
+                    "is_anonymous": false,
+                    "is_megathread": false,
+                    "anonymous_comments": false,
+                    "approved_status": "approved",
+                    "created_at": created_at,
+                    "updated_at": null,
+                    "deleted_at": null,
+                    "pinned_at": null,
+                    "anonymous_id": 0,
+                    "vote": 0,
+                    "is_seen": false,
+                    "is_starred": false,
+                    "is_watched": null,
+                    "glanced_at": null,
+                    "new_reply_count": 0,
+                    "duplicate_title": null,
+                    "answers": answers,
+                    "comments": [],
+                },
+                "users": [
+                    participant(2, "Sam Student", "student"),
+                    participant(3, "Tess Tutor", "tutor"),
+                ],
+            }))
+            .unwrap()
+        }
+
+        fn threads() -> Vec<ThreadResponse> {
+            vec![
+                response(
+                    1,
+                    2,
+                    "question",
+                    "2025-01-06T09:00:00Z",
+                    0,
+                    vec![answer("2025-01-06T09:30:00Z")],
+                ),
+                response(2, 2, "question", "2025-01-07T09:00:00Z", 1, Vec::new()),
+                response(3, 3, "announcement", "2025-01-05T23:00:00Z", 0, Vec::new()),
+            ]
+        }
+
+        #[test]
+        fn computes_report() {
+            let report = ForumReport::new(&threads(), None, &AnalyticsOptions::default()).unwrap();
+
+            let [general] = report.response_times.as_slice() else {
+                panic!("expected one category, got {:?}", report.response_times);
+            };
+            assert_eq!(general.threads, 2);
+            assert_eq!(
+                general.minutes.as_ref().map(|m| (m.count, m.max)),
+                Some((1, 30.0))
+            );
+
+            let unanswered: Vec<_> = report.backlog.unanswered.iter().map(|t| t.id).collect();
+            let unresolved: Vec<_> = report.backlog.unresolved.iter().map(|t| t.id).collect();
+            assert_eq!((unanswered, unresolved), (vec![2], vec![2]));
+
+            let tutor = &report.top_contributors["Tutor"][0];
+            assert_eq!((tutor.threads, tutor.replies, tutor.endorsed), (1, 1, 1));
+            assert_eq!(report.top_contributors["Student"][0].threads, 2);
+
+            assert_eq!(report.endorsements.answers.rate, Some(1.0));
+            assert_eq!(report.endorsements.threads.endorsed, 0);
+            assert_eq!(report.activity.by_weekday[..2], [2, 1]);
+            assert_eq!(report.activity.by_weekday[6], 1);
+        }
+
+        #[test]
+        fn counts_activity_in_time_zone() {
+            let options = AnalyticsOptions {
+                tz: String::from("Australia/Sydney"),
+                ..Default::default()
+            };
+            let report = ForumReport::new(&threads(), None, &options).unwrap();
+            // the announcement was posted on Monday morning in Sydney
+            assert_eq!(report.activity.by_weekday[..2], [3, 1]);
+
+            let options = AnalyticsOptions {
+                tz: String::from("Nowhere/Special"),
+                ..Default::default()
+            };
+            assert!(matches!(
+                ForumReport::new(&threads(), None, &options),
+                Err(crate::Error::Time(_))
+            ));
+        }
+
+        #[test]
+        fn exports_csv() {
+            let report = ForumReport::new(&threads(), None, &AnalyticsOptions::default()).unwrap();
+            assert_eq!(
+                report.to_csv(ReportTable::Endorsements),
+                "kind,total,endorsed,rate\r\n\
+                 threads,3,0,0.0000\r\n\
+                 answers,1,1,1.0000\r\n\
+                 comments,0,0,\r\n"
+            );
+        }
+    }
+}
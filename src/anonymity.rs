@@ -0,0 +1,528 @@
+//! Resolving the authors of anonymous posts, for display and for aggregating activity.
+//!
+//! Ed Discussion reports anonymous authors with a `user_id` of 0 and an
+//! [`AnonymousID`] which is only meaningful within one thread. Staff in courses with
+//! `anonymous_to_staff` set instead see the real `user_id`. An [`AnonymousResolver`] gives each
+//! anonymous author in a thread a stable pseudonym, and, only when told the requesting user may
+//! see through anonymity, their real [`ThreadParticipant`] too. An anonymous post with no
+//! [`AnonymousID`] gets a pseudonym of its own.
+//!
+//! [`AuthorKey`] identifies an author for aggregation: the same student is one key across
+//! threads only when their identity is revealed, so aggregated reports never link anonymous
+//! posts for a user who could not otherwise link them.
+
+use std::collections::HashMap;
+
+use crate::{
+    model::{
+        course::{CourseDiscussionSettings, Role},
+        thread::{AnonymousID, MaybeAnonymousID, Reply, ReplyID, Thread, ThreadID},
+        user::{ThreadParticipant, UserID},
+    },
+    participants::ParticipantIndex,
+};
+
+const ANIMALS: &[&str] = &[
+    "Aardvark",
+    "Albatross",
+    "Alpaca",
+    "Armadillo",
+    "Axolotl",
+    "Badger",
+    "Beaver",
+    "Bison",
+    "Capybara",
+    "Chameleon",
+    "Cheetah",
+    "Chinchilla",
+    "Dingo",
+    "Dolphin",
+    "Echidna",
+    "Ferret",
+    "Flamingo",
+    "Gazelle",
+    "Gecko",
+    "Hedgehog",
+    "Heron",
+    "Ibex",
+    "Jackal",
+    "Kangaroo",
+    "Koala",
+    "Lemur",
+    "Lynx",
+    "Manatee",
+    "Meerkat",
+    "Narwhal",
+    "Ocelot",
+    "Otter",
+    "Pangolin",
+    "Pelican",
+    "Platypus",
+    "Quokka",
+    "Raccoon",
+    "Salamander",
+    "Tapir",
+    "Toucan",
+    "Walrus",
+    "Wombat",
+];
+
+/// FNV-1a, so that pseudonyms do not change between runs or Rust versions.
+fn stable_hash(values: &[u64]) -> u64 {
+    values
+        .iter()
+        .flat_map(|v| v.to_le_bytes())
+        .fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        })
+}
+
+/// Whether a user with `role` can see who posted anonymously in a course with `settings`.
+pub fn can_reveal(role: &Role, settings: &CourseDiscussionSettings) -> bool {
+    role.is_course_admin() && *settings.anonymous_to_staff()
+}
+
+/// A key identifying an author, for grouping their activity.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum AuthorKey {
+    /// A user whose identity is known.
+    User(UserID),
+    /// An anonymous author, who can only be identified within one thread.
+    Anonymous(ThreadID, AnonymousID),
+    /// An anonymous author given no [`AnonymousID`], who can only be identified within one post:
+    /// the thread itself, or one of its replies.
+    AnonymousPost(ThreadID, Option<ReplyID>),
+}
+
+/// Who a pseudonym is for: an anonymous ID, or a single anonymous post without one.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+enum Poster {
+    Anonymous(AnonymousID),
+    Post(Option<ReplyID>),
+}
+
+impl Poster {
+    fn new(anonymous_id: MaybeAnonymousID, reply_id: Option<ReplyID>) -> Self {
+        match anonymous_id.dissolve() {
+            Some(anonymous_id) => Self::Anonymous(anonymous_id),
+            None => Self::Post(reply_id),
+        }
+    }
+}
+
+/// The author of a thread or reply, as resolved by an [`AnonymousResolver`].
+#[derive(Clone, Debug)]
+pub struct ResolvedAuthor {
+    /// For grouping activity by author.
+    pub key: AuthorKey,
+    /// The pseudonym shown for an anonymous post, e.g. "Anonymous Otter".
+    pub pseudonym: Option<String>,
+    /// The real author, if known and visible to the requesting user.
+    pub participant: Option<ThreadParticipant>,
+}
+
+impl ResolvedAuthor {
+    /// Whether the post was anonymous.
+    pub fn is_anonymous(&self) -> bool {
+        self.pseudonym.is_some()
+    }
+
+    /// The name to show: the pseudonym for anonymous posts, unless `reveal` and the real name is
+    /// known, in which case both.
+    pub fn display_name(&self, reveal: bool) -> String {
+        match (&self.pseudonym, &self.participant) {
+            (Some(pseudonym), Some(participant)) if reveal => {
+                format!("{} ({pseudonym})", participant.name())
+            }
+            (Some(pseudonym), _) => pseudonym.clone(),
+            (None, Some(participant)) => participant.name().clone(),
+            (None, None) => String::from("Unknown user"),
+        }
+    }
+}
+
+/// Resolves the authors of one thread and its replies. See the [module docs](self).
+#[derive(Clone, Debug)]
+pub struct AnonymousResolver {
+    thread_id: ThreadID,
+    reveal: bool,
+    pseudonyms: HashMap<Poster, String>,
+    participants: ParticipantIndex,
+}
+
+impl AnonymousResolver {
+    /// A resolver for `thread`, whose participants are `participants`, e.g. from
+    /// [`Client::get_thread`](crate::Client::get_thread). Real identities are only resolved for
+    /// anonymous posts if `reveal`, see [`can_reveal`].
+    pub fn new(thread: &Thread, participants: &[ThreadParticipant], reveal: bool) -> Self {
+        let replies = thread.replies_chronological();
+
+        // in order of first post, so existing pseudonyms never change as replies are added
+        let posters = std::iter::once((
+            *thread.is_anonymous(),
+            Poster::new(*thread.anonymous_id(), None),
+        ))
+        .chain(replies.iter().map(|r| {
+            (
+                *r.is_anonymous(),
+                Poster::new(*r.anonymous_id(), Some(*r.id())),
+            )
+        }))
+        .filter(|(is_anonymous, _)| *is_anonymous)
+        .map(|(_, poster)| poster);
+
+        let thread_id = *thread.id();
+        let mut pseudonyms: HashMap<Poster, String> = HashMap::new();
+        for poster in posters {
+            if pseudonyms.contains_key(&poster) {
+                continue;
+            }
+            let hash = match poster {
+                Poster::Anonymous(anonymous_id) => {
+                    stable_hash(&[thread_id.into(), anonymous_id.dissolve().get()])
+                }
+                // 0 is never an anonymous ID, so these never share a hash with the above
+                Poster::Post(reply_id) => {
+                    stable_hash(&[thread_id.into(), 0, reply_id.map_or(0, u64::from)])
+                }
+            };
+            let animal = ANIMALS[(hash % ANIMALS.len() as u64) as usize];
+
+            let mut pseudonym = format!("Anonymous {animal}");
+            let mut n = 1;
+            while pseudonyms.values().any(|p| *p == pseudonym) {
+                n += 1;
+                pseudonym = format!("Anonymous {animal} {n}");
+            }
+            pseudonyms.insert(poster, pseudonym);
+        }
+
+        Self {
+            thread_id,
+            reveal,
+            pseudonyms,
+            participants: participants.iter().cloned().collect(),
+        }
+    }
+
+    /// Whether real identities are resolved for anonymous posts.
+    pub fn reveals(&self) -> bool {
+        self.reveal
+    }
+
+    fn resolve(
+        &self,
+        user_id: UserID,
+        anonymous_id: MaybeAnonymousID,
+        is_anonymous: bool,
+        reply_id: Option<ReplyID>,
+    ) -> ResolvedAuthor {
+        let participant = self.participants.get(user_id).cloned();
+        if !is_anonymous {
+            return ResolvedAuthor {
+                key: AuthorKey::User(user_id),
+                pseudonym: None,
+                participant,
+            };
+        }
+
+        let poster = Poster::new(anonymous_id, reply_id);
+        let participant = participant.filter(|_| self.reveal);
+        ResolvedAuthor {
+            key: match (&participant, poster) {
+                (Some(participant), _) => AuthorKey::User(*participant.id()),
+                (None, Poster::Anonymous(anonymous_id)) => {
+                    AuthorKey::Anonymous(self.thread_id, anonymous_id)
+                }
+                (None, Poster::Post(reply_id)) => {
+                    AuthorKey::AnonymousPost(self.thread_id, reply_id)
+                }
+            },
+            // only missing for a reply from another thread
+            pseudonym: Some(
+                self.pseudonyms
+                    .get(&poster)
+                    .cloned()
+                    .unwrap_or_else(|| String::from("Anonymous")),
+            ),
+            participant,
+        }
+    }
+
+    /// The author of the thread.
+    pub fn thread_author(&self, thread: &Thread) -> ResolvedAuthor {
+        self.resolve(
+            *thread.user_id(),
+            *thread.anonymous_id(),
+            *thread.is_anonymous(),
+            None,
+        )
+    }
+
+    /// The author of a reply in the thread.
+    pub fn reply_author(&self, reply: &Reply) -> ResolvedAuthor {
+        self.resolve(
+            *reply.user_id(),
+            *reply.anonymous_id(),
+            *reply.is_anonymous(),
+            Some(*reply.id()),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use super::*;
+
+    const THREAD_ID: u64 = 10;
+
+    fn reply(id: u64, user_id: u64, is_anonymous: bool, anonymous_id: u64) -> Value {
+        json!({
+            "id": id,
+            "user_id": user_id,
+            "course_id": 1,
+            "thread_id": THREAD_ID,
+            "original_id": null,
+            "parent_id": null,
+            "editor_id": null,
+            "number": id,
+            "type": "comment",
+            "kind": "normal",
+            "content": "<document version=\"2.0\"><paragraph>hi</paragraph></document>",
+            "document": "hi",
+            "flag_count": 0,
+            "vote_count": 0,
+            "is_endorsed": false,
+            "is_anonymous": is_anonymous,
+            "is_private": false,
+            "is_resolved": false,
+            "created_at": format!("2025-01-{id:02}T00:00:00Z"),
+            "updated_at": null,
+            "deleted_at": null,
+            "anonymous_id": anonymous_id,
+            "vote": 0,
+            "comments": [],
+        })
+    }
+
+    /// A thread posted anonymously by user 2, with `comments` under it.
+    fn thread(comments: Vec<Value>) -> Thread {
+        serde_json::from_value(json!({
+            "id": THREAD_ID,
+            "user_id": 2,
+            "course_id": 1,
+            "original_id": null,
+            "editor_id": null,
+            "accepted_id": null,
+            "duplicate_id": null,
+            "number": 1,
+            "type": "question",
+            "title": "Help",
+            "content": "<document version=\"2.0\"><paragraph>help</paragraph></document>",
+            "document": "help",
+            "category": "General",
+            "subcategory": "",
+            "subsubcategory": "",
+            "flag_count": 0,
+            "star_count": 0,
+            "view_count": 0,
+            "unique_view_count": 0,
+            "vote_count": 0,
+            "reply_count": comments.len(),
+            "unresolved_count": 0,
+            "is_locked": false,
+            "is_pinned": false,
+            "is_private": false,
+            "is_endorsed": false,
+            "is_student_answered": false,
+            "is_staff_answered": false,
+            "is_archived": false,
+            "is_anonymous": true,
+            "is_megathread": false,
+            "anonymous_comments": false,
+            "approved_status": "approved",
+            "created_at": "2025-01-01T00:00:00Z",
+            "updated_at": null,
+            "deleted_at": null,
+            "pinned_at": null,
+            "anonymous_id": 7,
+            "vote": 0,
+            "is_seen": false,
+            "is_starred": false,
+            "is_watched": null,
+            "glanced_at": null,
+            "new_reply_count": 0,
+            "duplicate_title": null,
+            "answers": [],
+            "comments": comments,
+        }))
+        .unwrap()
+    }
+
+    fn participants() -> Vec<ThreadParticipant> {
+        (2..=5)
+            .map(|id| {
+                serde_json::from_value(json!({
+                    "id": id,
+                    "role": "user",
+                    "name": format!("User {id}"),
+                    "avatar": null,
+                    "course_role": "student",
+                    "tutorials": {},
+                }))
+                .unwrap()
+            })
+            .collect()
+    }
+
+    /// user 3 replies openly, user 2 anonymously as before, user 4 anonymously and user 5
+    /// anonymously with no anonymous ID
+    fn replies() -> Vec<Value> {
+        vec![
+            reply(1, 3, false, 0),
+            reply(2, 2, true, 7),
+            reply(3, 4, true, 8),
+            reply(4, 5, true, 0),
+        ]
+    }
+
+    fn authors(thread: &Thread, reveal: bool) -> Vec<ResolvedAuthor> {
+        let resolver = AnonymousResolver::new(thread, &participants(), reveal);
+        std::iter::once(resolver.thread_author(thread))
+            .chain(
+                thread
+                    .replies_chronological()
+                    .into_iter()
+                    .map(|r| resolver.reply_author(r)),
+            )
+            .collect()
+    }
+
+    #[test]
+    fn pseudonyms_are_stable_as_replies_are_added() {
+        let before = authors(&thread(replies()[..2].to_vec()), false);
+        let after = authors(&thread(replies()), false);
+        assert_eq!(before[0].pseudonym, after[0].pseudonym);
+        assert_eq!(after[0].pseudonym, after[2].pseudonym);
+        assert_ne!(after[0].pseudonym, after[3].pseudonym);
+        assert!(after[0].pseudonym.is_some());
+        assert!(after[1].pseudonym.is_none());
+    }
+
+    #[test]
+    fn colliding_pseudonyms_are_numbered() {
+        let animal =
+            |anonymous_id: u64| stable_hash(&[THREAD_ID, anonymous_id]) % ANIMALS.len() as u64;
+        let twin = (8..).find(|&id| animal(id) == animal(7)).unwrap();
+        let thread = thread(vec![reply(1, 3, true, twin)]);
+        let authors = authors(&thread, false);
+        let first = authors[0].pseudonym.clone().unwrap();
+        assert_eq!(authors[1].pseudonym, Some(format!("{first} 2")));
+    }
+
+    #[test]
+    fn hidden_authors_are_keyed_by_thread() {
+        let thread = thread(replies());
+        let thread_id = *thread.id();
+        let authors = authors(&thread, false);
+        for author in [&authors[0], &authors[2], &authors[3], &authors[4]] {
+            assert!(author.participant.is_none());
+            assert!(author.is_anonymous());
+            assert!(!author.display_name(true).contains("User"));
+        }
+        assert_eq!(authors[0].key, authors[2].key);
+        assert!(matches!(authors[0].key, AuthorKey::Anonymous(id, _) if id == thread_id));
+        // no anonymous ID, so only this post is known to be theirs
+        let last = *thread.replies_chronological()[3].id();
+        assert_eq!(
+            authors[4].key,
+            AuthorKey::AnonymousPost(thread_id, Some(last))
+        );
+        assert_ne!(authors[4].pseudonym, authors[3].pseudonym);
+
+        // the open reply is still named
+        assert!(matches!(authors[1].key, AuthorKey::User(_)));
+        assert_eq!(authors[1].display_name(false), "User 3");
+    }
+
+    #[test]
+    fn revealed_authors_are_keyed_by_user() {
+        let authors = authors(&thread(replies()), true);
+        let names: Vec<_> = authors
+            .iter()
+            .map(|a| a.participant.as_ref().map(|p| p.name().as_str()))
+            .collect();
+        assert_eq!(
+            names,
+            [
+                Some("User 2"),
+                Some("User 3"),
+                Some("User 2"),
+                Some("User 4"),
+                Some("User 5"),
+            ]
+        );
+        assert_eq!(authors[0].key, authors[2].key);
+        assert!(authors.iter().all(|a| matches!(a.key, AuthorKey::User(_))));
+        assert!(
+            authors[0]
+                .display_name(true)
+                .starts_with("User 2 (Anonymous ")
+        );
+    }
+
+    #[test]
+    fn only_course_admins_reveal_when_allowed() {
+        let settings = |anonymous_to_staff: bool| -> CourseDiscussionSettings {
+            serde_json::from_value(json!({
+                "private": true,
+                "private_threads_only": false,
+                "anonymous_comments": true,
+                "anonymous_comments_override": false,
+                "anonymous": true,
+                "anonymous_to_staff": anonymous_to_staff,
+                "threads_require_approval": false,
+                "unread_indicator_hidden": false,
+                "deleted": false,
+                "categories": [],
+                "thread_templates_enabled": false,
+                "category_unselected": false,
+                "default_snippet_language": "python",
+                "rejection_comment_template": null,
+                "bot_enabled": false,
+                "bot_enabled_v2": false,
+                "bot_name": "",
+                "bot_avatar": "",
+                "full_announcement_emails": false,
+                "no_digests": false,
+                "digest_interval": null,
+                "saved_replies_enabled": false,
+                "saved_replies": [],
+                "sortable_feed": true,
+                "default_feed_sort": "new",
+                "thread_numbers": true,
+                "comment_numbers": true,
+                "tutorial_badge_visible_to_all": false,
+                "tutorial_badge_visible_anon": false,
+                "readonly": false,
+                "show_all_pinned_threads": false,
+                "comment_endorsements": true,
+            }))
+            .unwrap()
+        };
+        let (allowed, disallowed) = (settings(true), settings(false));
+        for (role, reveals) in [
+            (Role::Student, false),
+            (Role::Mentor, false),
+            (Role::Tutor, false),
+            (Role::Staff, true),
+            (Role::Admin, true),
+            (Role::Other(String::from("guest")), false),
+        ] {
+            assert_eq!(can_reveal(&role, &allowed), reveals, "{role:?}");
+            assert!(!can_reveal(&role, &disallowed), "{role:?}");
+        }
+    }
+}
@@ -0,0 +1,58 @@
+//! Relays course activity from Ed Discussion to webhooks, as configured in a TOML file; see
+//! `edstem::relay`.
+//!
+//! Usage: `edstem-relay <config.toml>`. Reads an API token from the `ED_API_TOKEN` environment
+//! variable; request one at https://edstem.org/us/settings/api-tokens.
+
+use std::process::ExitCode;
+
+use edstem::relay::{Relay, RelayConfig};
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let Ok(token) = std::env::var("ED_API_TOKEN") else {
+        eprintln!(
+            "set ED_API_TOKEN to an API token from https://edstem.org/us/settings/api-tokens"
+        );
+        return ExitCode::FAILURE;
+    };
+    let Some(path) = std::env::args().nth(1) else {
+        eprintln!("usage: edstem-relay <config.toml>");
+        return ExitCode::FAILURE;
+    };
+
+    let config = match std::fs::read_to_string(&path)
+        .map_err(edstem::Error::from)
+        .and_then(|toml| RelayConfig::from_toml(&toml))
+    {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{path}: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    Relay::new(edstem::Client::new(&token), config)
+        .run(|report| {
+            for (course_id, e) in &report.errors {
+                eprintln!("course {course_id}: {e}");
+            }
+            for (url, event_id) in &report.delivered {
+                eprintln!("delivered {event_id} to {url}");
+            }
+            for failure in &report.retrying {
+                eprintln!(
+                    "failed to deliver {} to {} (attempt {}): {}; will retry",
+                    failure.event_id, failure.url, failure.attempts, failure.reason
+                );
+            }
+            for failure in &report.dropped {
+                eprintln!(
+                    "dropped {} for {} after {} attempts: {}",
+                    failure.event_id, failure.url, failure.attempts, failure.reason
+                );
+            }
+        })
+        .await;
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,24 @@
+//! Terminal client for Ed Discussion.
+//!
+//! Reads an API token from the `ED_API_TOKEN` environment variable; request one at
+//! https://edstem.org/us/settings/api-tokens.
+
+use std::process::ExitCode;
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let Ok(token) = std::env::var("ED_API_TOKEN") else {
+        eprintln!(
+            "set ED_API_TOKEN to an API token from https://edstem.org/us/settings/api-tokens"
+        );
+        return ExitCode::FAILURE;
+    };
+
+    match edstem::tui::run(edstem::Client::new(&token)).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+    }
+}
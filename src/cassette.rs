@@ -0,0 +1,480 @@
+//! Record and replay of HTTP interactions ("cassettes"), for deterministic tests against real
+//! Ed Discussion responses.
+//!
+//! A [`Cassette`] is [`Middleware`] which, given to
+//! [`ClientOptions::middleware`](crate::ClientOptions::middleware), either records every request
+//! the client makes, along with the response, to a JSON file, or replays a previously recorded
+//! file without touching the network. Recorded query parameters and bodies have the client's
+//! token and any email addresses redacted, so cassettes are safe to commit. Bodies which are not
+//! text, e.g. downloaded files, are stored base64-encoded and are not redacted.
+//!
+//! Requests are matched on method, path and query parameters. Each recorded interaction is
+//! replayed at most once, in recording order; a request with no unused match fails with
+//! [`Error::Cassette`](crate::Error::Cassette) rather than falling through to the network.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use reqwest::{
+    StatusCode,
+    header::{AUTHORIZATION, CONTENT_TYPE, HeaderMap, HeaderValue},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::transport::{BoxFuture, Middleware, Next, Request, Response};
+
+/// Replaces email addresses in recorded bodies.
+pub const REDACTED_EMAIL: &str = "redacted@example.com";
+/// Replaces the client's token in recorded bodies.
+pub const REDACTED_TOKEN: &str = "REDACTED";
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+struct RecordedRequest {
+    method: String,
+    path: String,
+    query: Vec<(String, String)>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    body: Option<Value>,
+}
+
+/// How a recorded body is stored.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum Encoding {
+    /// parsed JSON
+    #[default]
+    Json,
+    /// a string holding UTF-8 text which is not JSON
+    Text,
+    /// a string holding the base64 of a body which is not UTF-8
+    Base64,
+}
+
+impl Encoding {
+    fn is_json(&self) -> bool {
+        *self == Self::Json
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct RecordedResponse {
+    status: u16,
+    body: Value,
+    #[serde(default, skip_serializing_if = "Encoding::is_json")]
+    encoding: Encoding,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct Interaction {
+    request: RecordedRequest,
+    response: RecordedResponse,
+    #[serde(skip)]
+    used: bool,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+struct CassetteFile {
+    interactions: Vec<Interaction>,
+}
+
+#[derive(Debug)]
+enum Mode {
+    Record,
+    Replay,
+}
+
+#[derive(Debug)]
+struct Inner {
+    path: PathBuf,
+    mode: Mode,
+    file: CassetteFile,
+}
+
+/// A cassette file, either being recorded or replayed. See the [module docs](self).
+///
+/// Clones share the same recording, so a cassette can be kept for inspection after being handed
+/// to a client.
+#[derive(Clone, Debug)]
+pub struct Cassette {
+    inner: Arc<Mutex<Inner>>,
+}
+
+fn is_local_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "._%+-".contains(c)
+}
+
+fn is_domain_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || ".-".contains(c)
+}
+
+/// Replace anything shaped like `local@domain.tld` in `text` with [`REDACTED_EMAIL`].
+fn redact_emails(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(at) = rest.find('@') {
+        let local_start = rest[..at]
+            .char_indices()
+            .rev()
+            .take_while(|(_, c)| is_local_char(*c))
+            .last()
+            .map(|(i, _)| i)
+            .unwrap_or(at);
+        let domain_len = rest[at + 1..]
+            .char_indices()
+            .take_while(|(_, c)| is_domain_char(*c))
+            .last()
+            .map(|(i, c)| i + c.len_utf8())
+            .unwrap_or(0);
+        let domain = rest[at + 1..at + 1 + domain_len].trim_end_matches('.');
+
+        if local_start < at && domain.contains('.') {
+            out.push_str(&rest[..local_start]);
+            out.push_str(REDACTED_EMAIL);
+            rest = &rest[at + 1 + domain.len()..];
+        } else {
+            out.push_str(&rest[..=at]);
+            rest = &rest[at + 1..];
+        }
+    }
+
+    out.push_str(rest);
+    out
+}
+
+/// Replace email addresses and `token` in `text`.
+fn redact_text(text: &str, token: &str) -> String {
+    let redacted = redact_emails(text);
+    if token.is_empty() {
+        redacted
+    } else {
+        redacted.replace(token, REDACTED_TOKEN)
+    }
+}
+
+fn redact(value: &mut Value, token: &str) {
+    match value {
+        Value::String(s) => *s = redact_text(s, token),
+        Value::Array(items) => items.iter_mut().for_each(|v| redact(v, token)),
+        Value::Object(map) => map.values_mut().for_each(|v| redact(v, token)),
+        Value::Null | Value::Bool(_) | Value::Number(_) => {}
+    }
+}
+
+fn recorded_body(bytes: &[u8], token: &str) -> (Value, Encoding) {
+    if let Ok(mut value) = serde_json::from_slice::<Value>(bytes) {
+        redact(&mut value, token);
+        return (value, Encoding::Json);
+    }
+    match std::str::from_utf8(bytes) {
+        Ok(text) => (Value::String(redact_text(text, token)), Encoding::Text),
+        Err(_) => (Value::String(BASE64.encode(bytes)), Encoding::Base64),
+    }
+}
+
+/// The request as recorded and matched, with its query redacted so that it matches whatever
+/// token is replayed with.
+fn describe(request: &Request) -> RecordedRequest {
+    let token = token(request);
+    RecordedRequest {
+        method: request.method.to_string(),
+        path: String::from(request.url.path()),
+        query: request
+            .url
+            .query_pairs()
+            .map(|(key, value)| (key.into_owned(), redact_text(&value, &token)))
+            .collect(),
+        body: request
+            .body
+            .as_deref()
+            .and_then(|b| serde_json::from_slice(b).ok()),
+    }
+}
+
+/// The bearer token a request is authorized with, to redact from what is recorded.
+fn token(request: &Request) -> String {
+    request
+        .headers
+        .get(AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(String::from)
+        .unwrap_or_default()
+}
+
+impl Cassette {
+    /// Start a new recording which will be written to `path`, replacing any existing file.
+    ///
+    /// The file is rewritten after every request, so nothing is lost if the program stops early.
+    pub fn record(path: impl AsRef<Path>) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Inner {
+                path: path.as_ref().to_path_buf(),
+                mode: Mode::Record,
+                file: CassetteFile::default(),
+            })),
+        }
+    }
+
+    /// Load a recording from `path` to replay.
+    pub fn replay(path: impl AsRef<Path>) -> crate::Result<Self> {
+        let file = serde_json::from_slice(&fs::read(path.as_ref())?)?;
+        Ok(Self {
+            inner: Arc::new(Mutex::new(Inner {
+                path: path.as_ref().to_path_buf(),
+                mode: Mode::Replay,
+                file,
+            })),
+        })
+    }
+
+    /// Replay `path` if it exists, otherwise record to it. Delete the file to re-record.
+    pub fn record_or_replay(path: impl AsRef<Path>) -> crate::Result<Self> {
+        if path.as_ref().exists() {
+            Self::replay(path)
+        } else {
+            Ok(Self::record(path))
+        }
+    }
+
+    /// The number of interactions in the cassette.
+    pub fn len(&self) -> usize {
+        self.inner
+            .lock()
+            .expect("cassette poisoned")
+            .file
+            .interactions
+            .len()
+    }
+
+    /// Whether the cassette has no interactions.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The number of interactions not yet replayed, e.g. to check that a test made every request
+    /// it was recorded making.
+    pub fn unused(&self) -> usize {
+        let inner = self.inner.lock().expect("cassette poisoned");
+        match inner.mode {
+            Mode::Record => 0,
+            Mode::Replay => inner.file.interactions.iter().filter(|i| !i.used).count(),
+        }
+    }
+
+    fn replay_one(&self, request: &RecordedRequest) -> crate::Result<Response> {
+        let mut inner = self.inner.lock().expect("cassette poisoned");
+        let path = inner.path.display().to_string();
+        let Some(interaction) = inner.file.interactions.iter_mut().find(|i| {
+            !i.used
+                && i.request.method == request.method
+                && i.request.path == request.path
+                && i.request.query == request.query
+        }) else {
+            return Err(crate::Error::Cassette(format!(
+                "no unused interaction in {path} matches {} {} {:?}",
+                request.method, request.path, request.query,
+            )));
+        };
+        interaction.used = true;
+
+        let response = &interaction.response;
+        let body = match (&response.body, response.encoding) {
+            (Value::String(s), Encoding::Text) => s.clone().into_bytes(),
+            (Value::String(s), Encoding::Base64) => BASE64
+                .decode(s)
+                .map_err(|e| crate::Error::Cassette(format!("bad base64 body in {path}: {e}")))?,
+            (value, _) => serde_json::to_vec(value)?,
+        };
+        let status = StatusCode::from_u16(response.status)
+            .map_err(|e| crate::Error::Cassette(e.to_string()))?;
+
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        Ok(Response {
+            status,
+            headers,
+            body,
+        })
+    }
+
+    async fn record_one(&self, request: Request, next: Next<'_>) -> crate::Result<Response> {
+        let token = token(&request);
+        let mut described = describe(&request);
+        let response = next.run(request).await?;
+
+        let (recorded, encoding) = recorded_body(&response.body, &token);
+        if let Some(ref mut body) = described.body {
+            redact(body, &token);
+        }
+
+        let mut inner = self.inner.lock().expect("cassette poisoned");
+        inner.file.interactions.push(Interaction {
+            request: described,
+            response: RecordedResponse {
+                status: response.status.as_u16(),
+                body: recorded,
+                encoding,
+            },
+            used: false,
+        });
+        fs::write(&inner.path, serde_json::to_vec_pretty(&inner.file)?)?;
+        Ok(response)
+    }
+}
+
+impl Middleware for Cassette {
+    fn handle<'a>(
+        &'a self,
+        request: Request,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, crate::Result<Response>> {
+        Box::pin(async move {
+            let replaying = matches!(
+                self.inner.lock().expect("cassette poisoned").mode,
+                Mode::Replay
+            );
+
+            if replaying {
+                self.replay_one(&describe(&request))
+            } else {
+                self.record_one(request, next).await
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn redacts_emails() {
+        assert_eq!(
+            redact_emails("mail jane.doe+ed@cs.example.edu, or bob@example.com."),
+            "mail redacted@example.com, or redacted@example.com."
+        );
+    }
+
+    #[test]
+    fn leaves_other_at_signs() {
+        for text in ["@Jane Doe", "user@localhost", "a @ b.com", "trailing@"] {
+            assert_eq!(redact_emails(text), text);
+        }
+    }
+
+    #[test]
+    fn redacts_token_and_emails_throughout() {
+        let mut value = json!({
+            "user": {"email": "jane@example.edu", "name": "Jane"},
+            "notes": ["token secret-token here", 3, null],
+        });
+        redact(&mut value, "secret-token");
+        assert_eq!(
+            value,
+            json!({
+                "user": {"email": REDACTED_EMAIL, "name": "Jane"},
+                "notes": ["token REDACTED here", 3, null],
+            })
+        );
+    }
+
+    #[test]
+    fn keeps_raw_bodies_as_strings() {
+        assert_eq!(
+            recorded_body(b"print('jane@example.edu')", ""),
+            (json!("print('redacted@example.com')"), Encoding::Text)
+        );
+        assert_eq!(
+            recorded_body(b"{\"a\": 1}", ""),
+            (json!({"a": 1}), Encoding::Json)
+        );
+    }
+
+    #[test]
+    fn replays_binary_bodies_unchanged() {
+        let bytes = [0x89, b'P', b'N', b'G', 0xff, 0x00];
+        let (body, encoding) = recorded_body(&bytes, "");
+        assert_eq!(encoding, Encoding::Base64);
+
+        let mut recorded = interaction("/api/workspaces/1/files/raw", body);
+        recorded.response.encoding = encoding;
+        let request = recorded.request.clone();
+        let cassette = replaying(vec![recorded]);
+        assert_eq!(cassette.replay_one(&request).unwrap().body, bytes);
+    }
+
+    #[test]
+    fn redacts_queries() {
+        let mut request = Request {
+            method: reqwest::Method::GET,
+            url: reqwest::Url::parse(
+                "http://localhost/api/users?email=jane@example.edu&token=secret-token&limit=1",
+            )
+            .unwrap(),
+            headers: HeaderMap::new(),
+            body: None,
+        };
+        request.headers.insert(
+            AUTHORIZATION,
+            HeaderValue::from_static("Bearer secret-token"),
+        );
+        let query: Vec<_> = describe(&request)
+            .query
+            .into_iter()
+            .map(|(_, value)| value)
+            .collect();
+        assert_eq!(query, [REDACTED_EMAIL, REDACTED_TOKEN, "1"]);
+    }
+
+    fn replaying(interactions: Vec<Interaction>) -> Cassette {
+        Cassette {
+            inner: Arc::new(Mutex::new(Inner {
+                path: PathBuf::from("test.json"),
+                mode: Mode::Replay,
+                file: CassetteFile { interactions },
+            })),
+        }
+    }
+
+    fn interaction(path: &str, body: Value) -> Interaction {
+        Interaction {
+            request: RecordedRequest {
+                method: String::from("GET"),
+                path: String::from(path),
+                query: Vec::new(),
+                body: None,
+            },
+            response: RecordedResponse {
+                status: 200,
+                body,
+                encoding: Encoding::Json,
+            },
+            used: false,
+        }
+    }
+
+    #[test]
+    fn replays_each_interaction_once_in_order() {
+        let cassette = replaying(vec![
+            interaction("/api/user", json!({"n": 1})),
+            interaction("/api/user", json!({"n": 2})),
+        ]);
+        let request = interaction("/api/user", Value::Null).request;
+
+        assert_eq!(cassette.replay_one(&request).unwrap().body, b"{\"n\":1}");
+        assert_eq!(cassette.unused(), 1);
+        assert_eq!(cassette.replay_one(&request).unwrap().body, b"{\"n\":2}");
+        assert!(matches!(
+            cassette.replay_one(&request),
+            Err(crate::Error::Cassette(_))
+        ));
+    }
+}
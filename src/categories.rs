@@ -0,0 +1,605 @@
+//! Declarative management of a course's discussion categories.
+//!
+//! Describe the category tree a course should have as [`DesiredCategories`], e.g. loaded from a
+//! file kept in version control, then [`plan`](DesiredCategories::plan) the changes needed to get
+//! there from the live tree and [`apply`](CategoryPlan::apply) them. Applying refuses to remove or
+//! rename a category which still has threads unless forced, as Ed Discussion does not move them:
+//! they would be left filed under a category which no longer exists. Fields of existing
+//! categories which this crate does not know about are kept as they are.
+//!
+//! In JSON, a tree looks like:
+//!
+//! ```json
+//! {
+//!   "categories": [
+//!     { "name": "General" },
+//!     {
+//!       "name": "Assignments",
+//!       "subcategories": [{ "name": "A1" }, { "name": "A2", "renamed_from": "Assignment 2" }]
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! and in TOML, with the `toml` feature:
+//!
+//! ```toml
+//! [[categories]]
+//! name = "General"
+//!
+//! [[categories]]
+//! name = "Assignments"
+//! subcategories = [{ name = "A1" }, { name = "A2", renamed_from = "Assignment 2" }]
+//! ```
+
+use std::fmt;
+
+use serde::Deserialize;
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+use crate::{
+    Client,
+    model::{course::Category, thread::PartialThread},
+    opts::{CategorySpec, CourseDiscussionSettingsPatch},
+};
+
+/// A category as it should be, within [`DesiredCategories`]. Unknown fields are rejected, so
+/// that a misspelt `renamed_from` fails rather than removing the category.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[serde(deny_unknown_fields)]
+pub struct DesiredCategory {
+    /// The category's name, unique among its siblings.
+    pub name: String,
+    /// The category's current name, if it should be renamed to `name` rather than replaced.
+    #[serde(default)]
+    pub renamed_from: Option<String>,
+    /// Subcategories, in display order.
+    #[serde(default)]
+    pub subcategories: Vec<DesiredCategory>,
+    /// Text to prefill new threads in this category with.
+    #[serde(default)]
+    pub thread_template: Option<String>,
+}
+
+impl From<&DesiredCategory> for CategorySpec {
+    fn from(value: &DesiredCategory) -> Self {
+        Self {
+            name: value.name.clone(),
+            subcategories: value.subcategories.iter().map(Self::from).collect(),
+            thread_template: value.thread_template.clone(),
+            unknown_fields: Default::default(),
+        }
+    }
+}
+
+/// The whole category tree a course should have. Categories will be in the order given.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[serde(deny_unknown_fields)]
+pub struct DesiredCategories {
+    /// Top-level categories, in display order.
+    pub categories: Vec<DesiredCategory>,
+}
+
+/// A category's location in the tree, from the top-level category down.
+pub type CategoryPath = Vec<String>;
+
+fn display_path(path: &CategoryPath) -> String {
+    path.join(" / ")
+}
+
+/// A single change in a [`CategoryPlan`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CategoryChange {
+    /// A new category, along with any subcategories.
+    Add {
+        /// Where the category will be.
+        path: CategoryPath,
+    },
+    /// An existing category to remove, along with any subcategories.
+    Remove {
+        /// The category's current location.
+        path: CategoryPath,
+        /// How many threads are in the category or its subcategories.
+        threads: u64,
+    },
+    /// An existing category to rename, along with the paths of any subcategories.
+    Rename {
+        /// The category's current location.
+        path: CategoryPath,
+        /// Its new name.
+        to: String,
+        /// How many threads are in the category or its subcategories, which will be left under
+        /// the old name.
+        threads: u64,
+    },
+    /// Existing categories which will be put in a different order.
+    Reorder {
+        /// The location of the categories' parent; empty for the top level.
+        parent: CategoryPath,
+    },
+    /// An existing category whose thread template will change.
+    SetTemplate {
+        /// The category's current location.
+        path: CategoryPath,
+        /// The current template.
+        from: Option<String>,
+        /// The new template.
+        to: Option<String>,
+    },
+}
+
+impl fmt::Display for CategoryChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Add { path } => write!(f, "add {}", display_path(path)),
+            Self::Remove { path, threads } => {
+                write!(f, "remove {} ({threads} threads)", display_path(path))
+            }
+            Self::Rename { path, to, threads } => {
+                write!(
+                    f,
+                    "rename {} to {to} ({threads} threads)",
+                    display_path(path)
+                )
+            }
+            Self::Reorder { parent } if parent.is_empty() => f.write_str("reorder categories"),
+            Self::Reorder { parent } => {
+                write!(f, "reorder subcategories of {}", display_path(parent))
+            }
+            Self::SetTemplate { path, .. } => {
+                write!(f, "change thread template of {}", display_path(path))
+            }
+        }
+    }
+}
+
+/// The changes needed to turn a course's categories into [`DesiredCategories`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CategoryPlan {
+    /// Each change, parents before their subcategories.
+    pub changes: Vec<CategoryChange>,
+    desired: Vec<CategorySpec>,
+}
+
+/// The category path of a thread, without empty trailing levels.
+fn thread_path(thread: &PartialThread) -> Vec<&str> {
+    [
+        thread.category(),
+        thread.subcategory(),
+        thread.subsubcategory(),
+    ]
+    .into_iter()
+    .map(String::as_str)
+    .take_while(|c| !c.is_empty())
+    .collect()
+}
+
+fn count_threads(threads: &[Vec<&str>], path: &CategoryPath) -> u64 {
+    threads
+        .iter()
+        .filter(|t| t.len() >= path.len() && t.iter().zip(path).all(|(a, b)| *a == b))
+        .count() as u64
+}
+
+/// Push the changes from `current` to `desired` under `parent` onto `changes`, returning the
+/// categories to send.
+fn diff_level(
+    current: &[Category],
+    desired: &[DesiredCategory],
+    parent: &CategoryPath,
+    threads: &[Vec<&str>],
+    changes: &mut Vec<CategoryChange>,
+) -> Vec<CategorySpec> {
+    let mut specs = Vec::with_capacity(desired.len());
+    let mut matched = vec![false; current.len()];
+    // indices into `current` of the categories kept, in their new order
+    let mut kept = Vec::new();
+    let child = |name: &str| {
+        let mut path = parent.clone();
+        path.push(String::from(name));
+        path
+    };
+
+    for want in desired {
+        let unmatched = |name: &str| {
+            current
+                .iter()
+                .enumerate()
+                .position(|(i, c)| c.name() == name && !matched[i])
+        };
+        let existing =
+            unmatched(&want.name).or_else(|| want.renamed_from.as_deref().and_then(unmatched));
+
+        let Some(i) = existing else {
+            changes.push(CategoryChange::Add {
+                path: child(&want.name),
+            });
+            specs.push(CategorySpec::from(want));
+            continue;
+        };
+        matched[i] = true;
+        kept.push(i);
+
+        let have = &current[i];
+        let path = child(have.name());
+        if *have.name() != want.name {
+            changes.push(CategoryChange::Rename {
+                path: path.clone(),
+                to: want.name.clone(),
+                threads: count_threads(threads, &path),
+            });
+        }
+        if *have.thread_template() != want.thread_template {
+            changes.push(CategoryChange::SetTemplate {
+                path: path.clone(),
+                from: have.thread_template().clone(),
+                to: want.thread_template.clone(),
+            });
+        }
+        let subcategories = diff_level(
+            have.subcategories(),
+            &want.subcategories,
+            &path,
+            threads,
+            changes,
+        );
+        specs.push(CategorySpec {
+            name: want.name.clone(),
+            subcategories,
+            thread_template: want.thread_template.clone(),
+            ..CategorySpec::from(have)
+        });
+    }
+
+    if !kept.is_sorted() {
+        changes.push(CategoryChange::Reorder {
+            parent: parent.clone(),
+        });
+    }
+    for (have, _) in current.iter().zip(matched).filter(|(_, m)| !m) {
+        let path = child(have.name());
+        changes.push(CategoryChange::Remove {
+            threads: count_threads(threads, &path),
+            path,
+        });
+    }
+    specs
+}
+
+impl DesiredCategories {
+    /// Parse a tree from JSON.
+    pub fn from_json(json: &str) -> crate::Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Parse a tree from TOML.
+    #[cfg(feature = "toml")]
+    pub fn from_toml(toml: &str) -> crate::Result<Self> {
+        Ok(toml::from_str(toml)?)
+    }
+
+    /// The tree as it would be sent to Ed Discussion for a course with no categories yet. See
+    /// [`CategoryPlan::as_patch`] for changing existing ones.
+    pub fn to_specs(&self) -> Vec<CategorySpec> {
+        self.categories.iter().map(CategorySpec::from).collect()
+    }
+
+    /// Compute the changes from `current`, given the course's `threads` to count what removals
+    /// and renames would orphan.
+    pub fn diff(&self, current: &[Category], threads: &[PartialThread]) -> CategoryPlan {
+        let threads: Vec<_> = threads.iter().map(thread_path).collect();
+        let mut changes = Vec::new();
+        let desired = diff_level(
+            current,
+            &self.categories,
+            &Vec::new(),
+            &threads,
+            &mut changes,
+        );
+
+        CategoryPlan { changes, desired }
+    }
+
+    /// Fetch a course's categories and threads and compute the changes to reach this tree.
+    pub async fn plan(
+        &self,
+        client: &Client,
+        course_id: impl Into<u64>,
+    ) -> crate::Result<CategoryPlan> {
+        let course_id = course_id.into();
+        let course = client.get_course(course_id).await?;
+        let threads = client.get_all_course_threads(course_id, None).await?;
+        Ok(self.diff(course.settings().discussion().categories(), &threads))
+    }
+}
+
+impl CategoryPlan {
+    /// Whether the categories already match.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Removals and renames of categories which still have threads, which
+    /// [`apply`](Self::apply) refuses unless forced.
+    pub fn blocked(&self) -> impl Iterator<Item = &CategoryChange> {
+        self.changes.iter().filter(|c| {
+            matches!(
+                c,
+                CategoryChange::Remove { threads, .. } | CategoryChange::Rename { threads, .. }
+                    if *threads > 0
+            )
+        })
+    }
+
+    /// The settings patch which carries out the plan.
+    pub fn as_patch(&self) -> CourseDiscussionSettingsPatch {
+        CourseDiscussionSettingsPatch {
+            categories: Some(self.desired.clone()),
+            ..Default::default()
+        }
+    }
+
+    /// Carry out the plan on a course. Staff only.
+    ///
+    /// Unless `force` is set, fails with [`Error::Refused`](crate::Error::Refused) without
+    /// changing anything if a category with threads would be removed or renamed.
+    pub async fn apply(
+        &self,
+        client: &Client,
+        course_id: impl Into<u64>,
+        force: bool,
+    ) -> crate::Result<()> {
+        let blocked: Vec<_> = self.blocked().map(ToString::to_string).collect();
+        if !force && !blocked.is_empty() {
+            return Err(crate::Error::Refused(blocked.join(", ")));
+        }
+        if self.is_empty() {
+            return Ok(());
+        }
+
+        client
+            .update_course_settings(course_id, &self.as_patch())
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn current() -> Vec<Category> {
+        serde_json::from_value(json!([
+            {"name": "General", "subcategories": [], "thread_template": null, "colour": "red"},
+            {
+                "name": "Assignments",
+                "subcategories": [
+                    {"name": "A1", "subcategories": [], "thread_template": null},
+                    {"name": "Assignment 2", "subcategories": [], "thread_template": null},
+                ],
+                "thread_template": null,
+            },
+            {"name": "Social", "subcategories": [], "thread_template": null},
+        ]))
+        .unwrap()
+    }
+
+    /// A thread filed under `path`.
+    fn thread(path: &[&str]) -> PartialThread {
+        let level = |i: usize| path.get(i).copied().unwrap_or_default();
+        serde_json::from_value(json!({
+            "id": 1,
+            "user_id": 1,
+            "course_id": 1,
+            "original_id": null,
+            "editor_id": null,
+            "accepted_id": null,
+            "duplicate_id": null,
+            "number": 1,
+            "type": "question",
+            "title": "Help",
+            "content": "<document version=\"2.0\"><paragraph>help</paragraph></document>",
+            "document": "help",
+            "category": level(0),
+            "subcategory": level(1),
+            "subsubcategory": level(2),
+            "flag_count": 0,
+            "star_count": 0,
+            "view_count": 0,
+            "unique_view_count": 0,
+            "vote_count": 0,
+            "reply_count": 0,
+            "unresolved_count": 0,
+            "is_locked": false,
+            "is_pinned": false,
+            "is_private": false,
+            "is_endorsed": false,
+            "is_answered": false,
+            "is_student_answered": false,
+            "is_staff_answered": false,
+            "is_archived": false,
+            "is_anonymous": false,
+            "is_megathread": false,
+            "anonymous_comments": false,
+            "approved_status": "approved",
+            "created_at": "2025-01-01T00:00:00Z",
+            "updated_at": null,
+            "deleted_at": null,
+            "pinned_at": null,
+            "anonymous_id": 0,
+            "vote": 0,
+            "is_seen": false,
+            "is_starred": false,
+            "is_watched": null,
+            "glanced_at": null,
+            "new_reply_count": 0,
+            "duplicate_title": null,
+            "user": null,
+        }))
+        .unwrap()
+    }
+
+    fn plan(desired: serde_json::Value, threads: &[&[&str]]) -> CategoryPlan {
+        let desired = DesiredCategories::from_json(&desired.to_string()).unwrap();
+        let threads: Vec<PartialThread> = threads.iter().map(|t| thread(t)).collect();
+        desired.diff(&current(), &threads)
+    }
+
+    fn path(names: &[&str]) -> CategoryPath {
+        names.iter().map(|n| String::from(*n)).collect()
+    }
+
+    #[test]
+    fn unchanged_tree_is_empty() {
+        let plan = plan(
+            json!({"categories": [
+                {"name": "General"},
+                {"name": "Assignments", "subcategories": [{"name": "A1"}, {"name": "Assignment 2"}]},
+                {"name": "Social"},
+            ]}),
+            &[],
+        );
+        assert!(plan.is_empty());
+    }
+
+    #[test]
+    fn diffs_adds_removals_renames_and_templates() {
+        let plan = plan(
+            json!({"categories": [
+                {"name": "General", "thread_template": "Describe your problem."},
+                {
+                    "name": "Assignments",
+                    "subcategories": [{"name": "A2", "renamed_from": "Assignment 2"}, {"name": "A3"}],
+                },
+                {"name": "Exams"},
+            ]}),
+            &[&["Assignments", "A1"], &["Assignments", "A1"], &["Social"]],
+        );
+
+        assert_eq!(
+            plan.changes,
+            [
+                CategoryChange::SetTemplate {
+                    path: path(&["General"]),
+                    from: None,
+                    to: Some(String::from("Describe your problem.")),
+                },
+                CategoryChange::Rename {
+                    path: path(&["Assignments", "Assignment 2"]),
+                    to: String::from("A2"),
+                    threads: 0,
+                },
+                CategoryChange::Add {
+                    path: path(&["Assignments", "A3"]),
+                },
+                CategoryChange::Remove {
+                    path: path(&["Assignments", "A1"]),
+                    threads: 2,
+                },
+                CategoryChange::Add {
+                    path: path(&["Exams"]),
+                },
+                CategoryChange::Remove {
+                    path: path(&["Social"]),
+                    threads: 1,
+                },
+            ]
+        );
+        assert_eq!(plan.blocked().count(), 2);
+    }
+
+    #[test]
+    fn renames_with_threads_are_blocked() {
+        let plan = plan(
+            json!({"categories": [
+                {"name": "Chat", "renamed_from": "General"},
+                {"name": "Assignments", "subcategories": [{"name": "A1"}, {"name": "Assignment 2"}]},
+                {"name": "Social"},
+            ]}),
+            &[&["General"]],
+        );
+
+        let blocked: Vec<_> = plan.blocked().map(ToString::to_string).collect();
+        assert_eq!(blocked, ["rename General to Chat (1 threads)"]);
+    }
+
+    #[test]
+    fn keeps_unknown_fields_of_existing_categories() {
+        let plan = plan(
+            json!({"categories": [
+                {"name": "Chat", "renamed_from": "General"},
+                {"name": "Assignments", "subcategories": [{"name": "A1"}, {"name": "Assignment 2"}]},
+            ]}),
+            &[],
+        );
+
+        let sent = serde_json::to_value(plan.as_patch().categories.unwrap()).unwrap();
+        assert_eq!(
+            sent[0],
+            json!({"name": "Chat", "subcategories": [], "thread_template": null, "colour": "red"})
+        );
+        assert_eq!(sent[1]["subcategories"][1]["name"], "Assignment 2");
+        assert_eq!(sent.as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn reorders_are_changes() {
+        let plan = plan(
+            json!({"categories": [
+                {"name": "Social"},
+                {"name": "General"},
+                {"name": "Assignments", "subcategories": [{"name": "Assignment 2"}, {"name": "A1"}]},
+            ]}),
+            &[],
+        );
+
+        let changes: Vec<_> = plan.changes.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            changes,
+            ["reorder subcategories of Assignments", "reorder categories"]
+        );
+        let sent = serde_json::to_value(plan.as_patch().categories.unwrap()).unwrap();
+        assert_eq!(sent[0]["name"], "Social");
+        assert_eq!(sent[2]["subcategories"][0]["name"], "Assignment 2");
+    }
+
+    #[test]
+    fn renames_fall_back_when_the_name_is_taken() {
+        // the old "General" is already renamed, so the new one must come from "Social"
+        let plan = plan(
+            json!({"categories": [
+                {"name": "Chat", "renamed_from": "General"},
+                {"name": "Assignments", "subcategories": [{"name": "A1"}, {"name": "Assignment 2"}]},
+                {"name": "General", "renamed_from": "Social"},
+            ]}),
+            &[],
+        );
+
+        assert_eq!(
+            plan.changes,
+            [
+                CategoryChange::Rename {
+                    path: path(&["General"]),
+                    to: String::from("Chat"),
+                    threads: 0,
+                },
+                CategoryChange::Rename {
+                    path: path(&["Social"]),
+                    to: String::from("General"),
+                    threads: 0,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_fields() {
+        let typo = r#"{"categories": [{"name": "Chat", "renamed_form": "General"}]}"#;
+        assert!(DesiredCategories::from_json(typo).is_err());
+    }
+}
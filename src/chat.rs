@@ -0,0 +1,107 @@
+//! Whole chat histories, and exporting them.
+//!
+//! ```no_run
+//! # async fn example(client: edstem::Client) -> edstem::Result<()> {
+//! use edstem::chat::ChannelExport;
+//!
+//! for channel in client.get_chat_channels(1234u64).await? {
+//!     let history = client.get_all_chat_messages(*channel.id()).await?;
+//!     let export = ChannelExport::new(channel.name(), &history);
+//!     std::fs::write(format!("{}.md", channel.name()), export.to_markdown())?;
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use serde::Serialize;
+
+use crate::{
+    document::Document,
+    model::{chat::ChatMessage, user::ThreadParticipant},
+    participants::ParticipantIndex,
+};
+
+/// Every message in a channel or conversation, from
+/// [`Client::get_all_chat_messages`](crate::Client::get_all_chat_messages).
+#[derive(Clone, Debug, Default)]
+pub struct ChatHistory {
+    /// The messages, oldest first.
+    pub messages: Vec<ChatMessage>,
+    /// The authors of the messages.
+    pub users: Vec<ThreadParticipant>,
+}
+
+/// A message in a [`ChannelExport`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ExportedMessage {
+    /// The message ID.
+    pub id: u64,
+    /// The author's user ID.
+    pub user_id: u64,
+    /// The author's name, if known.
+    pub author: Option<String>,
+    /// When the message was sent.
+    pub created_at: String,
+    /// When the message was last edited, if ever.
+    pub updated_at: Option<String>,
+    /// The message as Markdown.
+    pub text: String,
+}
+
+/// A channel's history in a form for archiving outside Ed.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ChannelExport {
+    /// The channel's name.
+    pub channel: String,
+    /// Every message which has not been deleted, oldest first.
+    pub messages: Vec<ExportedMessage>,
+}
+
+impl ChannelExport {
+    /// Export `history`, of a channel called `channel`.
+    pub fn new(channel: &str, history: &ChatHistory) -> Self {
+        let users: ParticipantIndex = history.users.iter().cloned().collect();
+
+        Self {
+            channel: String::from(channel),
+            messages: history
+                .messages
+                .iter()
+                .filter(|m| m.deleted_at().is_none())
+                .map(|m| ExportedMessage {
+                    id: u64::from(*m.id()),
+                    user_id: u64::from(*m.user_id()),
+                    author: users.get(*m.user_id()).map(|u| u.name().clone()),
+                    created_at: m.created_at().clone(),
+                    updated_at: m.updated_at().clone(),
+                    text: Document::parse(m.content())
+                        .map(|d| d.to_markdown())
+                        .unwrap_or_else(|_| m.document().clone()),
+                })
+                .collect(),
+        }
+    }
+
+    /// The export as JSON.
+    pub fn to_json(&self) -> crate::Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// The export as a Markdown transcript.
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!("# {}\n", self.channel);
+        for message in &self.messages {
+            let author = message.author.as_deref().unwrap_or("Unknown user");
+            let edited = if message.updated_at.is_some() {
+                " (edited)"
+            } else {
+                ""
+            };
+            out.push_str(&format!(
+                "\n**{author}** {}{edited}\n\n{}\n",
+                message.created_at, message.text
+            ));
+        }
+        out
+    }
+}
@@ -0,0 +1,42 @@
+//! Just enough CSV writing for the exports in this crate.
+
+/// Append a row to `out`, quoting fields as RFC 4180 requires.
+pub(crate) fn write_row<I, S>(out: &mut String, fields: I)
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    for (i, field) in fields.into_iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        let field = field.as_ref();
+        if field.contains([',', '"', '\n', '\r']) {
+            out.push('"');
+            out.push_str(&field.replace('"', "\"\""));
+            out.push('"');
+        } else {
+            out.push_str(field);
+        }
+    }
+    out.push_str("\r\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quotes_fields_only_when_needed() {
+        let mut out = String::new();
+        write_row(
+            &mut out,
+            ["plain", "with, comma", "say \"hi\"", "two\nlines", ""],
+        );
+        write_row(&mut out, Vec::<String>::new());
+        assert_eq!(
+            out,
+            "plain,\"with, comma\",\"say \"\"hi\"\"\",\"two\nlines\",\r\n\r\n"
+        );
+    }
+}
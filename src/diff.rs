@@ -0,0 +1,231 @@
+//! Line diffs, e.g. between the expected and actual output of a challenge test case.
+//!
+//! ```no_run
+//! # async fn example(client: edstem::Client, submission: u64) -> edstem::Result<()> {
+//! use edstem::diff;
+//!
+//! let submission = client.get_submission(submission).await?;
+//! for result in submission.failures() {
+//!     println!("{} failed", result.name());
+//!     if let Some(lines) = result.diff() {
+//!         print!("{}", diff::render(&lines));
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fmt;
+
+/// A line of a diff from some old text to some new text.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DiffLine {
+    /// In both.
+    Same(String),
+    /// Only in the old text, e.g. expected but not output.
+    Removed(String),
+    /// Only in the new text, e.g. output but not expected.
+    Added(String),
+}
+
+impl fmt::Display for DiffLine {
+    /// The line prefixed with `" "`, `"-"` or `"+"`, as in a unified diff.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Same(line) => write!(f, " {line}"),
+            Self::Removed(line) => write!(f, "-{line}"),
+            Self::Added(line) => write!(f, "+{line}"),
+        }
+    }
+}
+
+/// The shortest line diff turning `old` into `new`, with removals before additions where lines
+/// were replaced.
+///
+/// This takes time proportional to the product of the texts' lengths, not counting lines they
+/// share at the start and end, but only linear space, so long outputs which differ throughout
+/// are slow to diff rather than exhausting memory.
+pub fn lines(old: &str, new: &str) -> Vec<DiffLine> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // matching lines at either end can always be kept, so only the middle needs diffing
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let (old_mid, new_mid) = (
+        &old[prefix..old.len() - suffix],
+        &new[prefix..new.len() - suffix],
+    );
+
+    let mut common = Vec::new();
+    common_lines(old_mid, new_mid, (0, 0), &mut common);
+
+    let mut ret = Vec::with_capacity(old.len().max(new.len()));
+    ret.extend(
+        old[..prefix]
+            .iter()
+            .map(|l| DiffLine::Same(String::from(*l))),
+    );
+    let (mut i, mut j) = (0, 0);
+    for (next_i, next_j) in common.into_iter().chain([(old_mid.len(), new_mid.len())]) {
+        ret.extend(
+            old_mid[i..next_i]
+                .iter()
+                .map(|l| DiffLine::Removed(String::from(*l))),
+        );
+        ret.extend(
+            new_mid[j..next_j]
+                .iter()
+                .map(|l| DiffLine::Added(String::from(*l))),
+        );
+        if let Some(line) = old_mid.get(next_i) {
+            ret.push(DiffLine::Same(String::from(*line)));
+        }
+        (i, j) = (next_i + 1, next_j + 1);
+    }
+    ret.extend(
+        old[old.len() - suffix..]
+            .iter()
+            .map(|l| DiffLine::Same(String::from(*l))),
+    );
+    ret
+}
+
+/// The lengths of the longest common subsequences of `old` and each prefix of `new`, keeping
+/// only one row of the usual table at a time.
+fn lcs_lengths<'a>(old: impl Iterator<Item = &'a str>, new: &[&'a str]) -> Vec<usize> {
+    let mut row = vec![0; new.len() + 1];
+    let mut next = row.clone();
+    for line in old {
+        for (j, other) in new.iter().enumerate() {
+            next[j + 1] = if line == *other {
+                row[j] + 1
+            } else {
+                row[j + 1].max(next[j])
+            };
+        }
+        std::mem::swap(&mut row, &mut next);
+    }
+    row
+}
+
+/// Push the positions of a longest common subsequence of `old` and `new` onto `out` in order,
+/// offset by `at`, by Hirschberg's algorithm.
+fn common_lines(old: &[&str], new: &[&str], at: (usize, usize), out: &mut Vec<(usize, usize)>) {
+    if old.is_empty() || new.is_empty() {
+        return;
+    }
+    if let [line] = old {
+        if let Some(j) = new.iter().position(|l| l == line) {
+            out.push((at.0, at.1 + j));
+        }
+        return;
+    }
+
+    // split `new` where the best subsequence crosses the middle of `old`
+    let mid = old.len() / 2;
+    let forward = lcs_lengths(old[..mid].iter().copied(), new);
+    let reversed: Vec<&str> = new.iter().rev().copied().collect();
+    let backward = lcs_lengths(old[mid..].iter().rev().copied(), &reversed);
+    let split = (0..=new.len())
+        .max_by_key(|j| (forward[*j] + backward[new.len() - j], std::cmp::Reverse(*j)))
+        .expect("range is not empty");
+
+    common_lines(&old[..mid], &new[..split], at, out);
+    common_lines(&old[mid..], &new[split..], (at.0 + mid, at.1 + split), out);
+}
+
+/// Whether a diff has any changes.
+pub fn has_changes(lines: &[DiffLine]) -> bool {
+    lines.iter().any(|l| !matches!(l, DiffLine::Same(_)))
+}
+
+/// A diff as text, one prefixed line per line.
+pub fn render(lines: &[DiffLine]) -> String {
+    lines.iter().map(|l| format!("{l}\n")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diff(old: &str, new: &str) -> String {
+        render(&lines(old, new))
+    }
+
+    #[test]
+    fn identical_texts_have_no_changes() {
+        let lines = lines("a\nb\n", "a\nb");
+        assert_eq!(
+            lines,
+            [DiffLine::Same("a".into()), DiffLine::Same("b".into())]
+        );
+        assert!(!has_changes(&lines));
+        assert!(super::lines("", "").is_empty());
+    }
+
+    #[test]
+    fn additions_and_removals() {
+        assert_eq!(diff("", "a\nb"), "+a\n+b\n");
+        assert_eq!(diff("a\nb", ""), "-a\n-b\n");
+        assert_eq!(diff("a\nc", "a\nb\nc"), " a\n+b\n c\n");
+        assert_eq!(diff("a\nb\nc", "a\nc"), " a\n-b\n c\n");
+    }
+
+    #[test]
+    fn replacements_remove_before_adding() {
+        assert_eq!(diff("a\nb\nc", "a\nx\nc"), " a\n-b\n+x\n c\n");
+        assert_eq!(diff("1\n2", "3\n4"), "-1\n-2\n+3\n+4\n");
+    }
+
+    #[test]
+    fn diffs_are_shortest() {
+        let old = "a\nb\nc\na\nb\nb\na";
+        let new = "c\nb\na\nb\na\nc";
+        let lines = lines(old, new);
+        let same = lines
+            .iter()
+            .filter(|l| matches!(l, DiffLine::Same(_)))
+            .count();
+        assert_eq!(same, 4);
+
+        // applying the diff gives back both texts
+        let side = |keep: fn(&DiffLine) -> Option<&String>| {
+            lines
+                .iter()
+                .filter_map(keep)
+                .cloned()
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+        assert_eq!(
+            side(|l| match l {
+                DiffLine::Same(s) | DiffLine::Removed(s) => Some(s),
+                DiffLine::Added(_) => None,
+            }),
+            old
+        );
+        assert_eq!(
+            side(|l| match l {
+                DiffLine::Same(s) | DiffLine::Added(s) => Some(s),
+                DiffLine::Removed(_) => None,
+            }),
+            new
+        );
+    }
+
+    #[test]
+    fn long_texts() {
+        let old: String = (0..100_000).map(|i| format!("{i}\n")).collect();
+        let new = old.replace("\n50000\n", "\nfifty thousand\n");
+        let lines = lines(&old, &new);
+        assert_eq!(lines.len(), 100_001);
+        assert_eq!(lines[50_000], DiffLine::Removed("50000".into()));
+        assert_eq!(lines[50_001], DiffLine::Added("fifty thousand".into()));
+    }
+}
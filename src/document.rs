@@ -0,0 +1,849 @@
+//! The XML dialect in which Ed Discussion stores post bodies ("documents").
+//!
+//! A [`Document`] can be parsed from the `content` field of a thread or reply, rendered to plain
+//! text or Markdown, and written back out to XML for posting. Elements this module does not
+//! understand are kept as an [`Element`] so that a parse/write round trip loses nothing.
+
+use std::fmt::{self, Display, Write};
+
+use quick_xml::{
+    Reader,
+    errors::IllFormedError,
+    escape::{escape, resolve_predefined_entity},
+    events::{BytesStart, Event},
+};
+
+#[cfg(feature = "markdown")]
+mod markdown;
+
+/// The document format version this module reads and writes.
+pub const DOCUMENT_VERSION: &str = "2.0";
+
+/// A generic XML node, used to preserve elements with no typed representation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Node {
+    /// An element and its children.
+    Element(Element),
+    /// Unescaped character data.
+    Text(String),
+}
+
+/// A generic XML element.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Element {
+    /// The tag name, e.g. `paragraph`.
+    pub name: String,
+    /// Attributes in document order, unescaped.
+    pub attributes: Vec<(String, String)>,
+    /// Child nodes in document order.
+    pub children: Vec<Node>,
+}
+
+impl Element {
+    fn new(name: &str) -> Self {
+        Self {
+            name: String::from(name),
+            ..Default::default()
+        }
+    }
+
+    fn with_attribute(mut self, key: &str, value: impl Into<String>) -> Self {
+        self.attributes.push((String::from(key), value.into()));
+        self
+    }
+
+    fn with_children(mut self, children: Vec<Node>) -> Self {
+        self.children = children;
+        self
+    }
+
+    /// Get the value of an attribute by name, if present.
+    pub fn attribute(&self, key: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// The concatenation of all text beneath this element.
+    pub fn text(&self) -> String {
+        let mut out = String::new();
+        for child in &self.children {
+            match child {
+                Node::Text(t) => out.push_str(t),
+                Node::Element(e) => out.push_str(&e.text()),
+            }
+        }
+        out
+    }
+
+    fn child_elements(&self) -> impl Iterator<Item = &Element> {
+        self.children.iter().filter_map(|c| match c {
+            Node::Element(e) => Some(e),
+            Node::Text(_) => None,
+        })
+    }
+
+    fn write_xml(&self, out: &mut String) {
+        out.push('<');
+        out.push_str(&self.name);
+        for (k, v) in &self.attributes {
+            let _ = write!(out, " {}=\"{}\"", k, escape(v.as_str()));
+        }
+        if self.children.is_empty() {
+            out.push_str("/>");
+            return;
+        }
+        out.push('>');
+        for child in &self.children {
+            match child {
+                Node::Text(t) => out.push_str(&escape(t.as_str())),
+                Node::Element(e) => e.write_xml(out),
+            }
+        }
+        let _ = write!(out, "</{}>", self.name);
+    }
+}
+
+/// The style of a [`Block::List`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ListStyle {
+    /// An unordered list.
+    Bullet,
+    /// An ordered list.
+    Number,
+    /// Some other style, by its name in the document.
+    Other(String),
+}
+
+impl ListStyle {
+    fn as_str(&self) -> &str {
+        match self {
+            Self::Bullet => "bullet",
+            Self::Number => "number",
+            Self::Other(s) => s,
+        }
+    }
+}
+
+/// The flavour of a [`Block::Callout`], which determines its colour in the UI.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CalloutKind {
+    /// Blue, "info".
+    Info,
+    /// Green, "success".
+    Success,
+    /// Yellow, "warning".
+    Warning,
+    /// Red, "error".
+    Error,
+    /// Some other kind, by its name in the document.
+    Other(String),
+}
+
+impl CalloutKind {
+    fn as_str(&self) -> &str {
+        match self {
+            Self::Info => "info",
+            Self::Success => "success",
+            Self::Warning => "warning",
+            Self::Error => "error",
+            Self::Other(s) => s,
+        }
+    }
+}
+
+/// Inline content, i.e. text and its formatting.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Inline {
+    /// Plain text.
+    Text(String),
+    /// `<bold>`
+    Bold(Vec<Inline>),
+    /// `<italic>`
+    Italic(Vec<Inline>),
+    /// `<underline>`
+    Underline(Vec<Inline>),
+    /// `<strike>`
+    Strike(Vec<Inline>),
+    /// Inline `<code>`.
+    Code(String),
+    /// A hyperlink.
+    Link {
+        /// The link target.
+        href: String,
+        /// The link text.
+        content: Vec<Inline>,
+    },
+    /// Inline LaTeX.
+    Math(String),
+    /// An @-mention of a user, which notifies them when posted.
+    Mention {
+        /// The mentioned user's ID.
+        user_id: u64,
+        /// The name shown, without the `@`.
+        name: String,
+    },
+    /// A hard line break.
+    Break,
+    /// An element with no typed representation.
+    Unknown(Element),
+}
+
+/// Block-level content of a [`Document`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Block {
+    /// A paragraph of inline content.
+    Paragraph(Vec<Inline>),
+    /// A heading, where `level` is 1 for the largest.
+    Heading {
+        /// The heading level.
+        level: u8,
+        /// The heading text.
+        content: Vec<Inline>,
+    },
+    /// A list, where each item is itself a sequence of blocks.
+    List {
+        /// Bulleted or numbered.
+        style: ListStyle,
+        /// The list items.
+        items: Vec<Vec<Block>>,
+    },
+    /// Preformatted text.
+    Pre(String),
+    /// A code snippet with syntax highlighting.
+    Snippet {
+        /// The language for highlighting, e.g. `"py"`; may be empty.
+        language: String,
+        /// Whether the snippet can be run from the UI.
+        runnable: bool,
+        /// The code itself.
+        content: String,
+    },
+    /// A coloured callout box.
+    Callout {
+        /// The callout's colour.
+        kind: CalloutKind,
+        /// The blocks inside the callout.
+        content: Vec<Block>,
+    },
+    /// An image in a `<figure>`, referencing a static URL.
+    Image {
+        /// The image URL.
+        src: String,
+        /// The display width, if set.
+        width: Option<u32>,
+        /// The display height, if set.
+        height: Option<u32>,
+    },
+    /// A spoiler, hidden until clicked.
+    Spoiler(Vec<Block>),
+    /// An element with no typed representation.
+    Unknown(Element),
+}
+
+/// A parsed post body.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Document {
+    /// The top-level blocks.
+    pub blocks: Vec<Block>,
+}
+
+fn is_blank(node: &Node) -> bool {
+    matches!(node, Node::Text(t) if t.trim().is_empty())
+}
+
+fn element_from_start(start: &BytesStart) -> crate::Result<Element> {
+    let mut element = Element::new(&String::from_utf8_lossy(start.name().as_ref()));
+    for attr in start.attributes() {
+        let attr = attr.map_err(quick_xml::Error::from)?;
+        element.attributes.push((
+            String::from_utf8_lossy(attr.key.as_ref()).into_owned(),
+            attr.unescape_value()?.into_owned(),
+        ));
+    }
+    Ok(element)
+}
+
+fn parse_tree(xml: &str) -> crate::Result<Element> {
+    let mut reader = Reader::from_str(xml);
+    let mut stack = vec![Element::new("")];
+
+    fn push_text(stack: &mut [Element], text: &str) {
+        let top = stack.last_mut().expect("root is never popped");
+        if let Some(Node::Text(t)) = top.children.last_mut() {
+            t.push_str(text);
+        } else {
+            top.children.push(Node::Text(String::from(text)));
+        }
+    }
+
+    loop {
+        match reader.read_event()? {
+            Event::Start(start) => stack.push(element_from_start(&start)?),
+            Event::Empty(start) => {
+                let element = element_from_start(&start)?;
+                stack
+                    .last_mut()
+                    .expect("root is never popped")
+                    .children
+                    .push(Node::Element(element));
+            }
+            Event::End(_) => {
+                // the reader checks that end tags match, so this is never the root
+                let done = stack.pop().expect("end tag without start");
+                stack
+                    .last_mut()
+                    .expect("root is never popped")
+                    .children
+                    .push(Node::Element(done));
+            }
+            Event::Text(text) => push_text(
+                &mut stack,
+                &text.xml_content().map_err(quick_xml::Error::from)?,
+            ),
+            Event::CData(data) => push_text(
+                &mut stack,
+                &data.xml_content().map_err(quick_xml::Error::from)?,
+            ),
+            Event::GeneralRef(reference) => {
+                if let Some(c) = reference.resolve_char_ref()? {
+                    push_text(&mut stack, c.encode_utf8(&mut [0; 4]));
+                } else {
+                    let name = reference.xml_content().map_err(quick_xml::Error::from)?;
+                    push_text(
+                        &mut stack,
+                        resolve_predefined_entity(&name).unwrap_or_default(),
+                    );
+                }
+            }
+            Event::Eof => {
+                if stack.len() > 1 {
+                    let open = stack.pop().expect("checked above").name;
+                    return Err(
+                        quick_xml::Error::IllFormed(IllFormedError::MissingEndTag(open)).into(),
+                    );
+                }
+                break;
+            }
+            Event::Comment(_) | Event::Decl(_) | Event::PI(_) | Event::DocType(_) => {}
+        }
+    }
+
+    Ok(stack.pop().expect("root is never popped"))
+}
+
+fn parse_inlines(nodes: &[Node]) -> Vec<Inline> {
+    nodes
+        .iter()
+        .map(|node| match node {
+            Node::Text(t) => Inline::Text(t.clone()),
+            Node::Element(e) => match e.name.as_str() {
+                "bold" => Inline::Bold(parse_inlines(&e.children)),
+                "italic" => Inline::Italic(parse_inlines(&e.children)),
+                "underline" => Inline::Underline(parse_inlines(&e.children)),
+                "strike" => Inline::Strike(parse_inlines(&e.children)),
+                "code" => Inline::Code(e.text()),
+                "link" => Inline::Link {
+                    href: String::from(e.attribute("href").unwrap_or_default()),
+                    content: parse_inlines(&e.children),
+                },
+                "math" => Inline::Math(e.text()),
+                "mention" => match e.attribute("id").and_then(|id| id.parse().ok()) {
+                    Some(user_id) => Inline::Mention {
+                        user_id,
+                        name: e.text(),
+                    },
+                    None => Inline::Unknown(e.clone()),
+                },
+                "break" => Inline::Break,
+                _ => Inline::Unknown(e.clone()),
+            },
+        })
+        .collect()
+}
+
+fn parse_blocks(nodes: &[Node]) -> Vec<Block> {
+    nodes
+        .iter()
+        .filter(|n| !is_blank(n))
+        .map(|node| match node {
+            // stray text at block level is treated as its own paragraph
+            Node::Text(t) => Block::Paragraph(vec![Inline::Text(t.clone())]),
+            Node::Element(e) => parse_block(e),
+        })
+        .collect()
+}
+
+fn parse_block(e: &Element) -> Block {
+    match e.name.as_str() {
+        "paragraph" => Block::Paragraph(parse_inlines(&e.children)),
+        "heading" => Block::Heading {
+            level: e
+                .attribute("level")
+                .and_then(|l| l.parse().ok())
+                .unwrap_or(1),
+            content: parse_inlines(&e.children),
+        },
+        "list" => Block::List {
+            style: match e.attribute("style") {
+                Some("bullet") | None => ListStyle::Bullet,
+                Some("number") => ListStyle::Number,
+                Some(other) => ListStyle::Other(String::from(other)),
+            },
+            items: e
+                .child_elements()
+                .map(|item| parse_blocks(&item.children))
+                .collect(),
+        },
+        "pre" => Block::Pre(e.text()),
+        "snippet" => Block::Snippet {
+            language: String::from(e.attribute("language").unwrap_or_default()),
+            runnable: e.attribute("runnable") == Some("true"),
+            content: e
+                .child_elements()
+                .find(|c| c.name == "snippet-content")
+                .map(Element::text)
+                .unwrap_or_default(),
+        },
+        "callout" => Block::Callout {
+            kind: match e.attribute("type") {
+                Some("info") | None => CalloutKind::Info,
+                Some("success") => CalloutKind::Success,
+                Some("warning") => CalloutKind::Warning,
+                Some("error") => CalloutKind::Error,
+                Some(other) => CalloutKind::Other(String::from(other)),
+            },
+            content: parse_blocks(&e.children),
+        },
+        "figure" => match e.child_elements().find(|c| c.name == "image") {
+            Some(image) => Block::Image {
+                src: String::from(image.attribute("src").unwrap_or_default()),
+                width: image.attribute("width").and_then(|w| w.parse().ok()),
+                height: image.attribute("height").and_then(|h| h.parse().ok()),
+            },
+            None => Block::Unknown(e.clone()),
+        },
+        "spoiler" => Block::Spoiler(parse_blocks(&e.children)),
+        _ => Block::Unknown(e.clone()),
+    }
+}
+
+fn inlines_to_nodes(inlines: &[Inline]) -> Vec<Node> {
+    inlines
+        .iter()
+        .map(|inline| match inline {
+            Inline::Text(t) => Node::Text(t.clone()),
+            Inline::Bold(c) => {
+                Node::Element(Element::new("bold").with_children(inlines_to_nodes(c)))
+            }
+            Inline::Italic(c) => {
+                Node::Element(Element::new("italic").with_children(inlines_to_nodes(c)))
+            }
+            Inline::Underline(c) => {
+                Node::Element(Element::new("underline").with_children(inlines_to_nodes(c)))
+            }
+            Inline::Strike(c) => {
+                Node::Element(Element::new("strike").with_children(inlines_to_nodes(c)))
+            }
+            Inline::Code(t) => {
+                Node::Element(Element::new("code").with_children(vec![Node::Text(t.clone())]))
+            }
+            Inline::Link { href, content } => Node::Element(
+                Element::new("link")
+                    .with_attribute("href", href.clone())
+                    .with_children(inlines_to_nodes(content)),
+            ),
+            Inline::Math(t) => {
+                Node::Element(Element::new("math").with_children(vec![Node::Text(t.clone())]))
+            }
+            Inline::Mention { user_id, name } => Node::Element(
+                Element::new("mention")
+                    .with_attribute("id", user_id.to_string())
+                    .with_children(vec![Node::Text(name.clone())]),
+            ),
+            Inline::Break => Node::Element(Element::new("break")),
+            Inline::Unknown(e) => Node::Element(e.clone()),
+        })
+        .collect()
+}
+
+fn blocks_to_nodes(blocks: &[Block]) -> Vec<Node> {
+    blocks
+        .iter()
+        .map(|b| Node::Element(block_to_element(b)))
+        .collect()
+}
+
+fn block_to_element(block: &Block) -> Element {
+    match block {
+        Block::Paragraph(c) => Element::new("paragraph").with_children(inlines_to_nodes(c)),
+        Block::Heading { level, content } => Element::new("heading")
+            .with_attribute("level", level.to_string())
+            .with_children(inlines_to_nodes(content)),
+        Block::List { style, items } => Element::new("list")
+            .with_attribute("style", style.as_str())
+            .with_children(
+                items
+                    .iter()
+                    .map(|item| {
+                        Node::Element(
+                            Element::new("list-item").with_children(blocks_to_nodes(item)),
+                        )
+                    })
+                    .collect(),
+            ),
+        Block::Pre(t) => Element::new("pre").with_children(vec![Node::Text(t.clone())]),
+        Block::Snippet {
+            language,
+            runnable,
+            content,
+        } => Element::new("snippet")
+            .with_attribute("language", language.clone())
+            .with_attribute("runnable", runnable.to_string())
+            .with_children(vec![Node::Element(
+                Element::new("snippet-content").with_children(vec![Node::Text(content.clone())]),
+            )]),
+        Block::Callout { kind, content } => Element::new("callout")
+            .with_attribute("type", kind.as_str())
+            .with_children(blocks_to_nodes(content)),
+        Block::Image { src, width, height } => {
+            let mut image = Element::new("image").with_attribute("src", src.clone());
+            if let Some(w) = width {
+                image = image.with_attribute("width", w.to_string());
+            }
+            if let Some(h) = height {
+                image = image.with_attribute("height", h.to_string());
+            }
+            Element::new("figure").with_children(vec![Node::Element(image)])
+        }
+        Block::Spoiler(c) => Element::new("spoiler").with_children(blocks_to_nodes(c)),
+        Block::Unknown(e) => e.clone(),
+    }
+}
+
+fn inlines_plain(inlines: &[Inline], out: &mut String) {
+    for inline in inlines {
+        match inline {
+            Inline::Text(t) | Inline::Code(t) | Inline::Math(t) => out.push_str(t),
+            Inline::Bold(c) | Inline::Italic(c) | Inline::Underline(c) | Inline::Strike(c) => {
+                inlines_plain(c, out)
+            }
+            Inline::Link { content, .. } => inlines_plain(content, out),
+            Inline::Mention { name, .. } => {
+                let _ = write!(out, "@{name}");
+            }
+            Inline::Break => out.push('\n'),
+            Inline::Unknown(e) => out.push_str(&e.text()),
+        }
+    }
+}
+
+fn inlines_markdown(inlines: &[Inline], out: &mut String) {
+    for inline in inlines {
+        match inline {
+            Inline::Text(t) => out.push_str(t),
+            Inline::Bold(c) => {
+                out.push_str("**");
+                inlines_markdown(c, out);
+                out.push_str("**");
+            }
+            Inline::Italic(c) => {
+                out.push('_');
+                inlines_markdown(c, out);
+                out.push('_');
+            }
+            // Markdown has no underline; keep the text
+            Inline::Underline(c) => inlines_markdown(c, out),
+            Inline::Strike(c) => {
+                out.push_str("~~");
+                inlines_markdown(c, out);
+                out.push_str("~~");
+            }
+            Inline::Code(t) => {
+                let _ = write!(out, "`{t}`");
+            }
+            Inline::Link { href, content } => {
+                out.push('[');
+                inlines_markdown(content, out);
+                let _ = write!(out, "]({href})");
+            }
+            Inline::Math(t) => {
+                let _ = write!(out, "${t}$");
+            }
+            Inline::Mention { name, .. } => {
+                let _ = write!(out, "@{name}");
+            }
+            Inline::Break => out.push_str("  \n"),
+            Inline::Unknown(e) => out.push_str(&e.text()),
+        }
+    }
+}
+
+fn inlines_mentions<'a>(inlines: &'a [Inline], out: &mut Vec<(u64, &'a str)>) {
+    for inline in inlines {
+        match inline {
+            Inline::Bold(c) | Inline::Italic(c) | Inline::Underline(c) | Inline::Strike(c) => {
+                inlines_mentions(c, out)
+            }
+            Inline::Link { content, .. } => inlines_mentions(content, out),
+            Inline::Mention { user_id, name } => out.push((*user_id, name)),
+            _ => {}
+        }
+    }
+}
+
+fn blocks_mentions<'a>(blocks: &'a [Block], out: &mut Vec<(u64, &'a str)>) {
+    for block in blocks {
+        match block {
+            Block::Paragraph(c) | Block::Heading { content: c, .. } => inlines_mentions(c, out),
+            Block::List { items, .. } => items.iter().for_each(|item| blocks_mentions(item, out)),
+            Block::Callout { content, .. } | Block::Spoiler(content) => {
+                blocks_mentions(content, out)
+            }
+            _ => {}
+        }
+    }
+}
+
+fn inlines_rewrite_text(inlines: &mut Vec<Inline>, f: &mut impl FnMut(String) -> Vec<Inline>) {
+    *inlines = std::mem::take(inlines)
+        .into_iter()
+        .flat_map(|inline| match inline {
+            Inline::Text(t) => f(t),
+            Inline::Bold(mut c) => {
+                inlines_rewrite_text(&mut c, f);
+                vec![Inline::Bold(c)]
+            }
+            Inline::Italic(mut c) => {
+                inlines_rewrite_text(&mut c, f);
+                vec![Inline::Italic(c)]
+            }
+            Inline::Underline(mut c) => {
+                inlines_rewrite_text(&mut c, f);
+                vec![Inline::Underline(c)]
+            }
+            Inline::Strike(mut c) => {
+                inlines_rewrite_text(&mut c, f);
+                vec![Inline::Strike(c)]
+            }
+            // link text, code and maths are left as written
+            other => vec![other],
+        })
+        .collect();
+}
+
+fn blocks_rewrite_text(blocks: &mut [Block], f: &mut impl FnMut(String) -> Vec<Inline>) {
+    for block in blocks {
+        match block {
+            Block::Paragraph(c) | Block::Heading { content: c, .. } => inlines_rewrite_text(c, f),
+            Block::List { items, .. } => {
+                for item in items {
+                    blocks_rewrite_text(item, f);
+                }
+            }
+            Block::Callout { content, .. } | Block::Spoiler(content) => {
+                blocks_rewrite_text(content, f)
+            }
+            _ => {}
+        }
+    }
+}
+
+fn indent(text: &str, first: &str, rest: &str) -> String {
+    let mut out = String::new();
+    for (i, line) in text.lines().enumerate() {
+        out.push_str(if i == 0 { first } else { rest });
+        out.push_str(line);
+        out.push('\n');
+    }
+    out
+}
+
+fn blocks_markdown(blocks: &[Block], plain: bool) -> String {
+    let inlines = |content: &[Inline]| {
+        let mut out = String::new();
+        if plain {
+            inlines_plain(content, &mut out);
+        } else {
+            inlines_markdown(content, &mut out);
+        }
+        out
+    };
+
+    let mut parts = Vec::new();
+    for block in blocks {
+        parts.push(match block {
+            Block::Paragraph(c) => inlines(c),
+            Block::Heading { level, content } if !plain => {
+                format!(
+                    "{} {}",
+                    "#".repeat(usize::from(*level).max(1)),
+                    inlines(content)
+                )
+            }
+            Block::Heading { content, .. } => inlines(content),
+            Block::List { style, items } => {
+                let mut out = String::new();
+                for (i, item) in items.iter().enumerate() {
+                    let marker = match style {
+                        ListStyle::Number => format!("{}. ", i + 1),
+                        _ => String::from("- "),
+                    };
+                    let body = blocks_markdown(item, plain);
+                    out.push_str(&indent(&body, &marker, &" ".repeat(marker.len())));
+                }
+                String::from(out.trim_end())
+            }
+            Block::Pre(t) if !plain => format!("```\n{t}\n```"),
+            Block::Snippet {
+                language, content, ..
+            } if !plain => {
+                format!("```{language}\n{content}\n```")
+            }
+            Block::Pre(t) | Block::Snippet { content: t, .. } => t.clone(),
+            Block::Callout { content, .. } if !plain => {
+                String::from(indent(&blocks_markdown(content, plain), "> ", "> ").trim_end())
+            }
+            Block::Callout { content, .. } | Block::Spoiler(content) => {
+                blocks_markdown(content, plain)
+            }
+            Block::Image { src, .. } if !plain => format!("![]({src})"),
+            Block::Image { src, .. } => src.clone(),
+            Block::Unknown(e) => e.text(),
+        });
+    }
+    parts.join("\n\n")
+}
+
+impl Document {
+    /// Parse a document from the XML found in the `content` field of threads and replies.
+    pub fn parse(xml: &str) -> crate::Result<Self> {
+        let root = parse_tree(xml)?;
+        let document = root
+            .child_elements()
+            .find(|e| e.name == "document")
+            .map(|e| e.children.as_slice())
+            // tolerate a bare fragment without the <document> wrapper
+            .unwrap_or(&root.children);
+        Ok(Self {
+            blocks: parse_blocks(document),
+        })
+    }
+
+    /// Build a document of plain paragraphs, one per blank-line-separated chunk of `text`.
+    pub fn from_plain_text(text: &str) -> Self {
+        Self {
+            blocks: text
+                .split("\n\n")
+                .filter(|p| !p.trim().is_empty())
+                .map(|p| {
+                    let mut content = Vec::new();
+                    for (i, line) in p.trim_matches('\n').lines().enumerate() {
+                        if i > 0 {
+                            content.push(Inline::Break);
+                        }
+                        content.push(Inline::Text(String::from(line)));
+                    }
+                    Block::Paragraph(content)
+                })
+                .collect(),
+        }
+    }
+
+    /// Write this document as XML, suitable for the `content` of a new thread or reply.
+    pub fn to_xml(&self) -> String {
+        let mut out = String::new();
+        Element::new("document")
+            .with_attribute("version", DOCUMENT_VERSION)
+            .with_children(blocks_to_nodes(&self.blocks))
+            .write_xml(&mut out);
+        out
+    }
+
+    /// Render this document as plain text, dropping all formatting.
+    pub fn to_plain_text(&self) -> String {
+        blocks_markdown(&self.blocks, true)
+    }
+
+    /// Render this document as (CommonMark-ish) Markdown.
+    pub fn to_markdown(&self) -> String {
+        blocks_markdown(&self.blocks, false)
+    }
+
+    /// Every [`Inline::Mention`] as its user ID and name, in document order.
+    pub fn mentions(&self) -> Vec<(u64, &str)> {
+        let mut out = Vec::new();
+        blocks_mentions(&self.blocks, &mut out);
+        out
+    }
+
+    /// Replace each run of formatted text with the inlines `f` makes of it. Text in links, code
+    /// and maths is left alone.
+    pub(crate) fn rewrite_text(&mut self, mut f: impl FnMut(String) -> Vec<Inline>) {
+        blocks_rewrite_text(&mut self.blocks, &mut f);
+    }
+}
+
+impl Display for Document {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_xml())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_round_trips() {
+        let xml = r#"<document version="2.0"><paragraph>Hi <bold>there</bold> &amp; <link href="https://example.com">a link</link></paragraph><list style="number"><list-item><paragraph>one</paragraph></list-item></list><snippet language="py" runnable="true"><snippet-content>print(1)</snippet-content></snippet></document>"#;
+        let document = Document::parse(xml).unwrap();
+        assert_eq!(document.to_xml(), xml);
+        assert_eq!(
+            document.to_plain_text(),
+            "Hi there & a link\n\n1. one\n\nprint(1)"
+        );
+        assert_eq!(
+            document.to_markdown(),
+            "Hi **there** & [a link](https://example.com)\n\n1. one\n\n```py\nprint(1)\n```"
+        );
+    }
+
+    #[test]
+    fn parse_keeps_unknown_elements() {
+        let xml = r#"<document version="2.0"><paragraph><sparkle size="2">!</sparkle></paragraph><widget/></document>"#;
+        assert_eq!(Document::parse(xml).unwrap().to_xml(), xml);
+    }
+
+    #[test]
+    fn parse_rejects_unclosed_elements() {
+        assert!(Document::parse(r#"<document version="2.0"><paragraph>hello"#).is_err());
+        assert!(Document::parse("<document><paragraph>hello</paragraph>").is_err());
+    }
+
+    #[test]
+    fn mentions_round_trip() {
+        let xml = r#"<document version="2.0"><paragraph>cc <mention id="12">Jane Doe</mention>, <mention>nobody</mention></paragraph></document>"#;
+        let document = Document::parse(xml).unwrap();
+        assert_eq!(document.to_xml(), xml);
+        assert_eq!(document.mentions(), [(12, "Jane Doe")]);
+        assert_eq!(document.to_plain_text(), "cc @Jane Doe, nobody");
+    }
+
+    #[test]
+    fn from_plain_text_splits_paragraphs_and_lines() {
+        let document = Document::from_plain_text("one\ntwo\n\nthree");
+        assert_eq!(
+            document.blocks,
+            vec![
+                Block::Paragraph(vec![
+                    Inline::Text(String::from("one")),
+                    Inline::Break,
+                    Inline::Text(String::from("two")),
+                ]),
+                Block::Paragraph(vec![Inline::Text(String::from("three"))]),
+            ]
+        );
+    }
+}
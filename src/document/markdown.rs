@@ -0,0 +1,256 @@
+use pulldown_cmark::{CodeBlockKind, Event, HeadingLevel, Options, Parser, Tag, TagEnd};
+
+use super::{Block, CalloutKind, Document, Inline, ListStyle};
+
+enum InlineKind {
+    /// text in a tight list item, with no paragraph of its own
+    Implicit,
+    Paragraph,
+    Heading(u8),
+    Strong,
+    Emphasis,
+    Strikethrough,
+    Link(String),
+}
+
+enum Frame {
+    Blocks(Vec<Block>),
+    List(ListStyle, Vec<Vec<Block>>),
+    Inlines(InlineKind, Vec<Inline>),
+    Code(String, String),
+}
+
+struct Builder {
+    stack: Vec<Frame>,
+}
+
+impl Builder {
+    fn close_implicit(&mut self) {
+        if let Some(Frame::Inlines(InlineKind::Implicit, _)) = self.stack.last() {
+            self.end();
+        }
+    }
+
+    fn push_block(&mut self, block: Block) {
+        self.close_implicit();
+        // blocks can only nest inside block containers, so anything else is malformed input
+        if let Some(Frame::Blocks(blocks)) = self.stack.last_mut() {
+            blocks.push(block);
+        }
+    }
+
+    fn push_inline(&mut self, inline: Inline) {
+        match self.stack.last_mut() {
+            Some(Frame::Inlines(_, inlines)) => match (inlines.last_mut(), inline) {
+                (Some(Inline::Text(prev)), Inline::Text(t)) => prev.push_str(&t),
+                (_, inline) => inlines.push(inline),
+            },
+            Some(Frame::Code(_, text)) => {
+                if let Inline::Text(t) = inline {
+                    text.push_str(&t);
+                }
+            }
+            _ => {
+                self.stack
+                    .push(Frame::Inlines(InlineKind::Implicit, vec![inline]));
+            }
+        }
+    }
+
+    fn start(&mut self, tag: Tag) {
+        let frame = match tag {
+            Tag::Paragraph => Frame::Inlines(InlineKind::Paragraph, Vec::new()),
+            Tag::Heading { level, .. } => Frame::Inlines(
+                InlineKind::Heading(match level {
+                    HeadingLevel::H1 => 1,
+                    HeadingLevel::H2 => 2,
+                    HeadingLevel::H3 => 3,
+                    HeadingLevel::H4 => 4,
+                    HeadingLevel::H5 => 5,
+                    HeadingLevel::H6 => 6,
+                }),
+                Vec::new(),
+            ),
+            Tag::BlockQuote(_) | Tag::Item => Frame::Blocks(Vec::new()),
+            Tag::CodeBlock(kind) => Frame::Code(
+                match kind {
+                    CodeBlockKind::Fenced(lang) => lang.into_string(),
+                    CodeBlockKind::Indented => String::new(),
+                },
+                String::new(),
+            ),
+            Tag::List(start) => Frame::List(
+                match start {
+                    Some(_) => ListStyle::Number,
+                    None => ListStyle::Bullet,
+                },
+                Vec::new(),
+            ),
+            Tag::Emphasis => Frame::Inlines(InlineKind::Emphasis, Vec::new()),
+            Tag::Strong => Frame::Inlines(InlineKind::Strong, Vec::new()),
+            Tag::Strikethrough => Frame::Inlines(InlineKind::Strikethrough, Vec::new()),
+            Tag::Link { dest_url, .. } | Tag::Image { dest_url, .. } => {
+                Frame::Inlines(InlineKind::Link(dest_url.into_string()), Vec::new())
+            }
+            // anything else (tables, footnotes, ...) is flattened into its text
+            _ => return,
+        };
+
+        if matches!(
+            frame,
+            Frame::Inlines(InlineKind::Paragraph | InlineKind::Heading(_), _)
+        ) || matches!(frame, Frame::Blocks(_) | Frame::List(..) | Frame::Code(..))
+        {
+            self.close_implicit();
+        } else if matches!(self.stack.last(), Some(Frame::Blocks(_))) {
+            self.stack
+                .push(Frame::Inlines(InlineKind::Implicit, Vec::new()));
+        }
+        self.stack.push(frame);
+    }
+
+    fn end(&mut self) {
+        let Some(frame) = self.stack.pop() else {
+            return;
+        };
+        match frame {
+            Frame::Inlines(kind, inlines) => match kind {
+                InlineKind::Implicit | InlineKind::Paragraph => {
+                    self.push_block(Block::Paragraph(inlines))
+                }
+                InlineKind::Heading(level) => self.push_block(Block::Heading {
+                    level,
+                    content: inlines,
+                }),
+                InlineKind::Strong => self.push_inline(Inline::Bold(inlines)),
+                InlineKind::Emphasis => self.push_inline(Inline::Italic(inlines)),
+                InlineKind::Strikethrough => self.push_inline(Inline::Strike(inlines)),
+                InlineKind::Link(href) => self.push_inline(Inline::Link {
+                    href,
+                    content: inlines,
+                }),
+            },
+            Frame::Blocks(blocks) => match self.stack.last_mut() {
+                Some(Frame::List(_, items)) => items.push(blocks),
+                _ => self.push_block(Block::Callout {
+                    kind: CalloutKind::Info,
+                    content: blocks,
+                }),
+            },
+            Frame::List(style, items) => self.push_block(Block::List { style, items }),
+            Frame::Code(language, mut content) => {
+                if content.ends_with('\n') {
+                    content.pop();
+                }
+                self.push_block(if language.is_empty() {
+                    Block::Pre(content)
+                } else {
+                    Block::Snippet {
+                        language,
+                        runnable: false,
+                        content,
+                    }
+                })
+            }
+        }
+    }
+
+    fn end_tag(&mut self, tag: TagEnd) {
+        match tag {
+            TagEnd::Paragraph
+            | TagEnd::Heading(_)
+            | TagEnd::BlockQuote(_)
+            | TagEnd::Item
+            | TagEnd::CodeBlock
+            | TagEnd::List(_)
+            | TagEnd::Emphasis
+            | TagEnd::Strong
+            | TagEnd::Strikethrough
+            | TagEnd::Link
+            | TagEnd::Image => {
+                // an item's trailing text in a tight list has no end tag of its own
+                if matches!(tag, TagEnd::Item | TagEnd::BlockQuote(_)) {
+                    self.close_implicit();
+                }
+                self.end();
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Document {
+    /// Convert Markdown into a document, e.g. to post a reply written in an editor.
+    ///
+    /// Block quotes become info callouts, fenced code blocks with a language become snippets and
+    /// constructs with no equivalent (tables, footnotes, ...) are flattened into their text.
+    pub fn from_markdown(markdown: &str) -> Self {
+        let mut builder = Builder {
+            stack: vec![Frame::Blocks(Vec::new())],
+        };
+
+        for event in Parser::new_ext(markdown, Options::ENABLE_STRIKETHROUGH) {
+            match event {
+                Event::Start(tag) => builder.start(tag),
+                Event::End(tag) => builder.end_tag(tag),
+                Event::Text(t) | Event::Html(t) | Event::InlineHtml(t) => {
+                    builder.push_inline(Inline::Text(t.into_string()))
+                }
+                Event::Code(t) => builder.push_inline(Inline::Code(t.into_string())),
+                Event::InlineMath(t) | Event::DisplayMath(t) => {
+                    builder.push_inline(Inline::Math(t.into_string()))
+                }
+                Event::SoftBreak => builder.push_inline(Inline::Text(String::from(" "))),
+                Event::HardBreak => builder.push_inline(Inline::Break),
+                Event::TaskListMarker(done) => {
+                    builder.push_inline(Inline::Text(String::from(if done {
+                        "[x] "
+                    } else {
+                        "[ ] "
+                    })))
+                }
+                Event::Rule | Event::FootnoteReference(_) => {}
+            }
+        }
+
+        builder.close_implicit();
+        match builder.stack.into_iter().next() {
+            Some(Frame::Blocks(blocks)) => Self { blocks },
+            _ => Self::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inline_formatting() {
+        let document = Document::from_markdown(
+            "# Title\n\nSome **bold**, *italic*, ~~struck~~ and `code`\nwith [a link](https://example.com).",
+        );
+        assert_eq!(
+            document.to_xml(),
+            r#"<document version="2.0"><heading level="1">Title</heading><paragraph>Some <bold>bold</bold>, <italic>italic</italic>, <strike>struck</strike> and <code>code</code> with <link href="https://example.com">a link</link>.</paragraph></document>"#
+        );
+    }
+
+    #[test]
+    fn lists_quotes_and_code() {
+        let document = Document::from_markdown(
+            "- one\n- two\n  1. nested\n\n> note\n\n```py\nprint(1)\n```\n\n    indented",
+        );
+        assert_eq!(
+            document.to_xml(),
+            r#"<document version="2.0"><list style="bullet"><list-item><paragraph>one</paragraph></list-item><list-item><paragraph>two</paragraph><list style="number"><list-item><paragraph>nested</paragraph></list-item></list></list-item></list><callout type="info"><paragraph>note</paragraph></callout><snippet language="py" runnable="false"><snippet-content>print(1)</snippet-content></snippet><pre>indented</pre></document>"#
+        );
+    }
+
+    #[test]
+    fn markdown_round_trips() {
+        let markdown =
+            "Hi **there** & [a link](https://example.com)\n\n1. one\n\n```py\nprint(1)\n```";
+        assert_eq!(Document::from_markdown(markdown).to_markdown(), markdown);
+    }
+}
@@ -0,0 +1,297 @@
+//! Lesson completion and quiz scores per student, from
+//! [`Client::gradebook`](crate::Client::gradebook), for importing into a grading system.
+
+use std::collections::HashMap;
+
+use crate::{
+    csv::write_row,
+    model::{
+        LessonStatus,
+        course::Role,
+        lesson::{Lesson, LessonProgress, QuizResponse},
+        user::CourseMember,
+    },
+};
+
+/// A column group of a [`Gradebook`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct GradebookLesson {
+    /// The lesson ID.
+    pub id: u64,
+    /// The lesson title.
+    pub title: String,
+    /// The most anyone could score on the lesson's quizzes.
+    pub max_score: f64,
+}
+
+/// A student's result for one lesson.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GradebookEntry {
+    /// How far they got.
+    pub status: LessonStatus,
+    /// Their total quiz score.
+    pub score: f64,
+}
+
+/// A student's results for every lesson, in [`Gradebook::lessons`] order.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GradebookRow {
+    /// The student's email address, which identifies them in the gradebook.
+    pub email: String,
+    /// The student's name.
+    pub name: String,
+    /// The student's user ID.
+    pub user_id: u64,
+    /// Results, one per lesson.
+    pub entries: Vec<GradebookEntry>,
+}
+
+/// Every student's results in a course's lessons.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Gradebook {
+    /// The lessons included, in order.
+    pub lessons: Vec<GradebookLesson>,
+    /// One row per student, by email.
+    pub rows: Vec<GradebookRow>,
+}
+
+impl Gradebook {
+    /// An empty gradebook for the students among `members`.
+    pub fn new(members: &[CourseMember]) -> Self {
+        let mut rows: Vec<_> = members
+            .iter()
+            .filter(|m| *m.course_role() == Role::Student)
+            .map(|m| GradebookRow {
+                email: m.email().clone(),
+                name: m.name().clone(),
+                user_id: (*m.id()).into(),
+                entries: Vec::new(),
+            })
+            .collect();
+        rows.sort_by(|a, b| a.email.cmp(&b.email));
+
+        Self {
+            lessons: Vec::new(),
+            rows,
+        }
+    }
+
+    /// Add a column group for `lesson`, given everyone's `progress` through it and `responses`
+    /// to its quizzes.
+    ///
+    /// `lesson` should include its slides, as from
+    /// [`Client::get_lesson`](crate::Client::get_lesson), for the maximum score to be known.
+    pub fn add_lesson(
+        &mut self,
+        lesson: &Lesson,
+        progress: &[LessonProgress],
+        responses: &[QuizResponse],
+    ) {
+        let status: HashMap<u64, &LessonStatus> = progress
+            .iter()
+            .map(|p| (u64::from(*p.user_id()), p.status()))
+            .collect();
+        let mut scores: HashMap<u64, f64> = HashMap::new();
+        for response in responses {
+            *scores.entry((*response.user_id()).into()).or_default() += response.score();
+        }
+
+        self.lessons.push(GradebookLesson {
+            id: (*lesson.id()).into(),
+            title: lesson.title().clone(),
+            max_score: lesson.max_quiz_score(),
+        });
+        for row in &mut self.rows {
+            row.entries.push(GradebookEntry {
+                status: status
+                    .get(&row.user_id)
+                    .map_or(LessonStatus::NotStarted, |s| (*s).clone()),
+                score: scores.get(&row.user_id).copied().unwrap_or_default(),
+            });
+        }
+    }
+
+    /// The row of the student with this email address.
+    pub fn row(&self, email: &str) -> Option<&GradebookRow> {
+        self.rows
+            .iter()
+            .find(|r| r.email.eq_ignore_ascii_case(email))
+    }
+
+    /// The gradebook as CSV, with a header row and a status and score column per lesson.
+    pub fn to_csv(&self) -> String {
+        let mut out = String::new();
+
+        let mut header = vec![String::from("email"), String::from("name")];
+        for lesson in &self.lessons {
+            header.push(format!("{} status", lesson.title));
+            header.push(format!("{} score (/{})", lesson.title, lesson.max_score));
+        }
+        write_row(&mut out, header);
+
+        for row in &self.rows {
+            let mut fields = vec![row.email.clone(), row.name.clone()];
+            for entry in &row.entries {
+                fields.push(String::from(entry.status.as_str()));
+                fields.push(entry.score.to_string());
+            }
+            write_row(&mut out, fields);
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn member(id: u64, name: &str, email: &str, course_role: &str) -> CourseMember {
+        serde_json::from_value(json!({
+            "id": id,
+            "course_id": 1,
+            "name": name,
+            "email": email,
+            "course_role": course_role,
+            "lab_id": null,
+            "created_at": "2024-01-01T00:00:00Z",
+        }))
+        .unwrap()
+    }
+
+    fn lesson(title: &str, question_scores: &[f64]) -> Lesson {
+        let questions: Vec<_> = question_scores
+            .iter()
+            .enumerate()
+            .map(|(i, max_score)| json!({"id": i + 1, "max_score": max_score}))
+            .collect();
+        serde_json::from_value(json!({
+            "id": 1,
+            "course_id": 1,
+            "module_id": null,
+            "user_id": 1,
+            "title": title,
+            "index": 1,
+            "state": "active",
+            "is_hidden": false,
+            "available_at": null,
+            "due_at": null,
+            "created_at": "2024-01-01T00:00:00Z",
+            "updated_at": null,
+            "slides": [{
+                "id": 1,
+                "lesson_id": 1,
+                "course_id": 1,
+                "type": "quiz",
+                "title": "Quiz",
+                "index": 1,
+                "content": "<document version=\"2.0\"></document>",
+                "is_hidden": false,
+                "created_at": "2024-01-01T00:00:00Z",
+                "questions": questions,
+            }],
+        }))
+        .unwrap()
+    }
+
+    fn progress(user_id: u64, status: &str) -> LessonProgress {
+        serde_json::from_value(json!({
+            "user_id": user_id,
+            "lesson_id": 1,
+            "status": status,
+            "completed_slides": 1,
+            "total_slides": 1,
+            "first_viewed_at": "2024-01-01T00:00:00Z",
+            "completed_at": null,
+        }))
+        .unwrap()
+    }
+
+    fn response(user_id: u64, question_id: u64, score: f64, max_score: f64) -> QuizResponse {
+        serde_json::from_value(json!({
+            "user_id": user_id,
+            "lesson_id": 1,
+            "slide_id": 1,
+            "question_id": question_id,
+            "response": [0],
+            "is_correct": score >= max_score,
+            "score": score,
+            "max_score": max_score,
+            "submitted_at": "2024-01-01T00:00:00Z",
+        }))
+        .unwrap()
+    }
+
+    fn gradebook() -> Gradebook {
+        let mut gradebook = Gradebook::new(&[
+            member(1, "Zoe", "zoe@example.com", "student"),
+            member(2, "Tutor", "tutor@example.com", "staff"),
+            member(3, "Adam", "adam@example.com", "student"),
+        ]);
+        gradebook.add_lesson(
+            &lesson("Week 1", &[1.0, 2.0, 3.0]),
+            &[progress(1, "completed"), progress(2, "completed")],
+            &[
+                response(1, 1, 1.0, 1.0),
+                response(1, 2, 0.5, 2.0),
+                response(2, 1, 1.0, 1.0),
+            ],
+        );
+        gradebook
+    }
+
+    #[test]
+    fn only_students_have_rows() {
+        let gradebook = gradebook();
+        let emails: Vec<_> = gradebook.rows.iter().map(|r| r.email.as_str()).collect();
+        assert_eq!(emails, ["adam@example.com", "zoe@example.com"]);
+        assert!(gradebook.row("tutor@example.com").is_none());
+        assert_eq!(gradebook.row("ZOE@example.com").unwrap().user_id, 1);
+    }
+
+    #[test]
+    fn totals_scores() {
+        let gradebook = gradebook();
+        assert_eq!(gradebook.lessons[0].max_score, 6.0);
+        assert_eq!(
+            gradebook.row("zoe@example.com").unwrap().entries,
+            [GradebookEntry {
+                status: LessonStatus::Completed,
+                score: 1.5,
+            }]
+        );
+    }
+
+    #[test]
+    fn missing_results_are_not_started() {
+        let gradebook = gradebook();
+        assert_eq!(
+            gradebook.row("adam@example.com").unwrap().entries,
+            [GradebookEntry {
+                status: LessonStatus::NotStarted,
+                score: 0.0,
+            }]
+        );
+    }
+
+    #[test]
+    fn unanswered_questions_count_towards_the_maximum() {
+        let mut gradebook = gradebook();
+        gradebook.add_lesson(&lesson("Week 2", &[2.0, 2.0]), &[], &[]);
+        assert_eq!(gradebook.lessons[1].max_score, 4.0);
+    }
+
+    #[test]
+    fn csv() {
+        let mut gradebook = gradebook();
+        gradebook.add_lesson(&lesson("Loops, \"part 2\"", &[1.0]), &[], &[]);
+        assert_eq!(
+            gradebook.to_csv(),
+            "email,name,Week 1 status,Week 1 score (/6),\"Loops, \"\"part 2\"\" status\",\
+             \"Loops, \"\"part 2\"\" score (/1)\"\r\n\
+             adam@example.com,Adam,not_started,0,not_started,0\r\n\
+             zoe@example.com,Zoe,completed,1.5,not_started,0\r\n"
+        );
+    }
+}
@@ -12,28 +12,166 @@
 //! ## features
 //!
 //! enable `serde` to add `Serialize` impls for structs
+//!
+//! enable `cassette` to record responses to files and replay them in tests, see [`cassette`]
+//!
+//! enable `markdown` to convert Markdown into [`document::Document`]s
+//!
+//! enable `analytics` for [`analytics::ForumReport`], statistics over a course's threads
+//!
+//! enable `relay` for [`relay::Relay`], which POSTs course activity to webhooks, and to build
+//! `edstem-relay`, which runs one from a config file
+//!
+//! enable `sync` for [`sync::sync_workspace`], two-way sync of a workspace with a local directory
+//!
+//! enable `toml` to load [`categories::DesiredCategories`] from TOML
+//!
+//! enable `testing` for [`testing::MockServer`], a local stand-in for Ed Discussion to test against
+//!
+//! enable `tui` to build `edstem-tui`, a terminal client for browsing and replying to threads
 #![deny(missing_docs)]
+// the fixtures in `testing` are large `json!` literals
+#![cfg_attr(any(test, feature = "testing"), recursion_limit = "256")]
 
+#[cfg(feature = "analytics")]
+use analytics::ForumReport;
+use chat::ChatHistory;
+use document::Document;
+use gradebook::Gradebook;
 use model::{
-    thread::{CourseThreads, ThreadResponse},
-    user::SelfUser,
+    ApprovalStatus,
+    challenge::{
+        Challenge, ChallengeResponse, Submission, SubmissionResponse, SubmissionsResponse,
+    },
+    chat::{
+        Channel, ChannelsResponse, ChatMessage, ChatMessageResponse, ChatMessages,
+        DirectConversation, DirectConversationsResponse,
+    },
+    course::{Course, CourseResponse, CourseRole, CourseRoleResponse, Role},
+    lab::{Lab, LabsResponse},
+    lesson::{
+        CourseLessons, Lesson, LessonProgress, LessonProgressResponse, LessonResponse,
+        QuizResponse, QuizResponsesResponse,
+    },
+    notification::{ActivityItem, ActivityResponse, Notifications},
+    realm::{Realm, RealmCoursesResponse, RealmResponse, RealmUser, RealmUsersResponse},
+    thread::{CourseThreads, PartialThread, ReplyResponse, ThreadResponse},
+    user::{
+        CourseMember, CourseMemberResponse, CourseMembersResponse, SelfUser, ThreadParticipant,
+        User, UserResponse,
+    },
+    workspace::{
+        Workspace, WorkspaceFile, WorkspaceFileResponse, WorkspaceFilesResponse, WorkspaceResponse,
+        WorkspacesResponse,
+    },
+};
+use moderation::ModerationReport;
+#[cfg(feature = "analytics")]
+use opts::AnalyticsOptions;
+use opts::{
+    ActivityOptions, ChatHistoryOptions, CourseDiscussionSettingsPatch, CourseRoleSettingsPatch,
+    GetCourseThreadsFilterKey, GetCourseThreadsOptions, ModerationReportOptions, NewCourse,
+    NotificationOptions, PostReplyOptions, RealmSettingsPatch, SNOOZE_NONE, SettingChange,
+    SourceFile, SubmissionPollOptions, UserSettingsPatch,
 };
-use opts::GetCourseThreadsOptions;
-use reqwest::RequestBuilder;
-use serde::{Deserialize, Serialize};
+use reqwest::{
+    Method, StatusCode, Url,
+    header::{AUTHORIZATION, CONTENT_TYPE, HeaderMap, HeaderValue, USER_AGENT},
+};
+use schema::{SchemaMode, SchemaReport};
+use serde::{Deserialize, Serialize, de::IgnoredAny};
+use std::{collections::HashSet, sync::Arc};
+use templates::{ReplyTemplate, TemplateContext};
+use transport::{Middleware, Request, Stack, Transport};
 
+#[cfg(feature = "analytics")]
+pub mod analytics;
+pub mod anonymity;
+#[cfg(feature = "cassette")]
+pub mod cassette;
+pub mod categories;
+pub mod chat;
+mod csv;
+pub mod diff;
+pub mod document;
+pub mod gradebook;
+pub mod mentions;
 pub mod model;
+pub mod moderation;
 pub mod opts;
+pub mod participants;
+#[cfg(feature = "relay")]
+pub mod relay;
+pub mod schema;
+#[cfg(feature = "sync")]
+pub mod sync;
+pub mod templates;
+#[cfg(feature = "testing")]
+pub mod testing;
+pub mod transport;
+#[cfg(feature = "tui")]
+pub mod tui;
 
 /// Unified error type from the crate.
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
-    /// Error from underlying `reqwest`, e.g. 403 or connectivity error.
+    /// Error from underlying `reqwest`, e.g. connectivity error.
     #[error("reqwest error: {0}")]
     Reqwest(#[from] reqwest::Error),
+    /// Ed Discussion responded with an error status, e.g. 403 or 429.
+    #[error("HTTP {status}: {body}")]
+    Status {
+        /// The status returned.
+        status: StatusCode,
+        /// The response body, usually JSON with `code` and `message` keys.
+        body: String,
+    },
+    /// A request could not be built, e.g. because of an invalid base URL or header value.
+    #[error("invalid request: {0}")]
+    Request(String),
     /// Error from underlying `serde_json`, i.e. JSON parsing has gone wrong.
     #[error("error deserializing json: {0}")]
     Json(#[from] serde_json::Error),
+    /// A response did not match the models, see [`schema`].
+    #[error("error deserializing json at {path}: {source}")]
+    Schema {
+        /// Where in the response, e.g. `threads[3].user_id`.
+        path: String,
+        /// What went wrong.
+        source: serde_json::Error,
+    },
+    /// I/O error, e.g. from the terminal or filesystem.
+    #[error("i/o error: {0}")]
+    Io(#[from] std::io::Error),
+    /// A request made while replaying a [`cassette::Cassette`] was not in the recording.
+    #[cfg(feature = "cassette")]
+    #[error("cassette error: {0}")]
+    Cassette(String),
+    /// Error parsing TOML, see [`categories::DesiredCategories::from_toml`].
+    #[cfg(feature = "toml")]
+    #[error("error deserializing toml: {0}")]
+    Toml(#[from] toml::de::Error),
+    /// An operation was refused because it would lose data, e.g. deleting a category which still
+    /// has threads without forcing it.
+    #[error("refused: {0}")]
+    Refused(String),
+    /// Something looked up by position or name does not exist, e.g. a saved reply index past
+    /// the end of the list.
+    #[error("not found: {0}")]
+    NotFound(String),
+    /// A template used an unknown placeholder, see [`templates::ReplyTemplate::render`].
+    #[error("template error: {0}")]
+    Template(String),
+    /// An unknown time zone or unrepresentable time, see [`analytics::ForumReport::new`].
+    #[cfg(feature = "analytics")]
+    #[error("time error: {0}")]
+    Time(#[from] jiff::Error),
+    /// Gave up waiting, see [`Client::wait_for_submission`].
+    #[error("timed out: {0}")]
+    Timeout(String),
+    /// Error parsing a post body, see [`document::Document::parse`].
+    #[error("error parsing document: {0}")]
+    Xml(#[from] quick_xml::Error),
 }
 
 /// Aliased [`std::result::Result`] for this crate.
@@ -42,23 +180,32 @@ pub type Result<T> = std::result::Result<T, self::Error>;
 /// An API client capable of making complete requests to Ed Discussion.
 #[derive(Clone, Debug)]
 pub struct Client {
-    http: reqwest::Client,
+    transport: Arc<dyn Transport>,
     base_url: String,
     token: String,
     user_agent: String,
+    schema_mode: SchemaMode,
+    schema_report: SchemaReport,
 }
 
-type EmptyParams = &'static [((), ())];
+type EmptyParams = &'static [(&'static str, String)];
 
 /// Options for creation of a new [`Client`].
 #[derive(Clone, Debug, Default)]
 pub struct ClientOptions {
-    /// A custom `reqwest::Client`, if desired.
+    /// A custom `reqwest::Client`, if desired. Ignored if `transport` is given.
     pub http: Option<reqwest::Client>,
+    /// A custom [`Transport`] to send requests with, instead of `reqwest`.
+    pub transport: Option<Arc<dyn Transport>>,
+    /// [`Middleware`] to wrap the transport in, outermost first. See [`transport`] for those
+    /// provided, and [`cassette::Cassette`] with the `cassette` feature.
+    pub middleware: Vec<Arc<dyn Middleware>>,
     /// The base URL, if connecting to the "typical" Ed Discussion domain is not desired.
     pub base_url: Option<String>,
     /// A user agent string, if the default is not desired.
     pub user_agent: Option<String>,
+    /// How to treat fields the models do not know about, see [`schema`].
+    pub schema_mode: SchemaMode,
 }
 
 impl Client {
@@ -69,50 +216,410 @@ impl Client {
 
     /// Construct a new client with [`ClientOptions`].
     pub fn new_with_opts(token: &str, options: ClientOptions) -> Self {
+        let transport = options
+            .transport
+            .unwrap_or_else(|| Arc::new(options.http.unwrap_or_default()));
+        let transport = if options.middleware.is_empty() {
+            transport
+        } else {
+            Arc::new(Stack {
+                middleware: options.middleware,
+                transport,
+            })
+        };
+
         Self {
-            http: options.http.unwrap_or_else(|| reqwest::Client::new()),
+            transport,
             base_url: options
                 .base_url
                 .unwrap_or(String::from("https://us.edstem.org")),
             token: String::from(token),
             user_agent: options.user_agent.unwrap_or(String::from("edstem-rust")),
+            schema_mode: options.schema_mode,
+            schema_report: SchemaReport::default(),
         }
     }
 
-    async fn request<T>(&self, request: RequestBuilder) -> Result<T>
+    /// Unknown and mismatched fields seen in responses to this client and its clones.
+    pub fn schema_report(&self) -> &SchemaReport {
+        &self.schema_report
+    }
+
+    fn build_request(&self, method: Method, endpoint: &str) -> Result<Request> {
+        let url = Url::parse(&format!("{}{}", self.base_url, endpoint))
+            .map_err(|e| Error::Request(e.to_string()))?;
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            AUTHORIZATION,
+            HeaderValue::from_str(&format!("Bearer {}", self.token))
+                .map_err(|e| Error::Request(e.to_string()))?,
+        );
+        headers.insert(
+            USER_AGENT,
+            HeaderValue::from_str(&self.user_agent).map_err(|e| Error::Request(e.to_string()))?,
+        );
+
+        Ok(Request {
+            method,
+            url,
+            headers,
+            body: None,
+        })
+    }
+
+    async fn request<T>(&self, request: Request) -> Result<T>
     where
         T: for<'de> Deserialize<'de>,
     {
-        let built = request
-            .header("Authorization", format!("Bearer {}", self.token))
-            .header("User-Agent", &self.user_agent)
-            .build()?;
+        let endpoint = String::from(request.url.path());
+        let body = self.request_raw(request).await?;
+
+        // some actions respond with no body at all
+        let body = match body.as_slice() {
+            [] => b"null".as_slice(),
+            body => body,
+        };
+        schema::deserialize(body, &endpoint, self.schema_mode, &self.schema_report)
+    }
 
-        let response = self.http.execute(built).await?;
+    async fn request_raw(&self, request: Request) -> Result<Vec<u8>> {
+        let response = self.transport.execute(request).await?;
 
-        Ok(response.json().await?)
+        if !response.status.is_success() {
+            return Err(Error::Status {
+                status: response.status,
+                body: String::from_utf8_lossy(&response.body).into_owned(),
+            });
+        }
+        Ok(response.body)
     }
 
-    async fn get<T>(
-        &self,
-        endpoint: &str,
-        parameters: Option<&[(impl Serialize, impl Serialize)]>,
-    ) -> Result<T>
+    async fn get<T>(&self, endpoint: &str, parameters: Option<&[(&str, String)]>) -> Result<T>
     where
         T: for<'de> Deserialize<'de>,
     {
-        let mut builder = self.http.get(format!("{}{}", self.base_url, endpoint));
+        let mut request = self.build_request(Method::GET, endpoint)?;
 
         if let Some(params) = parameters {
-            builder = builder.query(params);
+            request.url.query_pairs_mut().extend_pairs(params);
         };
 
-        Ok(self.request(builder).await?)
+        self.request(request).await
+    }
+
+    async fn post<T>(&self, endpoint: &str, body: &impl Serialize) -> Result<T>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        self.send_json(Method::POST, endpoint, body).await
+    }
+
+    async fn put<T>(&self, endpoint: &str, body: &impl Serialize) -> Result<T>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        self.send_json(Method::PUT, endpoint, body).await
+    }
+
+    async fn delete<T>(&self, endpoint: &str, parameters: Option<&[(&str, String)]>) -> Result<T>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        let mut request = self.build_request(Method::DELETE, endpoint)?;
+
+        if let Some(params) = parameters {
+            request.url.query_pairs_mut().extend_pairs(params);
+        };
+
+        self.request(request).await
+    }
+
+    async fn send_json<T>(&self, method: Method, endpoint: &str, body: &impl Serialize) -> Result<T>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        let mut request = self.build_request(method, endpoint)?;
+        request
+            .headers
+            .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        request.body = Some(serde_json::to_vec(body)?);
+
+        self.request(request).await
     }
 
     /// Get the [`SelfUser`] representing the user making API requests.
     pub async fn get_self_user(&self) -> Result<SelfUser> {
-        Ok(self.get("/api/user", None::<EmptyParams>).await?)
+        self.get("/api/user", None::<EmptyParams>).await
+    }
+
+    /// Update the current user's settings, returning the updated [`User`].
+    pub async fn update_user_settings(&self, patch: &UserSettingsPatch) -> Result<User> {
+        let response: UserResponse = self.put("/api/user/settings", &patch.as_body()).await?;
+        Ok(response.dissolve().0)
+    }
+
+    /// Snooze notifications until `until`, an ISO 8601 datetime.
+    pub async fn snooze_notifications(&self, until: &str) -> Result<User> {
+        let patch = UserSettingsPatch {
+            snooze_end: Some(String::from(until)),
+            ..Default::default()
+        };
+        self.update_user_settings(&patch).await
+    }
+
+    /// Stop snoozing notifications.
+    pub async fn clear_snooze(&self) -> Result<User> {
+        self.snooze_notifications(SNOOZE_NONE).await
+    }
+
+    /// Update the current user's settings for a single course, returning the updated
+    /// [`CourseRole`].
+    pub async fn update_course_role_settings(
+        &self,
+        course_id: impl Into<u64>,
+        patch: &CourseRoleSettingsPatch,
+    ) -> Result<CourseRole> {
+        let endpoint = format!("/api/courses/{}/role/settings", course_id.into());
+        let response: CourseRoleResponse = self.put(&endpoint, &patch.as_body()).await?;
+        Ok(response.dissolve().0)
+    }
+
+    /// Get your notifications across all courses, newest first.
+    pub async fn get_notifications(
+        &self,
+        options: Option<NotificationOptions>,
+    ) -> Result<Notifications> {
+        self.get(
+            "/api/notifications",
+            options.as_ref().map(|o| o.as_params()).as_deref(),
+        )
+        .await
+    }
+
+    /// Mark notifications as read.
+    pub async fn mark_notifications_read(
+        &self,
+        ids: impl IntoIterator<Item = impl Into<u64>>,
+    ) -> Result<()> {
+        let ids: Vec<u64> = ids.into_iter().map(Into::into).collect();
+        let body = serde_json::json!({ "ids": ids });
+        let _: IgnoredAny = self.post("/api/notifications/read", &body).await?;
+        Ok(())
+    }
+
+    /// Mark every notification as read.
+    pub async fn mark_all_notifications_read(&self) -> Result<()> {
+        let body = serde_json::json!({ "all": true });
+        let _: IgnoredAny = self.post("/api/notifications/read", &body).await?;
+        Ok(())
+    }
+
+    /// Delete every notification, read or not.
+    pub async fn clear_notifications(&self) -> Result<()> {
+        let _: IgnoredAny = self
+            .delete("/api/notifications", None::<EmptyParams>)
+            .await?;
+        Ok(())
+    }
+
+    /// Get the threads and replies a user has posted, newest first, in courses you share with
+    /// them. Anonymous posts are only included in your own activity.
+    pub async fn get_user_activity(
+        &self,
+        user_id: impl Into<u64>,
+        options: Option<ActivityOptions>,
+    ) -> Result<Vec<ActivityItem>> {
+        let endpoint = format!("/api/users/{}/activity", user_id.into());
+        let response: ActivityResponse = self
+            .get(
+                &endpoint,
+                options.as_ref().map(|o| o.as_params()).as_deref(),
+            )
+            .await?;
+        Ok(response.dissolve().0)
+    }
+
+    /// Get a [`Realm`], i.e. an institution, by ID.
+    pub async fn get_realm(&self, id: impl Into<u64>) -> Result<Realm> {
+        let endpoint = format!("/api/realms/{}", id.into());
+        let response: RealmResponse = self.get(&endpoint, None::<EmptyParams>).await?;
+        Ok(response.dissolve().0)
+    }
+
+    /// Update a realm's settings and theme, returning the updated [`Realm`]. Realm admins only.
+    pub async fn update_realm_settings(
+        &self,
+        id: impl Into<u64>,
+        patch: &RealmSettingsPatch,
+    ) -> Result<Realm> {
+        let endpoint = format!("/api/realms/{}/settings", id.into());
+        let response: RealmResponse = self.put(&endpoint, &patch.as_body()).await?;
+        Ok(response.dissolve().0)
+    }
+
+    /// Get every course in a realm, including inactive ones. Realm admins only.
+    pub async fn get_realm_courses(&self, realm_id: impl Into<u64>) -> Result<Vec<Course>> {
+        let endpoint = format!("/api/realms/{}/courses", realm_id.into());
+        let response: RealmCoursesResponse = self.get(&endpoint, None::<EmptyParams>).await?;
+        Ok(response.dissolve().0)
+    }
+
+    /// Create a course in a realm. Realm admins only.
+    ///
+    /// Fails with [`Error::Refused`] without creating anything if the realm does not allow
+    /// course creation, or `course` is for an LMS and the realm does not allow that.
+    pub async fn create_realm_course(
+        &self,
+        realm_id: impl Into<u64>,
+        course: &NewCourse,
+    ) -> Result<Course> {
+        let realm_id = realm_id.into();
+        let realm = self.get_realm(realm_id).await?;
+        let settings = realm.settings();
+        if !*settings.allow_course_creation() {
+            return Err(Error::Refused(format!(
+                "realm {realm_id} does not allow course creation"
+            )));
+        }
+        if course.lti_context_id.is_some() && !*settings.lti_and_course_creation() {
+            return Err(Error::Refused(format!(
+                "realm {realm_id} does not allow course creation for an LMS"
+            )));
+        }
+
+        let endpoint = format!("/api/realms/{realm_id}/courses");
+        let response: CourseResponse = self.post(&endpoint, &course.as_body(settings)).await?;
+        Ok(response.dissolve().0)
+    }
+
+    /// Get every user in a realm. Realm admins only.
+    pub async fn get_realm_users(&self, realm_id: impl Into<u64>) -> Result<Vec<RealmUser>> {
+        let endpoint = format!("/api/realms/{}/users", realm_id.into());
+        let response: RealmUsersResponse = self.get(&endpoint, None::<EmptyParams>).await?;
+        Ok(response.dissolve().0)
+    }
+
+    /// Get a [`Course`] by ID.
+    pub async fn get_course(&self, id: impl Into<u64>) -> Result<Course> {
+        let endpoint = format!("/api/courses/{}", id.into());
+        let response: CourseResponse = self.get(&endpoint, None::<EmptyParams>).await?;
+        Ok(response.dissolve().0)
+    }
+
+    /// Preview the changes [`Client::update_course_settings`] would make with `patch`, without
+    /// making them.
+    pub async fn preview_course_settings(
+        &self,
+        id: impl Into<u64>,
+        patch: &CourseDiscussionSettingsPatch,
+    ) -> Result<Vec<SettingChange>> {
+        let course = self.get_course(id).await?;
+        Ok(patch.diff(course.settings().discussion()))
+    }
+
+    /// Update a course's discussion settings, returning the updated [`Course`]. Staff only.
+    pub async fn update_course_settings(
+        &self,
+        id: impl Into<u64>,
+        patch: &CourseDiscussionSettingsPatch,
+    ) -> Result<Course> {
+        let endpoint = format!("/api/courses/{}/settings", id.into());
+        let response: CourseResponse = self.put(&endpoint, &patch.as_body()).await?;
+        Ok(response.dissolve().0)
+    }
+
+    /// Get a course's saved replies, in order.
+    pub async fn get_saved_replies(&self, course_id: impl Into<u64>) -> Result<Vec<String>> {
+        let course = self.get_course(course_id).await?;
+        Ok(course.settings().discussion().saved_replies().clone())
+    }
+
+    /// Replace a course's saved replies, returning the new list. Staff only.
+    pub async fn set_saved_replies(
+        &self,
+        course_id: impl Into<u64>,
+        saved_replies: Vec<String>,
+    ) -> Result<Vec<String>> {
+        let patch = CourseDiscussionSettingsPatch {
+            saved_replies: Some(saved_replies),
+            ..Default::default()
+        };
+        let course = self.update_course_settings(course_id, &patch).await?;
+        Ok(course.settings().discussion().saved_replies().clone())
+    }
+
+    /// Add a saved reply to the end of a course's list, returning the new list. Staff only.
+    ///
+    /// Ed Discussion only stores the list as a whole, so this fetches it and writes it back
+    /// changed. Changes made by someone else in between are lost; the same goes for
+    /// [`Self::update_saved_reply`] and [`Self::remove_saved_reply`].
+    pub async fn add_saved_reply(
+        &self,
+        course_id: impl Into<u64>,
+        text: &str,
+    ) -> Result<Vec<String>> {
+        let course_id = course_id.into();
+        let mut saved_replies = self.get_saved_replies(course_id).await?;
+        saved_replies.push(String::from(text));
+        self.set_saved_replies(course_id, saved_replies).await
+    }
+
+    /// Replace the text of the saved reply at `index`, returning the new list. Staff only.
+    ///
+    /// Fails with [`Error::NotFound`] if there is no saved reply at `index`. Like
+    /// [`Self::add_saved_reply`], this can overwrite concurrent changes to the list.
+    pub async fn update_saved_reply(
+        &self,
+        course_id: impl Into<u64>,
+        index: usize,
+        text: &str,
+    ) -> Result<Vec<String>> {
+        let course_id = course_id.into();
+        let mut saved_replies = self.get_saved_replies(course_id).await?;
+        let Some(saved_reply) = saved_replies.get_mut(index) else {
+            return Err(Error::NotFound(format!("no saved reply at index {index}")));
+        };
+        *saved_reply = String::from(text);
+        self.set_saved_replies(course_id, saved_replies).await
+    }
+
+    /// Remove the saved reply at `index`, returning the new list. Staff only.
+    ///
+    /// Fails with [`Error::NotFound`] if there is no saved reply at `index`. Like
+    /// [`Self::add_saved_reply`], this can overwrite concurrent changes to the list.
+    pub async fn remove_saved_reply(
+        &self,
+        course_id: impl Into<u64>,
+        index: usize,
+    ) -> Result<Vec<String>> {
+        let course_id = course_id.into();
+        let mut saved_replies = self.get_saved_replies(course_id).await?;
+        if index >= saved_replies.len() {
+            return Err(Error::NotFound(format!("no saved reply at index {index}")));
+        }
+        saved_replies.remove(index);
+        self.set_saved_replies(course_id, saved_replies).await
+    }
+
+    /// The web address of a thread, for linking to it.
+    pub fn thread_url(&self, course_id: impl Into<u64>, thread_id: impl Into<u64>) -> String {
+        let path = format!(
+            "/courses/{}/discussion/{}",
+            course_id.into(),
+            thread_id.into()
+        );
+
+        // the API lives at e.g. us.edstem.org, but the site at edstem.org/us
+        let region = Url::parse(&self.base_url).ok().and_then(|url| {
+            url.host_str()
+                .and_then(|host| host.strip_suffix(".edstem.org"))
+                .map(String::from)
+        });
+        match region {
+            Some(region) => format!("https://edstem.org/{region}{path}"),
+            None => format!("{}{path}", self.base_url.trim_end_matches('/')),
+        }
     }
 
     /// Get the [`CourseThreads`] pertaining to a course.
@@ -122,18 +629,177 @@ impl Client {
         options: Option<GetCourseThreadsOptions>,
     ) -> Result<CourseThreads> {
         let endpoint = format!("/api/courses/{}/threads", id.into());
-        Ok(self
-            .get(
-                &*endpoint,
-                options.as_ref().map(|o| o.as_params()).as_deref(),
-            )
-            .await?)
+        self.get(
+            &endpoint,
+            options.as_ref().map(|o| o.as_params()).as_deref(),
+        )
+        .await
+    }
+
+    /// Every thread in a course matching `filter`, fetched page by page with
+    /// [`Client::get_course_threads`].
+    pub async fn get_all_course_threads(
+        &self,
+        id: impl Into<u64>,
+        filter: Option<GetCourseThreadsFilterKey>,
+    ) -> Result<Vec<PartialThread>> {
+        let id = id.into();
+        let mut options = GetCourseThreadsOptions {
+            limit: 100,
+            filter,
+            ..Default::default()
+        };
+        let mut threads = Vec::new();
+
+        loop {
+            let page = self.get_course_threads(id, Some(options.clone())).await?;
+            let count = page.threads().len() as u64;
+            threads.extend(page.dissolve().1);
+            if count < options.limit {
+                return Ok(threads);
+            }
+            options.offset += count;
+        }
+    }
+
+    /// Get the threads in a course awaiting approval. Staff only.
+    pub async fn get_pending_threads(
+        &self,
+        course_id: impl Into<u64>,
+    ) -> Result<Vec<PartialThread>> {
+        let mut threads = self.get_all_course_threads(course_id, None).await?;
+        threads.retain(|t| *t.approved_status() == ApprovalStatus::Pending);
+        Ok(threads)
+    }
+
+    /// Approve a pending thread, making it visible. Staff only.
+    pub async fn approve_thread(&self, id: impl Into<u64>) -> Result<()> {
+        let endpoint = format!("/api/threads/{}/approve", id.into());
+        let _: IgnoredAny = self.post(&endpoint, &serde_json::json!({})).await?;
+        Ok(())
+    }
+
+    /// Reject a pending thread, optionally explaining why to its author. Staff only.
+    pub async fn reject_thread(
+        &self,
+        id: impl Into<u64>,
+        comment: Option<&Document>,
+    ) -> Result<()> {
+        let endpoint = format!("/api/threads/{}/reject", id.into());
+        let body = serde_json::json!({ "comment": comment.map(Document::to_xml) });
+        let _: IgnoredAny = self.post(&endpoint, &body).await?;
+        Ok(())
+    }
+
+    /// Reject a pending thread with the course's rejection comment template filled in for it,
+    /// see [`templates`]. Without a template, the thread is rejected without comment. Staff only.
+    pub async fn reject_thread_with_template(
+        &self,
+        thread: &PartialThread,
+        author: Option<&ThreadParticipant>,
+    ) -> Result<()> {
+        let course = self.get_course(*thread.course_id()).await?;
+        let comment = match course.settings().discussion().rejection_comment_template() {
+            Some(template) if !template.trim().is_empty() => {
+                let context = TemplateContext::for_partial_thread(self, thread, author);
+                Some(ReplyTemplate::new(template).to_document(&context)?)
+            }
+            _ => None,
+        };
+        self.reject_thread(*thread.id(), comment.as_ref()).await
+    }
+
+    /// Flag a thread for staff attention.
+    pub async fn flag_thread(&self, id: impl Into<u64>, reason: &str) -> Result<()> {
+        let endpoint = format!("/api/threads/{}/flag", id.into());
+        let _: IgnoredAny = self
+            .post(&endpoint, &serde_json::json!({ "reason": reason }))
+            .await?;
+        Ok(())
+    }
+
+    /// Flag a reply for staff attention.
+    pub async fn flag_reply(&self, id: impl Into<u64>, reason: &str) -> Result<()> {
+        let endpoint = format!("/api/comments/{}/flag", id.into());
+        let _: IgnoredAny = self
+            .post(&endpoint, &serde_json::json!({ "reason": reason }))
+            .await?;
+        Ok(())
+    }
+
+    /// Clear all flags on a thread. Staff only.
+    pub async fn clear_thread_flags(&self, id: impl Into<u64>) -> Result<()> {
+        let endpoint = format!("/api/threads/{}/unflag", id.into());
+        let _: IgnoredAny = self.post(&endpoint, &serde_json::json!({})).await?;
+        Ok(())
+    }
+
+    /// Clear all flags on a reply. Staff only.
+    pub async fn clear_reply_flags(&self, id: impl Into<u64>) -> Result<()> {
+        let endpoint = format!("/api/comments/{}/unflag", id.into());
+        let _: IgnoredAny = self.post(&endpoint, &serde_json::json!({})).await?;
+        Ok(())
+    }
+
+    /// Get the flagged threads in a course, most flagged first. Staff only.
+    pub async fn get_flagged_threads(
+        &self,
+        course_id: impl Into<u64>,
+    ) -> Result<Vec<PartialThread>> {
+        let threads = self.get_all_course_threads(course_id, None).await?;
+        Ok(ModerationReport::new(&threads).threads)
+    }
+
+    /// Collect everything flagged in a course. Staff only.
+    pub async fn moderation_report(
+        &self,
+        course_id: impl Into<u64>,
+        options: ModerationReportOptions,
+    ) -> Result<ModerationReport> {
+        let threads = self.get_all_course_threads(course_id, None).await?;
+        let mut report = ModerationReport::new(&threads);
+
+        if options.include_replies {
+            for thread in threads.iter().filter(|t| *t.reply_count() > 0) {
+                let (full, _, _) = self.get_thread(*thread.id()).await?.dissolve();
+                report.add_replies(thread, &full);
+            }
+            report.sort_replies();
+        }
+        Ok(report)
+    }
+
+    /// Compute [`ForumReport`] statistics over every thread in a course. Staff only.
+    ///
+    /// If `options.tz` is left as `"UTC"`, activity is counted in the requesting user's `tz`
+    /// setting instead. This fetches every thread individually, so is slow in a large course.
+    #[cfg(feature = "analytics")]
+    pub async fn forum_report(
+        &self,
+        course_id: impl Into<u64>,
+        mut options: AnalyticsOptions,
+    ) -> Result<ForumReport> {
+        let course_id = course_id.into();
+        let course = self.get_course(course_id).await?;
+        if options.tz == "UTC" {
+            let self_user = self.get_self_user().await?;
+            let tz = self_user.user().settings().tz();
+            if !tz.is_empty() {
+                options.tz = tz.clone();
+            }
+        }
+
+        let mut threads = Vec::new();
+        for thread in self.get_all_course_threads(course_id, None).await? {
+            threads.push(self.get_thread(*thread.id()).await?);
+        }
+        ForumReport::new(&threads, Some(course.settings().role_labels()), &options)
     }
 
     /// Get a [`Thread`] by ID.
     pub async fn get_thread(&self, id: impl Into<u64>) -> Result<ThreadResponse> {
         let endpoint = format!("/api/threads/{}", id.into());
-        Ok(self.get(&*endpoint, None::<EmptyParams>).await?)
+        self.get(&endpoint, None::<EmptyParams>).await
     }
 
     /// Get a [`Thread`] by its number in its course.
@@ -149,6 +815,369 @@ impl Client {
             course_id.into(),
             thread_number
         );
-        Ok(self.get(&*endpoint, None::<EmptyParams>).await?)
+        self.get(&endpoint, None::<EmptyParams>).await
+    }
+
+    /// Reply to a thread, as either a comment or an answer.
+    pub async fn post_thread_reply(
+        &self,
+        thread_id: impl Into<u64>,
+        content: &Document,
+        options: PostReplyOptions,
+    ) -> Result<ReplyResponse> {
+        let endpoint = format!("/api/threads/{}/comments", thread_id.into());
+        self.post(&endpoint, &options.as_body(content)).await
+    }
+
+    /// Reply to an existing comment or answer, nesting the new comment beneath it.
+    pub async fn post_comment_reply(
+        &self,
+        reply_id: impl Into<u64>,
+        content: &Document,
+        options: PostReplyOptions,
+    ) -> Result<ReplyResponse> {
+        let endpoint = format!("/api/comments/{}/comments", reply_id.into());
+        self.post(&endpoint, &options.as_body(content)).await
+    }
+
+    /// Get the chat channels in a course.
+    pub async fn get_chat_channels(&self, course_id: impl Into<u64>) -> Result<Vec<Channel>> {
+        let endpoint = format!("/api/courses/{}/chat/channels", course_id.into());
+        let response: ChannelsResponse = self.get(&endpoint, None::<EmptyParams>).await?;
+        Ok(response.dissolve().0)
+    }
+
+    /// Get the requesting user's direct conversations in a course.
+    pub async fn get_direct_conversations(
+        &self,
+        course_id: impl Into<u64>,
+    ) -> Result<Vec<DirectConversation>> {
+        let endpoint = format!("/api/courses/{}/chat/direct", course_id.into());
+        let response: DirectConversationsResponse =
+            self.get(&endpoint, None::<EmptyParams>).await?;
+        Ok(response.dissolve().0)
+    }
+
+    /// Get a page of messages in a channel or direct conversation, newest first.
+    pub async fn get_chat_messages(
+        &self,
+        channel_id: impl Into<u64>,
+        options: Option<ChatHistoryOptions>,
+    ) -> Result<ChatMessages> {
+        let endpoint = format!("/api/chat/channels/{}/messages", channel_id.into());
+        let params = options.unwrap_or_default().as_params();
+        self.get(&endpoint, Some(&params)).await
+    }
+
+    /// Get every message in a channel or direct conversation, oldest first.
+    pub async fn get_all_chat_messages(&self, channel_id: impl Into<u64>) -> Result<ChatHistory> {
+        let channel_id = channel_id.into();
+        let mut options = ChatHistoryOptions {
+            limit: 100,
+            ..Default::default()
+        };
+        let mut history = ChatHistory::default();
+
+        loop {
+            let (mut messages, users, _) = self
+                .get_chat_messages(channel_id, Some(options.clone()))
+                .await?
+                .dissolve();
+            let count = messages.len() as u64;
+            options.before = messages.last().map(|m| (*m.id()).into());
+
+            messages.reverse();
+            messages.append(&mut history.messages);
+            history.messages = messages;
+            for user in users {
+                if !history.users.iter().any(|u| u.id() == user.id()) {
+                    history.users.push(user);
+                }
+            }
+
+            if count < options.limit {
+                return Ok(history);
+            }
+        }
+    }
+
+    /// Send a message to a channel or direct conversation.
+    pub async fn send_chat_message(
+        &self,
+        channel_id: impl Into<u64>,
+        content: &Document,
+    ) -> Result<ChatMessage> {
+        let endpoint = format!("/api/chat/channels/{}/messages", channel_id.into());
+        let body = serde_json::json!({"message": {"content": content.to_xml()}});
+        let response: ChatMessageResponse = self.post(&endpoint, &body).await?;
+        Ok(response.dissolve().0)
+    }
+
+    /// Get the lessons and lesson modules in a course, without their slides.
+    pub async fn get_course_lessons(&self, course_id: impl Into<u64>) -> Result<CourseLessons> {
+        let endpoint = format!("/api/courses/{}/lessons", course_id.into());
+        self.get(&endpoint, None::<EmptyParams>).await
+    }
+
+    /// Get a [`Lesson`] by ID, with its slides.
+    pub async fn get_lesson(&self, id: impl Into<u64>) -> Result<Lesson> {
+        let endpoint = format!("/api/lessons/{}", id.into());
+        let response: LessonResponse = self.get(&endpoint, None::<EmptyParams>).await?;
+        Ok(response.dissolve().0)
+    }
+
+    /// Get everyone enrolled in a course, with their email addresses. Staff only.
+    pub async fn get_course_members(&self, course_id: impl Into<u64>) -> Result<Vec<CourseMember>> {
+        let endpoint = format!("/api/courses/{}/admin/users", course_id.into());
+        let response: CourseMembersResponse = self.get(&endpoint, None::<EmptyParams>).await?;
+        Ok(response.dissolve().0)
+    }
+
+    /// Get the labs, i.e. tutorial groups, in a course.
+    pub async fn get_labs(&self, course_id: impl Into<u64>) -> Result<Vec<Lab>> {
+        let endpoint = format!("/api/courses/{}/labs", course_id.into());
+        let response: LabsResponse = self.get(&endpoint, None::<EmptyParams>).await?;
+        Ok(response.dissolve().0)
+    }
+
+    /// Get everyone in a lab, staff included. Staff only.
+    pub async fn get_lab_members(&self, lab_id: impl Into<u64>) -> Result<Vec<CourseMember>> {
+        let endpoint = format!("/api/labs/{}/users", lab_id.into());
+        let response: CourseMembersResponse = self.get(&endpoint, None::<EmptyParams>).await?;
+        Ok(response.dissolve().0)
+    }
+
+    async fn set_user_lab(
+        &self,
+        course_id: u64,
+        user_id: u64,
+        lab_id: Option<u64>,
+    ) -> Result<CourseMember> {
+        let endpoint = format!("/api/courses/{course_id}/admin/users/{user_id}");
+        let body = serde_json::json!({ "lab_id": lab_id });
+        let response: CourseMemberResponse = self.put(&endpoint, &body).await?;
+        Ok(response.dissolve().0)
+    }
+
+    /// Move a user into a lab, out of any lab they were in. Staff only.
+    pub async fn move_to_lab(
+        &self,
+        course_id: impl Into<u64>,
+        user_id: impl Into<u64>,
+        lab_id: impl Into<u64>,
+    ) -> Result<CourseMember> {
+        self.set_user_lab(course_id.into(), user_id.into(), Some(lab_id.into()))
+            .await
+    }
+
+    /// Take a user out of whichever lab they are in. Staff only.
+    pub async fn remove_from_lab(
+        &self,
+        course_id: impl Into<u64>,
+        user_id: impl Into<u64>,
+    ) -> Result<CourseMember> {
+        self.set_user_lab(course_id.into(), user_id.into(), None)
+            .await
+    }
+
+    /// Get the threads in a course started by the students in a lab. Staff only.
+    ///
+    /// Threads are matched by author, so anonymous threads are only included if the course
+    /// reveals their authors to you.
+    pub async fn get_lab_threads(
+        &self,
+        course_id: impl Into<u64>,
+        lab_id: impl Into<u64>,
+    ) -> Result<Vec<PartialThread>> {
+        let students: HashSet<u64> = self
+            .get_lab_members(lab_id)
+            .await?
+            .iter()
+            .filter(|m| *m.course_role() == Role::Student)
+            .map(|m| (*m.id()).into())
+            .collect();
+
+        let mut threads = self.get_all_course_threads(course_id, None).await?;
+        threads.retain(|t| students.contains(&u64::from(*t.user_id())));
+        Ok(threads)
+    }
+
+    /// Get every student's progress through a lesson. Staff only.
+    pub async fn get_lesson_progress(
+        &self,
+        lesson_id: impl Into<u64>,
+    ) -> Result<Vec<LessonProgress>> {
+        let endpoint = format!("/api/lessons/{}/progress", lesson_id.into());
+        let response: LessonProgressResponse = self.get(&endpoint, None::<EmptyParams>).await?;
+        Ok(response.dissolve().0)
+    }
+
+    /// Get every student's latest answers to the quiz questions in a lesson. Staff only.
+    pub async fn get_quiz_responses(&self, lesson_id: impl Into<u64>) -> Result<Vec<QuizResponse>> {
+        let endpoint = format!("/api/lessons/{}/quiz_responses", lesson_id.into());
+        let response: QuizResponsesResponse = self.get(&endpoint, None::<EmptyParams>).await?;
+        Ok(response.dissolve().0)
+    }
+
+    /// Collect every student's lesson progress and quiz scores in a course. Staff only.
+    ///
+    /// Lessons are in the order shown in the lesson list. This fetches each lesson's results
+    /// individually, so is slow in a course with many lessons.
+    pub async fn gradebook(&self, course_id: impl Into<u64>) -> Result<Gradebook> {
+        let course_id = course_id.into();
+        let mut gradebook = Gradebook::new(&self.get_course_members(course_id).await?);
+
+        let lessons = self.get_course_lessons(course_id).await?;
+        for (_, in_module) in lessons.by_module() {
+            for lesson in in_module {
+                let full = self.get_lesson(*lesson.id()).await?;
+                let progress = self.get_lesson_progress(*lesson.id()).await?;
+                let responses = self.get_quiz_responses(*lesson.id()).await?;
+                gradebook.add_lesson(&full, &progress, &responses);
+            }
+        }
+        Ok(gradebook)
+    }
+
+    /// Get a code challenge by ID, with its scaffold files.
+    pub async fn get_challenge(&self, id: impl Into<u64>) -> Result<Challenge> {
+        let endpoint = format!("/api/challenges/{}", id.into());
+        let response: ChallengeResponse = self.get(&endpoint, None::<EmptyParams>).await?;
+        Ok(response.dissolve().0)
+    }
+
+    /// Get your own submissions to a code challenge, newest first.
+    pub async fn get_challenge_submissions(
+        &self,
+        challenge_id: impl Into<u64>,
+    ) -> Result<Vec<Submission>> {
+        let endpoint = format!("/api/challenges/{}/submissions", challenge_id.into());
+        let response: SubmissionsResponse = self.get(&endpoint, None::<EmptyParams>).await?;
+        Ok(response.dissolve().0)
+    }
+
+    /// Submit source files for marking against a code challenge. The returned submission is
+    /// usually still queued; see [`Self::wait_for_submission`].
+    pub async fn submit_challenge(
+        &self,
+        challenge_id: impl Into<u64>,
+        files: &[SourceFile],
+    ) -> Result<Submission> {
+        let endpoint = format!("/api/challenges/{}/submissions", challenge_id.into());
+        let body = serde_json::json!({ "files": files });
+        let response: SubmissionResponse = self.post(&endpoint, &body).await?;
+        Ok(response.dissolve().0)
+    }
+
+    /// Get a challenge submission by ID, with its test case results once marked.
+    pub async fn get_submission(&self, id: impl Into<u64>) -> Result<Submission> {
+        let endpoint = format!("/api/submissions/{}", id.into());
+        let response: SubmissionResponse = self.get(&endpoint, None::<EmptyParams>).await?;
+        Ok(response.dissolve().0)
+    }
+
+    /// Check a submission every `options.interval` until marking finishes, successfully or
+    /// not, failing with [`Error::Timeout`] after `options.timeout`.
+    pub async fn wait_for_submission(
+        &self,
+        id: impl Into<u64>,
+        options: SubmissionPollOptions,
+    ) -> Result<Submission> {
+        let id = id.into();
+        let deadline = options.timeout.map(|t| std::time::Instant::now() + t);
+        loop {
+            let submission = self.get_submission(id).await?;
+            if !submission.status().is_pending() {
+                return Ok(submission);
+            }
+            if deadline.is_some_and(|d| std::time::Instant::now() + options.interval > d) {
+                return Err(Error::Timeout(format!(
+                    "submission {id} is still being marked"
+                )));
+            }
+            tokio::time::sleep(options.interval).await;
+        }
+    }
+
+    /// Submit source files for a code challenge and wait for them to be marked. See
+    /// [`Self::submit_challenge`] and [`Self::wait_for_submission`].
+    pub async fn submit_challenge_and_wait(
+        &self,
+        challenge_id: impl Into<u64>,
+        files: &[SourceFile],
+        options: SubmissionPollOptions,
+    ) -> Result<Submission> {
+        let submission = self.submit_challenge(challenge_id, files).await?;
+        self.wait_for_submission(*submission.id(), options).await
+    }
+
+    /// Get your own workspaces in a course.
+    pub async fn get_workspaces(&self, course_id: impl Into<u64>) -> Result<Vec<Workspace>> {
+        let endpoint = format!("/api/courses/{}/workspaces", course_id.into());
+        let response: WorkspacesResponse = self.get(&endpoint, None::<EmptyParams>).await?;
+        Ok(response.dissolve().0)
+    }
+
+    /// Get a [`Workspace`] by ID.
+    pub async fn get_workspace(&self, id: impl Into<u64>) -> Result<Workspace> {
+        let endpoint = format!("/api/workspaces/{}", id.into());
+        let response: WorkspaceResponse = self.get(&endpoint, None::<EmptyParams>).await?;
+        Ok(response.dissolve().0)
+    }
+
+    /// Get every file and directory in a workspace, at any depth.
+    pub async fn get_workspace_files(
+        &self,
+        workspace_id: impl Into<u64>,
+    ) -> Result<Vec<WorkspaceFile>> {
+        let endpoint = format!("/api/workspaces/{}/files", workspace_id.into());
+        let response: WorkspaceFilesResponse = self.get(&endpoint, None::<EmptyParams>).await?;
+        Ok(response.dissolve().0)
+    }
+
+    /// Download the contents of the file at `path` in a workspace.
+    pub async fn download_workspace_file(
+        &self,
+        workspace_id: impl Into<u64>,
+        path: &str,
+    ) -> Result<Vec<u8>> {
+        let endpoint = format!("/api/workspaces/{}/files/raw", workspace_id.into());
+        let mut request = self.build_request(Method::GET, &endpoint)?;
+        request.url.query_pairs_mut().append_pair("path", path);
+        self.request_raw(request).await
+    }
+
+    /// Create or replace the file at `path` in a workspace, creating directories as needed.
+    pub async fn upload_workspace_file(
+        &self,
+        workspace_id: impl Into<u64>,
+        path: &str,
+        content: &[u8],
+    ) -> Result<WorkspaceFile> {
+        let endpoint = format!("/api/workspaces/{}/files/raw", workspace_id.into());
+        let mut request = self.build_request(Method::PUT, &endpoint)?;
+        request.url.query_pairs_mut().append_pair("path", path);
+        request.headers.insert(
+            CONTENT_TYPE,
+            HeaderValue::from_static("application/octet-stream"),
+        );
+        request.body = Some(content.to_vec());
+
+        let response: WorkspaceFileResponse = self.request(request).await?;
+        Ok(response.dissolve().0)
+    }
+
+    /// Delete the file or directory at `path` in a workspace. Directories are deleted with
+    /// everything in them.
+    pub async fn delete_workspace_file(
+        &self,
+        workspace_id: impl Into<u64>,
+        path: &str,
+    ) -> Result<()> {
+        let endpoint = format!("/api/workspaces/{}/files", workspace_id.into());
+        let _: IgnoredAny = self
+            .delete(&endpoint, Some(&[("path", String::from(path))]))
+            .await?;
+        Ok(())
     }
 }
@@ -0,0 +1,423 @@
+//! Finding, resolving and writing @-mentions in post bodies.
+//!
+//! A mention is an [`Inline::Mention`] in a [`Document`], naming a user by ID. Posting one
+//! notifies that user, subject to their `email_mentions` and desktop notification settings.
+//! Text typed as `@Name` is only a mention once it has been resolved to a user, which a
+//! [`MentionResolver`] does by matching against a course's roster or the users listed alongside
+//! its threads.
+//!
+//! ```no_run
+//! # async fn example(client: edstem::Client) -> edstem::Result<()> {
+//! use edstem::{document::Document, mentions::MentionResolver};
+//!
+//! let resolver = MentionResolver::from_roster(&client.get_course_members(1234u64).await?);
+//!
+//! // notice staff being pinged in a thread
+//! let thread = client.get_thread(5678u64).await?.dissolve().0;
+//! for mention in edstem::mentions::thread_mentions(&thread)? {
+//!     if resolver.is_staff(mention.user_id) {
+//!         println!("{} was mentioned", mention.name);
+//!     }
+//! }
+//!
+//! // mention someone in a reply
+//! let mut document = Document::from_plain_text("@Jane Doe could you take a look?");
+//! for unresolved in resolver.resolve_document(&mut document) {
+//!     eprintln!("could not resolve @{}", unresolved.text);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    document::{Document, Inline},
+    model::{
+        course::Role,
+        thread::{ReplyID, Thread},
+        user::{CourseMember, ThreadParticipant, UserID},
+    },
+};
+
+/// A mention found in a thread or one of its replies.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ThreadMention {
+    /// The mentioned user's ID.
+    pub user_id: u64,
+    /// The name shown for the mentioned user.
+    pub name: String,
+    /// The reply containing the mention, or `None` for the thread itself.
+    pub reply_id: Option<ReplyID>,
+    /// The author of the post containing the mention; 0 if anonymous.
+    pub author_id: UserID,
+}
+
+/// Every mention in `thread` and its replies, the thread's own first and then the replies' in
+/// the order of [`Thread::replies`].
+pub fn thread_mentions(thread: &Thread) -> crate::Result<Vec<ThreadMention>> {
+    let posts = std::iter::once((None, *thread.user_id(), thread.content())).chain(
+        thread
+            .replies()
+            .map(|(_, r)| (Some(*r.id()), *r.user_id(), r.content())),
+    );
+
+    let mut out = Vec::new();
+    for (reply_id, author_id, content) in posts {
+        let document = Document::parse(content)?;
+        out.extend(
+            document
+                .mentions()
+                .into_iter()
+                .map(|(user_id, name)| ThreadMention {
+                    user_id,
+                    name: String::from(name),
+                    reply_id,
+                    author_id,
+                }),
+        );
+    }
+    Ok(out)
+}
+
+/// `@text` which could not be made into a mention.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnresolvedMention {
+    /// The text after the `@`, up to the end of the word if no name matched.
+    pub text: String,
+    /// The users it could refer to; empty if none.
+    pub candidates: Vec<UserID>,
+}
+
+#[derive(Clone, Debug)]
+struct Candidate {
+    id: UserID,
+    name: String,
+    role: Option<Role>,
+}
+
+/// Users by name, for turning `@Name` into mentions.
+///
+/// `@` followed by a user's full name, ignoring case, mentions that user; where several names
+/// match, the longest wins. Failing that, `@` followed by a single word mentions the only user
+/// whose first name it is. An `@` straight after a letter or digit, as in an email address, is
+/// never a mention.
+#[derive(Clone, Debug, Default)]
+pub struct MentionResolver {
+    users: Vec<Candidate>,
+}
+
+impl FromIterator<(UserID, String)> for MentionResolver {
+    fn from_iter<I: IntoIterator<Item = (UserID, String)>>(iter: I) -> Self {
+        Self {
+            users: iter
+                .into_iter()
+                .map(|(id, name)| Candidate {
+                    id,
+                    name,
+                    role: None,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl MentionResolver {
+    /// Resolve against a course's roster, from
+    /// [`Client::get_course_members`](crate::Client::get_course_members).
+    pub fn from_roster(members: &[CourseMember]) -> Self {
+        Self {
+            users: members
+                .iter()
+                .map(|m| Candidate {
+                    id: *m.id(),
+                    name: m.name().clone(),
+                    role: Some(m.course_role().clone()),
+                })
+                .collect(),
+        }
+    }
+
+    /// Resolve against the users listed with threads, e.g. `CourseThreads::users`. This needs no
+    /// staff access, but only knows users who have posted.
+    pub fn from_participants(users: &[ThreadParticipant]) -> Self {
+        Self {
+            users: users
+                .iter()
+                .map(|u| Candidate {
+                    id: *u.id(),
+                    name: u.name().clone(),
+                    role: u.course_role().clone(),
+                })
+                .collect(),
+        }
+    }
+
+    /// The course role of the user with ID `id`, if known.
+    pub fn role(&self, id: impl Into<u64>) -> Option<&Role> {
+        let id = id.into();
+        self.users
+            .iter()
+            .find(|u| u64::from(u.id) == id)
+            .and_then(|u| u.role.as_ref())
+    }
+
+    /// Whether the user with ID `id` is known to be a mentor, tutor, staff or admin.
+    pub fn is_staff(&self, id: impl Into<u64>) -> bool {
+        self.role(id).is_some_and(Role::is_staff)
+    }
+
+    /// The user `name` refers to: the only one with that full name, or failing that the only one
+    /// with that first name, ignoring case.
+    pub fn resolve(&self, name: &str) -> Result<UserID, UnresolvedMention> {
+        let name = name.trim();
+        let full = self.matching(|u| u.name.trim().to_lowercase() == name.to_lowercase());
+        let candidates = if full.is_empty() {
+            self.matching(|u| {
+                u.name
+                    .split_whitespace()
+                    .next()
+                    .is_some_and(|first| first.to_lowercase() == name.to_lowercase())
+            })
+        } else {
+            full
+        };
+
+        match candidates.as_slice() {
+            [id] => Ok(*id),
+            _ => Err(UnresolvedMention {
+                text: String::from(name),
+                candidates,
+            }),
+        }
+    }
+
+    /// Turn each `@Name` in `text` into a mention, leaving any which cannot be resolved as text.
+    pub fn resolve_text(&self, text: &str) -> (Vec<Inline>, Vec<UnresolvedMention>) {
+        let mut inlines = Vec::new();
+        let mut unresolved = Vec::new();
+        let mut literal = String::new();
+        let mut rest = text;
+
+        while let Some(at) = rest.find('@') {
+            let (before, after) = (&rest[..at], &rest[at + 1..]);
+            literal.push_str(before);
+            let attached = literal.chars().last().is_some_and(char::is_alphanumeric);
+            if attached {
+                literal.push('@');
+                rest = after;
+                continue;
+            }
+
+            match self.resolve_prefix(after) {
+                Ok((candidate, len)) => {
+                    if !literal.is_empty() {
+                        inlines.push(Inline::Text(std::mem::take(&mut literal)));
+                    }
+                    inlines.push(Inline::Mention {
+                        user_id: u64::from(candidate.id),
+                        name: candidate.name.clone(),
+                    });
+                    rest = &after[len..];
+                }
+                Err(missed) => {
+                    unresolved.extend(missed);
+                    literal.push('@');
+                    rest = after;
+                }
+            }
+        }
+
+        literal.push_str(rest);
+        if !literal.is_empty() {
+            inlines.push(Inline::Text(literal));
+        }
+        (inlines, unresolved)
+    }
+
+    /// Turn each `@Name` in the text of `document` into a mention, returning those which could
+    /// not be resolved. Text in links and code is left alone.
+    pub fn resolve_document(&self, document: &mut Document) -> Vec<UnresolvedMention> {
+        let mut unresolved = Vec::new();
+        document.rewrite_text(|text| {
+            let (inlines, mut missed) = self.resolve_text(&text);
+            unresolved.append(&mut missed);
+            inlines
+        });
+        unresolved
+    }
+
+    fn matching(&self, f: impl Fn(&Candidate) -> bool) -> Vec<UserID> {
+        let mut ids: Vec<UserID> = Vec::new();
+        for user in self.users.iter().filter(|u| f(u)) {
+            if !ids.contains(&user.id) {
+                ids.push(user.id);
+            }
+        }
+        ids
+    }
+
+    /// The user named at the start of `text`, which follows an `@`, and the length of the name
+    /// in `text`. `Err(None)` if `text` does not start with a word at all.
+    fn resolve_prefix(&self, text: &str) -> Result<(&Candidate, usize), Option<UnresolvedMention>> {
+        let mut best: Option<(usize, Vec<&Candidate>)> = None;
+        for user in &self.users {
+            let Some(len) = prefix_len(text, user.name.trim()) else {
+                continue;
+            };
+            match &mut best {
+                Some((best_len, users)) if *best_len == len => {
+                    if !users.iter().any(|u| u.id == user.id) {
+                        users.push(user);
+                    }
+                }
+                Some((best_len, _)) if *best_len > len => {}
+                _ => best = Some((len, vec![user])),
+            }
+        }
+        if let Some((len, users)) = best {
+            return match users.as_slice() {
+                [user] => Ok((user, len)),
+                _ => Err(Some(UnresolvedMention {
+                    text: String::from(&text[..len]),
+                    candidates: users.iter().map(|u| u.id).collect(),
+                })),
+            };
+        }
+
+        let word_len = text.find(|c: char| !is_name_char(c)).unwrap_or(text.len());
+        if word_len == 0 {
+            return Err(None);
+        }
+        let word = &text[..word_len];
+        match self.resolve(word) {
+            Ok(id) => {
+                let user = self
+                    .users
+                    .iter()
+                    .find(|u| u.id == id)
+                    .expect("resolved to a known user");
+                Ok((user, word_len))
+            }
+            Err(mention) => Err(Some(mention)),
+        }
+    }
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '-' || c == '\''
+}
+
+/// The length in `text` of `name` if `text` starts with it as whole words, ignoring case.
+fn prefix_len(text: &str, name: &str) -> Option<usize> {
+    if name.is_empty() {
+        return None;
+    }
+    let mut text_chars = text.char_indices();
+    for n in name.chars() {
+        let (_, t) = text_chars.next()?;
+        if !t.to_lowercase().eq(n.to_lowercase()) {
+            return None;
+        }
+    }
+    match text_chars.next() {
+        Some((i, t)) if !is_name_char(t) => Some(i),
+        Some(_) => None,
+        None => Some(text.len()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn resolver() -> MentionResolver {
+        let users: Vec<ThreadParticipant> = [
+            (1, "Jane Doe", "student"),
+            (2, "Jane Smith", "tutor"),
+            (3, "Bob", "student"),
+            (4, "Bob Lee", "admin"),
+        ]
+        .into_iter()
+        .map(|(id, name, course_role)| {
+            serde_json::from_value(json!({
+                "id": id,
+                "role": "user",
+                "name": name,
+                "avatar": null,
+                "course_role": course_role,
+                "tutorials": {},
+            }))
+            .unwrap()
+        })
+        .collect();
+        MentionResolver::from_participants(&users)
+    }
+
+    fn mention(user_id: u64, name: &str) -> Inline {
+        Inline::Mention {
+            user_id,
+            name: String::from(name),
+        }
+    }
+
+    fn text(text: &str) -> Inline {
+        Inline::Text(String::from(text))
+    }
+
+    fn candidates(mention: &UnresolvedMention) -> Vec<u64> {
+        mention.candidates.iter().copied().map(u64::from).collect()
+    }
+
+    #[test]
+    fn full_names_take_the_longest_match() {
+        let (inlines, unresolved) = resolver().resolve_text("@jane doe and @Bob Lee, thanks");
+        assert_eq!(
+            inlines,
+            [
+                mention(1, "Jane Doe"),
+                text(" and "),
+                mention(4, "Bob Lee"),
+                text(", thanks"),
+            ]
+        );
+        assert!(unresolved.is_empty());
+    }
+
+    #[test]
+    fn first_names_must_be_unique() {
+        let (inlines, unresolved) = resolver().resolve_text("@Jane?");
+        assert_eq!(inlines, [text("@Jane?")]);
+        assert_eq!(unresolved.len(), 1);
+        assert_eq!(unresolved[0].text, "Jane");
+        assert_eq!(candidates(&unresolved[0]), [1, 2]);
+    }
+
+    #[test]
+    fn unknown_names_and_addresses_are_left_as_text() {
+        let (inlines, unresolved) = resolver().resolve_text("mail bob@example.com or @nobody @");
+        assert_eq!(inlines, [text("mail bob@example.com or @nobody @")]);
+        assert_eq!(unresolved.len(), 1);
+        assert_eq!(unresolved[0].text, "nobody");
+        assert!(candidates(&unresolved[0]).is_empty());
+    }
+
+    #[test]
+    fn documents_resolve_outside_links_and_code() {
+        let mut document = Document::parse(
+            r#"<document version="2.0"><paragraph>@Bob see <code>@Bob</code> and <link href="https://example.com">@Bob</link></paragraph></document>"#,
+        )
+        .unwrap();
+        assert!(resolver().resolve_document(&mut document).is_empty());
+        assert_eq!(document.mentions(), [(3, "Bob")]);
+    }
+
+    #[test]
+    fn staff_are_known_by_role() {
+        let resolver = resolver();
+        assert!(!resolver.is_staff(1u64));
+        assert!(resolver.is_staff(2u64));
+        assert!(resolver.is_staff(4u64));
+        assert!(!resolver.is_staff(5u64));
+    }
+}
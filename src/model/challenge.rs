@@ -0,0 +1,194 @@
+use derive_getters::{Dissolve, Getters};
+use serde::Deserialize;
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+use crate::{
+    diff::{self, DiffLine},
+    document::Document,
+    schema::UnknownFields,
+};
+
+use super::{
+    course::CourseID,
+    lesson::{LessonID, SlideID},
+    user::UserID,
+};
+
+#[derive(Copy, Clone, Debug, Deserialize, Hash, PartialEq, Eq, Dissolve)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct ChallengeID(u64);
+
+impl From<ChallengeID> for u64 {
+    fn from(value: ChallengeID) -> Self {
+        value.0
+    }
+}
+
+impl ChallengeID {
+    pub async fn get(&self, client: &crate::Client) -> crate::Result<Challenge> {
+        client.get_challenge(*self).await
+    }
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Hash, PartialEq, Eq, Dissolve)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct SubmissionID(u64);
+
+impl From<SubmissionID> for u64 {
+    fn from(value: SubmissionID) -> Self {
+        value.0
+    }
+}
+
+impl SubmissionID {
+    pub async fn get(&self, client: &crate::Client) -> crate::Result<Submission> {
+        client.get_submission(*self).await
+    }
+}
+
+/// a file given to students to start from
+#[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct ChallengeFile {
+    /// relative to the workspace root, with `/` separators
+    path: String,
+    content: String,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<ChallengeFile>,
+}
+
+/// a code challenge, usually attached to a code slide of a lesson
+#[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Challenge {
+    id: ChallengeID,
+    course_id: CourseID,
+    lesson_id: Option<LessonID>,
+    slide_id: Option<SlideID>,
+    title: String,
+    /// XML, like the `content` of threads
+    content: String,
+    /// the scaffold students start from
+    #[serde(default)]
+    files: Vec<ChallengeFile>,
+    created_at: String,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<Challenge>,
+}
+
+impl Challenge {
+    /// Parse [`Self::content`] into the same model as thread bodies.
+    pub fn to_document(&self) -> crate::Result<Document> {
+        Document::parse(&self.content)
+    }
+}
+
+/// GET /api/challenges/:id
+#[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct ChallengeResponse {
+    challenge: Challenge,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<ChallengeResponse>,
+}
+
+/// where a [`Submission`] is in marking
+#[derive(Clone, Debug, Deserialize, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[serde(rename_all = "snake_case")]
+pub enum SubmissionStatus {
+    Queued,
+    Running,
+    /// every test case was run; see [`Submission::results`]
+    Completed,
+    /// the submission could not be marked, e.g. it did not compile; see [`Submission::error`]
+    Failed,
+    #[serde(untagged)]
+    Other(String),
+}
+
+impl SubmissionStatus {
+    /// Whether marking has yet to finish.
+    pub fn is_pending(&self) -> bool {
+        matches!(self, Self::Queued | Self::Running)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct TestCaseResult {
+    name: String,
+    passed: bool,
+    /// hidden test cases never include their outputs
+    is_hidden: bool,
+    score: f64,
+    max_score: f64,
+    expected_output: Option<String>,
+    actual_output: Option<String>,
+    /// e.g. a runtime error or the marker's explanation
+    feedback: Option<String>,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<TestCaseResult>,
+}
+
+impl TestCaseResult {
+    /// The line diff from the expected to the actual output, if both are shown.
+    pub fn diff(&self) -> Option<Vec<DiffLine>> {
+        Some(diff::lines(
+            self.expected_output.as_deref()?,
+            self.actual_output.as_deref()?,
+        ))
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Submission {
+    id: SubmissionID,
+    challenge_id: ChallengeID,
+    user_id: UserID,
+    status: SubmissionStatus,
+    /// only once completed
+    score: Option<f64>,
+    max_score: Option<f64>,
+    /// why marking failed, e.g. compiler output
+    error: Option<String>,
+    created_at: String,
+    marked_at: Option<String>,
+    /// empty until completed
+    #[serde(default)]
+    results: Vec<TestCaseResult>,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<Submission>,
+}
+
+impl Submission {
+    /// Whether marking completed and every test case passed.
+    pub fn passed(&self) -> bool {
+        self.status == SubmissionStatus::Completed && self.results.iter().all(|r| r.passed)
+    }
+
+    /// The test cases which did not pass.
+    pub fn failures(&self) -> impl Iterator<Item = &TestCaseResult> {
+        self.results.iter().filter(|r| !r.passed)
+    }
+}
+
+/// GET /api/submissions/:id
+#[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct SubmissionResponse {
+    submission: Submission,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<SubmissionResponse>,
+}
+
+/// GET /api/challenges/:id/submissions
+#[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct SubmissionsResponse {
+    submissions: Vec<Submission>,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<SubmissionsResponse>,
+}
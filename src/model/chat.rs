@@ -0,0 +1,115 @@
+use derive_getters::{Dissolve, Getters};
+use serde::Deserialize;
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+use crate::schema::UnknownFields;
+
+use super::{
+    course::CourseID,
+    user::{ThreadParticipant, UserID},
+};
+
+/// The ID of a chat channel or direct conversation; both hold messages the same way.
+#[derive(Copy, Clone, Debug, Deserialize, Hash, PartialEq, Eq, Dissolve)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct ChannelID(u64);
+
+impl From<ChannelID> for u64 {
+    fn from(value: ChannelID) -> Self {
+        value.0
+    }
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Hash, PartialEq, Eq, Dissolve)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct ChatMessageID(u64);
+
+impl From<ChatMessageID> for u64 {
+    fn from(value: ChatMessageID) -> Self {
+        value.0
+    }
+}
+
+/// a chat channel in a course, open to everyone in it
+#[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Channel {
+    id: ChannelID,
+    course_id: CourseID,
+    name: String,
+    description: String,
+    created_at: String,
+    last_message_at: Option<String>,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<Channel>,
+}
+
+/// a private conversation between users in a course
+#[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct DirectConversation {
+    id: ChannelID,
+    course_id: CourseID,
+    /// everyone in the conversation, including the requesting user
+    user_ids: Vec<UserID>,
+    created_at: String,
+    last_message_at: Option<String>,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<DirectConversation>,
+}
+
+#[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct ChatMessage {
+    id: ChatMessageID,
+    channel_id: ChannelID,
+    user_id: UserID,
+    /// XML, like the `content` of threads
+    content: String,
+    /// plain text rendering of `content`
+    document: String,
+    created_at: String,
+    updated_at: Option<String>,
+    deleted_at: Option<String>,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<ChatMessage>,
+}
+
+/// GET /api/courses/:id/chat/channels
+#[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct ChannelsResponse {
+    channels: Vec<Channel>,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<ChannelsResponse>,
+}
+
+/// GET /api/courses/:id/chat/direct
+#[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct DirectConversationsResponse {
+    conversations: Vec<DirectConversation>,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<DirectConversationsResponse>,
+}
+
+/// GET /api/chat/channels/:id/messages
+#[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct ChatMessages {
+    /// newest first
+    messages: Vec<ChatMessage>,
+    users: Vec<ThreadParticipant>,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<ChatMessages>,
+}
+
+/// The response when a message is sent, i.e. POST /api/chat/channels/:id/messages
+#[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct ChatMessageResponse {
+    message: ChatMessage,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<ChatMessageResponse>,
+}
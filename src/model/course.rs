@@ -3,7 +3,7 @@ use serde::Deserialize;
 #[cfg(feature = "serde")]
 use serde::Serialize;
 
-use crate::opts::GetCourseThreadsOptions;
+use crate::{opts::GetCourseThreadsOptions, schema::UnknownFields};
 
 use super::{
     lab::{Lab, LabID},
@@ -16,19 +16,23 @@ use super::{
 #[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct CourseID(u64);
 
-impl Into<u64> for CourseID {
-    fn into(self) -> u64 {
-        self.0
+impl From<CourseID> for u64 {
+    fn from(value: CourseID) -> Self {
+        value.0
     }
 }
 
 impl CourseID {
+    pub async fn get(&self, client: &crate::Client) -> crate::Result<Course> {
+        client.get_course(*self).await
+    }
+
     pub async fn get_threads(
         &self,
         client: &crate::Client,
         options: Option<GetCourseThreadsOptions>,
     ) -> crate::Result<CourseThreads> {
-        client.get_course_threads(self.clone(), options).await
+        client.get_course_threads(*self, options).await
     }
 
     pub async fn get_thread_by_number(
@@ -36,9 +40,7 @@ impl CourseID {
         client: &crate::Client,
         thread_number: u64,
     ) -> crate::Result<ThreadResponse> {
-        client
-            .get_thread_by_number(self.clone(), thread_number)
-            .await
+        client.get_thread_by_number(*self, thread_number).await
     }
 }
 
@@ -46,8 +48,11 @@ impl CourseID {
 #[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
 #[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct CourseRoleSettings {
-    digest_interval: DigestInterval,
+    /// `None` if the user-wide setting applies, as opposed to `Some` "Never"
+    digest_interval: Option<DigestInterval>,
     email_announcements: Option<bool>,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<CourseRoleSettings>,
 }
 
 /// The role of a [`crate::model::user::User`] in a [`Course`]
@@ -67,6 +72,19 @@ pub enum Role {
     Other(String),
 }
 
+impl Role {
+    /// Whether this is any staff role: mentor, tutor, staff or admin.
+    pub fn is_staff(&self) -> bool {
+        matches!(self, Self::Mentor | Self::Tutor | Self::Staff | Self::Admin)
+    }
+
+    /// Whether this role administers the course, i.e. staff or admin, as opposed to helping
+    /// with it.
+    pub fn is_course_admin(&self) -> bool {
+        matches!(self, Self::Staff | Self::Admin)
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
 #[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct CourseRole {
@@ -74,11 +92,15 @@ pub struct CourseRole {
     course_id: CourseID,
     lab_id: Option<LabID>,
     role: Role,
-    // tutorial: Option<_>,
+    /// the name of the lab [`Self::lab_id`] refers to
+    #[serde(default)]
+    tutorial: Option<String>,
     digest: bool,
     settings: CourseRoleSettings,
     created_at: String,
     deleted_at: Option<String>,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<CourseRole>,
 }
 
 #[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
@@ -86,14 +108,18 @@ pub struct CourseRole {
 pub struct CourseFeatures {
     analytics: bool,
     discussion: bool,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<CourseFeatures>,
 }
 
 #[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
 #[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct Category {
     name: String,
-    subcategories: Vec<Box<Category>>,
+    subcategories: Vec<Category>,
     thread_template: Option<String>,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<Category>,
 }
 
 #[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
@@ -144,6 +170,8 @@ pub struct CourseDiscussionSettings {
     /// if false, only the two most recently posted pinned threads show up
     show_all_pinned_threads: bool,
     comment_endorsements: bool,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<CourseDiscussionSettings>,
 }
 
 #[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
@@ -152,6 +180,8 @@ pub struct CourseChatSettings {
     student_dm_student: bool,
     student_dm_staff: bool,
     channels_enabled: bool,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<CourseChatSettings>,
 }
 
 #[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
@@ -162,12 +192,16 @@ pub struct CourseLessonSettings {
     workspace_partition_slide_enabled: bool,
     autoplay_videos: bool,
     hide_video_download: bool,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<CourseLessonSettings>,
 }
 
 #[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
 #[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct CourseWorkspaceSettingsInner {
     rstudio_layout: String,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<CourseWorkspaceSettingsInner>,
 }
 
 #[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
@@ -183,6 +217,8 @@ pub struct CourseWorkspaceSettings {
     extra_paths: String,
     // env: Option<_>,
     settings: CourseWorkspaceSettingsInner,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<CourseWorkspaceSettings>,
 }
 
 #[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
@@ -193,6 +229,8 @@ pub struct CourseCodeEditorSettings {
     // soft_tabs: Option<_>,
     // tab_size: Option<_>,
     // autocomplete: Option<_>,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<CourseCodeEditorSettings>,
 }
 
 #[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
@@ -201,6 +239,8 @@ pub struct CourseTheme {
     logo: String,
     background: String,
     foreground: String,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<CourseTheme>,
 }
 
 #[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
@@ -211,6 +251,8 @@ pub struct CourseRoleLabels {
     tutor: String,
     staff: String,
     admin: String,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<CourseRoleLabels>,
 }
 
 #[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
@@ -231,6 +273,8 @@ pub struct CourseSettings {
     code_editor: CourseCodeEditorSettings,
     theme: CourseTheme,
     role_labels: CourseRoleLabels,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<CourseSettings>,
 }
 
 #[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
@@ -247,6 +291,8 @@ pub struct Course {
     settings: CourseSettings,
     created_at: String,
     is_lab_regex_active: bool,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<Course>,
 }
 
 #[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
@@ -257,4 +303,24 @@ pub struct SelfUserCourse {
     lab: Option<Lab>,
     /// last time this course had any activity
     last_active: String,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<SelfUserCourse>,
+}
+
+/// GET /api/courses/:id
+#[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct CourseResponse {
+    course: Course,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<CourseResponse>,
+}
+
+/// The response when the current user's settings for a course are updated
+#[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct CourseRoleResponse {
+    role: CourseRole,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<CourseRoleResponse>,
 }
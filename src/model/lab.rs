@@ -3,11 +3,72 @@ use serde::Deserialize;
 #[cfg(feature = "serde")]
 use serde::Serialize;
 
+use crate::schema::UnknownFields;
+
+use super::{course::CourseID, user::UserID};
+
 #[derive(Copy, Clone, Debug, Deserialize, Hash, PartialEq, Eq, Dissolve)]
 #[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct LabID(u64);
 
-// i have no idea what this does
+impl From<LabID> for u64 {
+    fn from(value: LabID) -> Self {
+        value.0
+    }
+}
+
+/// a tutorial group within a course, shown in the UI as a "tutorial"; each student is in at most
+/// one, see `CourseRole::lab_id`
+///
+/// only the IDs are required since labs are also embedded in `/api/user`, where a field missing
+/// from one lab must not stop the whole user from loading
 #[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
 #[cfg_attr(feature = "serde", derive(Serialize))]
-pub struct Lab {}
+pub struct Lab {
+    id: LabID,
+    course_id: CourseID,
+    #[serde(default)]
+    name: String,
+    /// free text, e.g. a room or meeting link
+    location: Option<String>,
+    /// the staff who run the lab
+    #[serde(default)]
+    tutor_ids: Vec<UserID>,
+    created_at: Option<String>,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<Lab>,
+}
+
+/// GET /api/courses/:id/labs
+#[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct LabsResponse {
+    labs: Vec<Lab>,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<LabsResponse>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_fields_are_tolerated() {
+        let lab: Lab = serde_json::from_value(serde_json::json!({
+            "id": 3,
+            "course_id": 1,
+            "name": "Lab A",
+        }))
+        .unwrap();
+        assert_eq!(u64::from(*lab.id()), 3);
+        assert_eq!(lab.name(), "Lab A");
+        assert!(lab.location().is_none());
+        assert!(lab.tutor_ids().is_empty());
+        assert!(lab.created_at().is_none());
+    }
+
+    #[test]
+    fn ids_are_required() {
+        assert!(serde_json::from_value::<Lab>(serde_json::json!({ "name": "Lab A" })).is_err());
+    }
+}
@@ -0,0 +1,304 @@
+use derive_getters::{Dissolve, Getters};
+use serde::Deserialize;
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+use serde_json::Value;
+
+use crate::{document::Document, schema::UnknownFields};
+
+use super::{challenge::ChallengeID, course::CourseID, user::UserID};
+
+#[derive(Copy, Clone, Debug, Deserialize, Hash, PartialEq, Eq, Dissolve)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct LessonID(u64);
+
+impl From<LessonID> for u64 {
+    fn from(value: LessonID) -> Self {
+        value.0
+    }
+}
+
+impl LessonID {
+    pub async fn get(&self, client: &crate::Client) -> crate::Result<Lesson> {
+        client.get_lesson(*self).await
+    }
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Hash, PartialEq, Eq, Dissolve)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct LessonModuleID(u64);
+
+impl From<LessonModuleID> for u64 {
+    fn from(value: LessonModuleID) -> Self {
+        value.0
+    }
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Hash, PartialEq, Eq, Dissolve)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct SlideID(u64);
+
+impl From<SlideID> for u64 {
+    fn from(value: SlideID) -> Self {
+        value.0
+    }
+}
+
+/// a group of lessons, shown as a heading in the lesson list
+#[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct LessonModule {
+    id: LessonModuleID,
+    course_id: CourseID,
+    name: String,
+    created_at: String,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<LessonModule>,
+}
+
+/// the kind of content on a [`Slide`]
+#[derive(Clone, Debug, Deserialize, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[serde(rename_all = "snake_case")]
+pub enum SlideType {
+    Document,
+    Quiz,
+    Code,
+    Video,
+    Karel,
+    WorkspacePartition,
+    #[serde(untagged)]
+    Other(String),
+}
+
+#[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Slide {
+    id: SlideID,
+    lesson_id: LessonID,
+    course_id: CourseID,
+    #[serde(rename = "type")]
+    type_: SlideType,
+    title: String,
+    /// 1-based position in the lesson
+    index: u64,
+    /// XML, like the `content` of threads; the body of document slides and the instructions of
+    /// others
+    content: String,
+    is_hidden: bool,
+    created_at: String,
+    /// only on code slides with a challenge
+    #[serde(default)]
+    challenge_id: Option<ChallengeID>,
+    /// only on quiz slides
+    #[serde(default)]
+    questions: Option<Vec<QuizQuestion>>,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<Slide>,
+}
+
+/// a question on a quiz slide
+#[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct QuizQuestion {
+    id: QuizQuestionID,
+    /// the score for a fully correct answer
+    max_score: f64,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<QuizQuestion>,
+}
+
+impl Slide {
+    /// Parse [`Self::content`] into the same model as thread bodies.
+    pub fn to_document(&self) -> crate::Result<Document> {
+        Document::parse(&self.content)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Lesson {
+    id: LessonID,
+    course_id: CourseID,
+    module_id: Option<LessonModuleID>,
+    user_id: UserID,
+    title: String,
+    /// position within its module
+    index: u64,
+    /// speculation: "active" or "scheduled"
+    state: String,
+    is_hidden: bool,
+    available_at: Option<String>,
+    due_at: Option<String>,
+    created_at: String,
+    updated_at: Option<String>,
+    /// only present when the lesson is fetched individually
+    #[serde(default)]
+    slides: Option<Vec<Slide>>,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<Lesson>,
+}
+
+impl Lesson {
+    /// The lesson's document slides rendered as Markdown, in order, separated by their titles.
+    ///
+    /// Empty unless the lesson was fetched individually, e.g. with
+    /// [`Client::get_lesson`](crate::Client::get_lesson).
+    pub fn to_markdown(&self) -> crate::Result<String> {
+        let mut parts = Vec::new();
+        for slide in self.slides.iter().flatten() {
+            if slide.type_ == SlideType::Document {
+                parts.push(format!(
+                    "## {}\n\n{}",
+                    slide.title,
+                    slide.to_document()?.to_markdown()
+                ));
+            }
+        }
+        Ok(parts.join("\n\n"))
+    }
+
+    /// The most anyone could score on the lesson's quiz slides.
+    ///
+    /// Zero unless the lesson was fetched individually, e.g. with
+    /// [`Client::get_lesson`](crate::Client::get_lesson).
+    pub fn max_quiz_score(&self) -> f64 {
+        self.slides
+            .iter()
+            .flatten()
+            .flat_map(|slide| slide.questions.iter().flatten())
+            .map(|question| question.max_score)
+            .sum()
+    }
+}
+
+/// GET /api/courses/:id/lessons
+#[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct CourseLessons {
+    lessons: Vec<Lesson>,
+    modules: Vec<LessonModule>,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<CourseLessons>,
+}
+
+impl CourseLessons {
+    /// Lessons grouped by module, in module order then lesson order. Lessons outside any module
+    /// come first, under `None`.
+    pub fn by_module(&self) -> Vec<(Option<&LessonModule>, Vec<&Lesson>)> {
+        let in_module = |id: Option<LessonModuleID>| {
+            let mut lessons: Vec<_> = self.lessons.iter().filter(|l| l.module_id == id).collect();
+            lessons.sort_by_key(|l| l.index);
+            lessons
+        };
+
+        let mut groups = vec![(None, in_module(None))];
+        groups.extend(
+            self.modules
+                .iter()
+                .map(|m| (Some(m), in_module(Some(m.id)))),
+        );
+        groups.retain(|(module, lessons)| module.is_some() || !lessons.is_empty());
+        groups
+    }
+}
+
+/// How far a user has got through a lesson.
+#[derive(Clone, Debug, Deserialize, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[serde(rename_all = "snake_case")]
+pub enum LessonStatus {
+    /// Not yet opened.
+    NotStarted,
+    /// Opened, with slides still to complete.
+    InProgress,
+    /// Every slide completed.
+    Completed,
+    /// A status this crate does not know.
+    #[serde(untagged)]
+    Other(String),
+}
+
+impl LessonStatus {
+    /// The status as Ed Discussion writes it, e.g. `"in_progress"`.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::NotStarted => "not_started",
+            Self::InProgress => "in_progress",
+            Self::Completed => "completed",
+            Self::Other(other) => other,
+        }
+    }
+}
+
+/// one user's progress through a lesson
+#[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct LessonProgress {
+    user_id: UserID,
+    lesson_id: LessonID,
+    status: LessonStatus,
+    completed_slides: u64,
+    total_slides: u64,
+    first_viewed_at: Option<String>,
+    completed_at: Option<String>,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<LessonProgress>,
+}
+
+/// GET /api/lessons/:id/progress
+#[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct LessonProgressResponse {
+    progress: Vec<LessonProgress>,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<LessonProgressResponse>,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, Hash, PartialEq, Eq, Dissolve)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct QuizQuestionID(u64);
+
+impl From<QuizQuestionID> for u64 {
+    fn from(value: QuizQuestionID) -> Self {
+        value.0
+    }
+}
+
+/// one user's latest answer to a question on a quiz slide
+#[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct QuizResponse {
+    user_id: UserID,
+    lesson_id: LessonID,
+    slide_id: SlideID,
+    question_id: QuizQuestionID,
+    /// shape depends on the question type, e.g. a list of chosen option indices
+    response: Value,
+    /// `None` for questions which are not automatically marked
+    is_correct: Option<bool>,
+    score: f64,
+    max_score: f64,
+    submitted_at: String,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<QuizResponse>,
+}
+
+/// GET /api/lessons/:id/quiz_responses
+#[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct QuizResponsesResponse {
+    responses: Vec<QuizResponse>,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<QuizResponsesResponse>,
+}
+
+/// GET /api/lessons/:id
+#[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct LessonResponse {
+    lesson: Lesson,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<LessonResponse>,
+}
@@ -5,13 +5,26 @@ use serde::Deserialize;
 #[cfg(feature = "serde")]
 use serde::Serialize;
 
+use crate::schema::UnknownFields;
+
+pub(crate) mod challenge;
+pub(crate) mod chat;
 pub(crate) mod course;
 pub(crate) mod lab;
+pub(crate) mod lesson;
+pub(crate) mod notification;
 pub(crate) mod realm;
 pub(crate) mod thread;
 pub(crate) mod user;
+pub(crate) mod workspace;
+
+pub use lesson::LessonStatus;
+pub use thread::ApprovalStatus;
 
 /// Stand-in for maps not known to contain any fields.
 #[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
 #[cfg_attr(feature = "serde", derive(Serialize))]
-pub struct Empty {}
+pub struct Empty {
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<Empty>,
+}
@@ -0,0 +1,120 @@
+use derive_getters::{Dissolve, Getters};
+use serde::Deserialize;
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+use crate::schema::UnknownFields;
+
+use super::{
+    chat::ChannelID,
+    course::CourseID,
+    thread::{ReplyID, ThreadID},
+    user::UserID,
+};
+
+#[derive(Copy, Clone, Debug, Deserialize, Hash, PartialEq, Eq, Dissolve)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct NotificationID(u64);
+
+impl From<NotificationID> for u64 {
+    fn from(value: NotificationID) -> Self {
+        value.0
+    }
+}
+
+/// what a [`Notification`] is about; the same kinds as `DesktopNotificationScopes`
+#[derive(Clone, Debug, Deserialize, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationType {
+    Announcement,
+    /// a new thread in a course
+    Thread,
+    /// a reply to one of your threads or replies
+    DirectReply,
+    Mention,
+    /// a direct message or a message in a channel
+    Chat,
+    /// activity on a thread you are watching
+    Watch,
+    #[serde(untagged)]
+    Other(String),
+}
+
+#[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Notification {
+    id: NotificationID,
+    #[serde(rename = "type")]
+    type_: NotificationType,
+    course_id: Option<CourseID>,
+    thread_id: Option<ThreadID>,
+    /// set when the notification is about a reply rather than the thread itself
+    comment_id: Option<ReplyID>,
+    /// set for chat notifications
+    channel_id: Option<ChannelID>,
+    /// whoever caused the notification, unless anonymous
+    actor_id: Option<UserID>,
+    /// e.g. the thread title
+    title: String,
+    /// a plain text excerpt of the post or message
+    body: String,
+    read: bool,
+    created_at: String,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<Notification>,
+}
+
+/// GET /api/notifications
+#[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Notifications {
+    /// newest first
+    notifications: Vec<Notification>,
+    /// across all pages
+    unread_count: u64,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<Notifications>,
+}
+
+/// the kind of post in an [`ActivityItem`]
+#[derive(Clone, Debug, Deserialize, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[serde(rename_all = "snake_case")]
+pub enum ActivityType {
+    Thread,
+    Answer,
+    Comment,
+    #[serde(untagged)]
+    Other(String),
+}
+
+/// a thread or reply someone posted, as listed on their profile
+#[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct ActivityItem {
+    #[serde(rename = "type")]
+    type_: ActivityType,
+    course_id: CourseID,
+    thread_id: ThreadID,
+    thread_title: String,
+    /// the user-facing number of the thread within its course
+    thread_number: u64,
+    /// unset for threads
+    comment_id: Option<ReplyID>,
+    /// a plain text excerpt of the post
+    document: String,
+    created_at: String,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<ActivityItem>,
+}
+
+/// GET /api/users/:id/activity
+#[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct ActivityResponse {
+    /// newest first
+    items: Vec<ActivityItem>,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<ActivityResponse>,
+}
@@ -3,17 +3,33 @@ use serde::Deserialize;
 #[cfg(feature = "serde")]
 use serde::Serialize;
 
-use super::Empty;
+use crate::schema::UnknownFields;
 
-#[derive(Copy, Clone, Debug, Deserialize, Hash, PartialEq, Eq)]
+use super::{Empty, course::Course, user::UserID};
+
+#[derive(Copy, Clone, Debug, Deserialize, Hash, PartialEq, Eq, Dissolve)]
 #[cfg_attr(feature = "serde", derive(Serialize))]
-pub struct RealmID(usize);
+pub struct RealmID(u64);
+
+impl From<RealmID> for u64 {
+    fn from(value: RealmID) -> Self {
+        value.0
+    }
+}
+
+impl RealmID {
+    pub async fn get(&self, client: &crate::Client) -> crate::Result<Realm> {
+        client.get_realm(*self).await
+    }
+}
 
 #[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
 #[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct RealmTheme {
     logo: String,
     accent_color: String,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<RealmTheme>,
 }
 
 #[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
@@ -23,6 +39,8 @@ pub struct RealmAdminCapability {
     chat: bool,
     workspaces: bool,
     lessons: bool,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<RealmAdminCapability>,
 }
 
 #[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
@@ -39,6 +57,8 @@ pub struct RealmSettings {
     realm_admin_capability: RealmAdminCapability,
     allow_lessons_and_workspaces_enable: bool,
     // lexical_access: Option<_>,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<RealmSettings>,
 }
 
 #[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
@@ -53,4 +73,53 @@ pub struct Realm {
     features: Empty,
     settings: RealmSettings,
     affiliate_realm_id: Option<RealmID>,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<Realm>,
+}
+
+/// GET /api/realms/:id
+#[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct RealmResponse {
+    realm: Realm,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<RealmResponse>,
+}
+
+/// GET /api/realms/:id/courses
+#[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct RealmCoursesResponse {
+    courses: Vec<Course>,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<RealmCoursesResponse>,
+}
+
+/// a user as they appear to realm admins
+#[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct RealmUser {
+    id: UserID,
+    realm_id: RealmID,
+    name: String,
+    email: String,
+    /// "user" or "admin"
+    role: String,
+    /// false until the user first logs in
+    activated: bool,
+    is_lti: bool,
+    is_sso: bool,
+    created_at: String,
+    last_active: Option<String>,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<RealmUser>,
+}
+
+/// GET /api/realms/:id/users
+#[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct RealmUsersResponse {
+    users: Vec<RealmUser>,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<RealmUsersResponse>,
 }
@@ -1,10 +1,12 @@
-use std::num::NonZeroU64;
+use std::{collections::HashMap, num::NonZeroU64};
 
 use derive_getters::{Dissolve, Getters};
 use serde::{Deserialize, Deserializer};
 #[cfg(feature = "serde")]
 use serde::{Serialize, Serializer};
 
+use crate::schema::UnknownFields;
+
 use super::{
     course::CourseID,
     user::{ThreadParticipant, UserID},
@@ -14,6 +16,12 @@ use super::{
 #[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct ReplyID(u64);
 
+impl From<ReplyID> for u64 {
+    fn from(value: ReplyID) -> Self {
+        value.0
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Hash, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(Serialize))]
 #[serde(rename_all = "lowercase")]
@@ -54,21 +62,23 @@ pub struct Reply {
     anonymous_id: MaybeAnonymousID,
     vote: u64,
     comments: Vec<Reply>,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<Reply>,
 }
 
 #[derive(Copy, Clone, Debug, Deserialize, Hash, PartialEq, Eq, Dissolve)]
 #[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct ThreadID(u64);
 
-impl Into<u64> for ThreadID {
-    fn into(self) -> u64 {
-        self.0
+impl From<ThreadID> for u64 {
+    fn from(value: ThreadID) -> Self {
+        value.0
     }
 }
 
 impl ThreadID {
     pub async fn get(&self, client: &crate::Client) -> crate::Result<ThreadResponse> {
-        client.get_thread(self.clone()).await
+        client.get_thread(*self).await
     }
 }
 
@@ -114,6 +124,25 @@ pub enum ThreadType {
     Post,
 }
 
+/// Whether a thread is visible, in courses whose discussion settings have
+/// [`threads_require_approval`](crate::model::course::CourseDiscussionSettings::threads_require_approval)
+/// set.
+#[derive(Clone, Debug, Deserialize, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[serde(rename_all = "lowercase")]
+pub enum ApprovalStatus {
+    /// Visible to everyone who can see the thread. Threads in courses without approval are
+    /// always approved.
+    Approved,
+    /// Awaiting staff review, and visible only to staff and the author.
+    Pending,
+    /// Rejected by staff, and visible only to staff and the author.
+    Rejected,
+    /// A status this crate does not know.
+    #[serde(untagged)]
+    Other(String),
+}
+
 /// the ways in which a user may watch a thread
 #[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub enum ThreadWatchStatus {
@@ -198,8 +227,7 @@ pub struct PartialThread {
     is_anonymous: bool,
     is_megathread: bool,
     anonymous_comments: bool,
-    // unsure what besides "approved" is possible
-    approved_status: String,
+    approved_status: ApprovalStatus,
     created_at: String,
     updated_at: Option<String>,
     deleted_at: Option<String>,
@@ -219,6 +247,8 @@ pub struct PartialThread {
     /// if this thread was marked a duplicate of thread X, the title of thread X
     duplicate_title: Option<String>,
     user: Option<ThreadParticipant>,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<PartialThread>,
 }
 
 /// GET /api/courses/:id/threads
@@ -228,6 +258,8 @@ pub struct CourseThreads {
     sort_key: String,
     threads: Vec<PartialThread>,
     users: Vec<ThreadParticipant>,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<CourseThreads>,
 }
 
 /// Data from a thread when requested by ID
@@ -268,7 +300,7 @@ pub struct Thread {
     is_anonymous: bool,
     is_megathread: bool,
     anonymous_comments: bool,
-    approved_status: String,
+    approved_status: ApprovalStatus,
     created_at: String,
     updated_at: Option<String>,
     deleted_at: Option<String>,
@@ -283,6 +315,87 @@ pub struct Thread {
     duplicate_title: Option<String>,
     answers: Vec<Reply>,
     comments: Vec<Reply>,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<Thread>,
+}
+
+impl Thread {
+    /// Every answer and comment with its depth, 0 for direct replies to the thread, each
+    /// followed by its own comments. Answers come before comments.
+    pub fn replies(&self) -> impl Iterator<Item = (usize, &Reply)> {
+        let mut stack: Vec<_> = self
+            .answers
+            .iter()
+            .chain(&self.comments)
+            .rev()
+            .map(|r| (0, r))
+            .collect();
+        std::iter::from_fn(move || {
+            let (depth, reply) = stack.pop()?;
+            stack.extend(reply.comments.iter().rev().map(|r| (depth + 1, r)));
+            Some((depth, reply))
+        })
+    }
+
+    /// Every answer and comment with its depth, shallowest first.
+    pub fn replies_breadth_first(&self) -> impl Iterator<Item = (usize, &Reply)> {
+        let mut queue: std::collections::VecDeque<_> = self
+            .answers
+            .iter()
+            .chain(&self.comments)
+            .map(|r| (0, r))
+            .collect();
+        std::iter::from_fn(move || {
+            let (depth, reply) = queue.pop_front()?;
+            queue.extend(reply.comments.iter().map(|r| (depth + 1, r)));
+            Some((depth, reply))
+        })
+    }
+
+    /// Every answer and comment, oldest first.
+    pub fn replies_chronological(&self) -> Vec<&Reply> {
+        let mut replies: Vec<_> = self.replies().map(|(_, r)| r).collect();
+        replies.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+        replies
+    }
+
+    /// The reply with ID `id`, at any depth.
+    pub fn reply(&self, id: impl Into<u64>) -> Option<&Reply> {
+        let id = id.into();
+        self.replies()
+            .map(|(_, r)| r)
+            .find(|r| u64::from(r.id) == id)
+    }
+
+    /// The reply with `number`, as shown in the UI, at any depth.
+    pub fn reply_by_number(&self, number: u64) -> Option<&Reply> {
+        self.replies().map(|(_, r)| r).find(|r| r.number == number)
+    }
+
+    /// The replies which the reply with ID `id` is nested under, innermost first. Empty if it is
+    /// a direct reply to the thread or does not exist.
+    pub fn reply_ancestors(&self, id: impl Into<u64>) -> Vec<&Reply> {
+        let id = id.into();
+        let mut path: Vec<&Reply> = Vec::new();
+        for (depth, reply) in self.replies() {
+            path.truncate(depth);
+            if u64::from(reply.id) == id {
+                path.reverse();
+                return path;
+            }
+            path.push(reply);
+        }
+        Vec::new()
+    }
+
+    /// The number of replies of each type, at any depth.
+    pub fn reply_counts(&self) -> HashMap<ReplyType, usize> {
+        let mut counts = HashMap::new();
+        for (_, reply) in self.replies() {
+            *counts.entry(reply.type_.clone()).or_default() += 1;
+        }
+        counts
+    }
 }
 
 /// The full response when a thread is fetched individually, i.e. GET /api/threads/:id
@@ -290,4 +403,173 @@ pub struct Thread {
 #[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct ThreadResponse {
     thread: Thread,
+    /// the authors of the thread and its replies, where not anonymous to the requesting user
+    #[serde(default)]
+    users: Vec<ThreadParticipant>,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<ThreadResponse>,
+}
+
+/// The response when a reply is posted, i.e. POST /api/threads/:id/comments
+#[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct ReplyResponse {
+    comment: Reply,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<ReplyResponse>,
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use super::*;
+
+    fn reply(id: u64, reply_type: &str, created_at: &str, comments: Vec<Value>) -> Value {
+        json!({
+            "id": id,
+            "user_id": 1,
+            "course_id": 1,
+            "thread_id": 1,
+            "original_id": null,
+            "parent_id": null,
+            "editor_id": null,
+            "number": id,
+            "type": reply_type,
+            "kind": "normal",
+            "content": "<document version=\"2.0\"><paragraph>hi</paragraph></document>",
+            "document": "hi",
+            "flag_count": 0,
+            "vote_count": 0,
+            "is_endorsed": false,
+            "is_anonymous": false,
+            "is_private": false,
+            "is_resolved": false,
+            "created_at": created_at,
+            "updated_at": null,
+            "deleted_at": null,
+            "anonymous_id": 0,
+            "vote": 0,
+            "comments": comments,
+        })
+    }
+
+    /// answer 1 (comment 3 (comment 5)), answer 2, comment 4 (comment 6)
+    fn thread() -> Thread {
+        let answers = vec![
+            reply(
+                1,
+                "answer",
+                "2025-01-01",
+                vec![reply(
+                    3,
+                    "comment",
+                    "2025-01-03",
+                    vec![reply(5, "comment", "2025-01-05", vec![])],
+                )],
+            ),
+            reply(2, "answer", "2025-01-02", vec![]),
+        ];
+        let comments = vec![reply(
+            4,
+            "comment",
+            "2025-01-06",
+            vec![reply(6, "comment", "2025-01-04", vec![])],
+        )];
+        serde_json::from_value(json!({
+            "id": 1,
+            "user_id": 1,
+            "course_id": 1,
+            "original_id": null,
+            "editor_id": null,
+            "accepted_id": null,
+            "duplicate_id": null,
+            "number": 1,
+            "type": "question",
+            "title": "Help",
+            "content": "<document version=\"2.0\"><paragraph>help</paragraph></document>",
+            "document": "help",
+            "category": "General",
+            "subcategory": "",
+            "subsubcategory": "",
+            "flag_count": 0,
+            "star_count": 0,
+            "view_count": 0,
+            "unique_view_count": 0,
+            "vote_count": 0,
+            "reply_count": 6,
+            "unresolved_count": 0,
+            "is_locked": false,
+            "is_pinned": false,
+            "is_private": false,
+            "is_endorsed": false,
+            "is_student_answered": false,
+            "is_staff_answered": false,
+            "is_archived": false,
+            "is_anonymous": false,
+            "is_megathread": false,
+            "anonymous_comments": false,
+            "approved_status": "approved",
+            "created_at": "2025-01-01",
+            "updated_at": null,
+            "deleted_at": null,
+            "pinned_at": null,
+            "anonymous_id": 0,
+            "vote": 0,
+            "is_seen": false,
+            "is_starred": false,
+            "is_watched": null,
+            "glanced_at": null,
+            "new_reply_count": 0,
+            "duplicate_title": null,
+            "answers": answers,
+            "comments": comments,
+        }))
+        .unwrap()
+    }
+
+    fn ids<'a>(replies: impl IntoIterator<Item = &'a Reply>) -> Vec<u64> {
+        replies.into_iter().map(|r| u64::from(r.id)).collect()
+    }
+
+    #[test]
+    fn replies_depth_first() {
+        let thread = thread();
+        let replies: Vec<_> = thread
+            .replies()
+            .map(|(depth, r)| (depth, u64::from(r.id)))
+            .collect();
+        assert_eq!(replies, [(0, 1), (1, 3), (2, 5), (0, 2), (0, 4), (1, 6)]);
+    }
+
+    #[test]
+    fn replies_breadth_first() {
+        let thread = thread();
+        let replies: Vec<_> = thread
+            .replies_breadth_first()
+            .map(|(depth, r)| (depth, u64::from(r.id)))
+            .collect();
+        assert_eq!(replies, [(0, 1), (0, 2), (0, 4), (1, 3), (1, 6), (2, 5)]);
+    }
+
+    #[test]
+    fn replies_chronological() {
+        assert_eq!(ids(thread().replies_chronological()), [1, 2, 3, 6, 5, 4]);
+    }
+
+    #[test]
+    fn finds_replies_at_any_depth() {
+        let thread = thread();
+        assert_eq!(thread.reply(5u64).map(|r| r.number), Some(5));
+        assert_eq!(thread.reply_by_number(6).map(|r| u64::from(r.id)), Some(6));
+        assert!(thread.reply(7u64).is_none());
+
+        assert_eq!(ids(thread.reply_ancestors(5u64)), [3, 1]);
+        assert!(thread.reply_ancestors(2u64).is_empty());
+        assert!(thread.reply_ancestors(7u64).is_empty());
+
+        let counts = thread.reply_counts();
+        assert_eq!(counts[&ReplyType::Answer], 2);
+        assert_eq!(counts[&ReplyType::Comment], 4);
+    }
 }
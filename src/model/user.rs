@@ -1,13 +1,16 @@
-use std::num::NonZeroU64;
+use std::{collections::HashMap, num::NonZeroU64};
 
 use derive_getters::{Dissolve, Getters};
 use serde::Deserialize;
 #[cfg(feature = "serde")]
 use serde::Serialize;
 
+use crate::schema::UnknownFields;
+
 use super::{
     Empty,
-    course::{Role, SelfUserCourse},
+    course::{CourseID, Role, SelfUserCourse},
+    lab::LabID,
     realm::{Realm, RealmID},
 };
 
@@ -21,6 +24,8 @@ pub struct SelfUser {
     realms: Vec<Realm>,
     time: String,
     user: User,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<SelfUser>,
 }
 
 impl SelfUser {
@@ -33,6 +38,12 @@ impl SelfUser {
 #[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct UserID(u64);
 
+impl From<UserID> for u64 {
+    fn from(value: UserID) -> Self {
+        value.0
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(Serialize))]
 pub enum ThreadListStyle {
@@ -60,7 +71,7 @@ impl<'de> Deserialize<'de> for DigestInterval {
         D: serde::Deserializer<'de>,
     {
         let got = Option::<u64>::deserialize(deserializer)?;
-        Ok(Self(got.map(NonZeroU64::new).flatten()))
+        Ok(Self(got.and_then(NonZeroU64::new)))
     }
 }
 
@@ -97,6 +108,8 @@ pub struct DesktopNotificationScopes {
     // currently no way to change this in UI
     chat: bool,
     watch: bool,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<DesktopNotificationScopes>,
 }
 
 #[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
@@ -133,6 +146,8 @@ pub struct UserSettings {
     snooze_end: String,
     // lexical_access: Option<>,
     deactivated: bool,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<UserSettings>,
 }
 
 #[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
@@ -156,6 +171,50 @@ pub struct User {
     can_change_name: bool,
     has_pats: bool,
     realm_id: Option<RealmID>,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<User>,
+}
+
+/// The response when the current user's settings are updated
+#[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct UserResponse {
+    user: User,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<UserResponse>,
+}
+
+/// a user as they appear on a course's roster, visible to staff
+#[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct CourseMember {
+    id: UserID,
+    course_id: CourseID,
+    name: String,
+    email: String,
+    course_role: Role,
+    lab_id: Option<LabID>,
+    created_at: String,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<CourseMember>,
+}
+
+/// GET /api/courses/:id/admin/users
+#[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct CourseMembersResponse {
+    users: Vec<CourseMember>,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<CourseMembersResponse>,
+}
+
+/// PUT /api/courses/:id/admin/users/:id
+#[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct CourseMemberResponse {
+    user: CourseMember,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<CourseMemberResponse>,
 }
 
 /// a user as they appear as part of a response including threads
@@ -168,18 +227,34 @@ pub struct ThreadParticipant {
     name: String,
     avatar: Option<String>,
     course_role: Option<Role>,
-    // tutorials: ,
+    /// the name of the user's lab, by course ID
+    #[serde(default)]
+    tutorials: Option<HashMap<String, String>>,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<ThreadParticipant>,
+}
+
+impl ThreadParticipant {
+    /// The name of the user's lab in a course, if they are in one.
+    pub fn tutorial(&self, course_id: impl Into<u64>) -> Option<&str> {
+        self.tutorials
+            .as_ref()?
+            .get(&course_id.into().to_string())
+            .map(String::as_str)
+    }
 }
 
-impl Into<ThreadParticipant> for User {
-    fn into(self) -> ThreadParticipant {
+impl From<User> for ThreadParticipant {
+    fn from(value: User) -> Self {
         ThreadParticipant {
-            id: self.id,
+            id: value.id,
             // TODO: really?
             role: String::from("user"),
-            name: self.name,
-            avatar: self.avatar,
-            course_role: self.course_role,
+            name: value.name,
+            avatar: value.avatar,
+            course_role: value.course_role,
+            tutorials: None,
+            unknown_fields: UnknownFields::default(),
         }
     }
 }
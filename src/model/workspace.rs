@@ -0,0 +1,104 @@
+use derive_getters::{Dissolve, Getters};
+use serde::Deserialize;
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+use crate::schema::UnknownFields;
+
+use super::{challenge::ChallengeID, course::CourseID, user::UserID};
+
+#[derive(Copy, Clone, Debug, Deserialize, Hash, PartialEq, Eq, Dissolve)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct WorkspaceID(u64);
+
+impl From<WorkspaceID> for u64 {
+    fn from(value: WorkspaceID) -> Self {
+        value.0
+    }
+}
+
+impl WorkspaceID {
+    pub async fn get(&self, client: &crate::Client) -> crate::Result<Workspace> {
+        client.get_workspace(*self).await
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Workspace {
+    id: WorkspaceID,
+    course_id: CourseID,
+    user_id: UserID,
+    name: String,
+    /// speculation: one of the course's workspace types, e.g. "standard" or "jupyter"; see
+    /// `CourseWorkspaceSettings::default_type`
+    #[serde(rename = "type")]
+    type_: String,
+    /// set when the workspace belongs to a code challenge
+    challenge_id: Option<ChallengeID>,
+    created_at: String,
+    updated_at: Option<String>,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<Workspace>,
+}
+
+/// GET /api/courses/:id/workspaces
+#[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct WorkspacesResponse {
+    workspaces: Vec<Workspace>,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<WorkspacesResponse>,
+}
+
+/// GET /api/workspaces/:id
+#[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct WorkspaceResponse {
+    workspace: Workspace,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<WorkspaceResponse>,
+}
+
+#[derive(Clone, Debug, Deserialize, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[serde(rename_all = "lowercase")]
+pub enum WorkspaceFileKind {
+    File,
+    Directory,
+    #[serde(untagged)]
+    Other(String),
+}
+
+/// an entry in a workspace's file tree
+#[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct WorkspaceFile {
+    /// relative to the workspace root, with `/` separators
+    path: String,
+    #[serde(rename = "type")]
+    kind: WorkspaceFileKind,
+    /// in bytes; 0 for directories
+    size: u64,
+    modified_at: Option<String>,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<WorkspaceFile>,
+}
+
+/// GET /api/workspaces/:id/files
+#[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct WorkspaceFilesResponse {
+    files: Vec<WorkspaceFile>,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<WorkspaceFilesResponse>,
+}
+
+/// PUT /api/workspaces/:id/files/raw
+#[derive(Clone, Debug, Deserialize, Getters, Dissolve)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct WorkspaceFileResponse {
+    file: WorkspaceFile,
+    #[serde(flatten)]
+    unknown_fields: UnknownFields<WorkspaceFileResponse>,
+}
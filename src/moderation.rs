@@ -0,0 +1,98 @@
+//! Reports of flagged content, from [`Client::moderation_report`](crate::Client::moderation_report).
+
+use std::fmt;
+
+use crate::model::thread::{PartialThread, Reply, Thread};
+
+/// A flagged reply and the thread it is in.
+#[derive(Clone, Debug)]
+pub struct FlaggedReply {
+    /// The thread the reply is in.
+    pub thread: PartialThread,
+    /// The reply.
+    pub reply: Reply,
+}
+
+/// Everything in a course with a non-zero `flag_count`.
+#[derive(Clone, Debug, Default)]
+pub struct ModerationReport {
+    /// Flagged threads, most flagged first.
+    pub threads: Vec<PartialThread>,
+    /// Flagged replies, most flagged first.
+    pub replies: Vec<FlaggedReply>,
+}
+
+impl ModerationReport {
+    /// Keep the flagged threads of `threads`.
+    pub(crate) fn new(threads: &[PartialThread]) -> Self {
+        let mut threads: Vec<_> = threads
+            .iter()
+            .filter(|t| *t.flag_count() > 0)
+            .cloned()
+            .collect();
+        threads.sort_by_key(|t| std::cmp::Reverse(*t.flag_count()));
+
+        Self {
+            threads,
+            replies: Vec::new(),
+        }
+    }
+
+    /// Add the flagged replies in `full`, the complete version of `thread`. Call
+    /// [`sort_replies`](Self::sort_replies) once they have all been added.
+    pub(crate) fn add_replies(&mut self, thread: &PartialThread, full: &Thread) {
+        self.replies.extend(
+            full.replies()
+                .filter(|(_, reply)| *reply.flag_count() > 0)
+                .map(|(_, reply)| FlaggedReply {
+                    thread: thread.clone(),
+                    reply: reply.clone(),
+                }),
+        );
+    }
+
+    /// Put the flagged replies most flagged first.
+    pub(crate) fn sort_replies(&mut self) {
+        self.replies
+            .sort_by_key(|r| std::cmp::Reverse(*r.reply.flag_count()));
+    }
+
+    /// Whether nothing is flagged.
+    pub fn is_empty(&self) -> bool {
+        self.threads.is_empty() && self.replies.is_empty()
+    }
+
+    /// The total number of flags on everything in the report.
+    pub fn total_flags(&self) -> u64 {
+        self.threads.iter().map(|t| *t.flag_count()).sum::<u64>()
+            + self
+                .replies
+                .iter()
+                .map(|r| *r.reply.flag_count())
+                .sum::<u64>()
+    }
+}
+
+impl fmt::Display for ModerationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for thread in &self.threads {
+            writeln!(
+                f,
+                "thread #{} {:?}: {} flags",
+                thread.number(),
+                thread.title(),
+                thread.flag_count()
+            )?;
+        }
+        for flagged in &self.replies {
+            writeln!(
+                f,
+                "reply {} on #{}: {} flags",
+                u64::from(*flagged.reply.id()),
+                flagged.thread.number(),
+                flagged.reply.flag_count()
+            )?;
+        }
+        Ok(())
+    }
+}
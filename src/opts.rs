@@ -1,11 +1,22 @@
 //! Options for API requests.
 
+use std::{fmt, num::NonZeroU64, path::Path, time::Duration};
+
 #[cfg(feature = "serde")]
 use serde::Deserialize;
 
-use serde::Serialize;
+use serde::{Serialize, Serializer};
+use serde_json::{Map, Value};
 use strum_macros::AsRefStr;
 
+use crate::{
+    document::Document,
+    model::{
+        course::{Category, CourseDiscussionSettings},
+        realm::RealmSettings,
+    },
+};
+
 /// How to sort responses as part of [`GetCourseThreadsOptions`].
 /// All unit variants are sort keys with known meaning.
 #[derive(Clone, Debug, PartialEq, Eq, AsRefStr)]
@@ -82,7 +93,7 @@ impl Default for GetCourseThreadsOptions {
 }
 
 impl GetCourseThreadsOptions {
-    pub(crate) fn as_params(&self) -> Vec<(&str, impl Serialize)> {
+    pub(crate) fn as_params(&self) -> Vec<(&'static str, String)> {
         let mut ret = vec![
             ("limit", self.limit.to_string()),
             ("offset", self.offset.to_string()),
@@ -96,3 +107,742 @@ impl GetCourseThreadsOptions {
         ret
     }
 }
+
+/// Whether a reply made with [`PostReplyOptions`] is a comment or an answer.
+#[derive(Clone, Debug, Default, PartialEq, Eq, AsRefStr)]
+#[strum(serialize_all = "lowercase")]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum PostReplyKind {
+    /// A comment, which may be posted on any thread or nested under another reply.
+    #[default]
+    Comment,
+    /// An answer to a [`ThreadType::Question`](crate::model::thread::ThreadType::Question).
+    Answer,
+}
+
+/// Options to [`crate::Client::post_thread_reply`] and [`crate::Client::post_comment_reply`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct PostReplyOptions {
+    /// Comment or answer. Replies nested under other replies are always comments.
+    pub kind: PostReplyKind,
+    /// Whether the reply is visible only to its author and staff.
+    pub is_private: bool,
+    /// Whether the author's name is hidden from other students.
+    pub is_anonymous: bool,
+}
+
+#[derive(Serialize)]
+pub(crate) struct PostReplyBody<'a> {
+    comment: PostReplyComment<'a>,
+}
+
+#[derive(Serialize)]
+struct PostReplyComment<'a> {
+    #[serde(rename = "type")]
+    type_: &'a str,
+    content: String,
+    is_private: bool,
+    is_anonymous: bool,
+}
+
+impl PostReplyOptions {
+    pub(crate) fn as_body(&self, content: &Document) -> PostReplyBody<'_> {
+        PostReplyBody {
+            comment: PostReplyComment {
+                type_: self.kind.as_ref(),
+                content: content.to_xml(),
+                is_private: self.is_private,
+                is_anonymous: self.is_anonymous,
+            },
+        }
+    }
+}
+
+/// A discussion category, as set by [`CourseDiscussionSettingsPatch::categories`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "serde", derive(Deserialize))]
+pub struct CategorySpec {
+    /// The category's name, unique among its siblings.
+    pub name: String,
+    /// Subcategories, in display order.
+    #[serde(default)]
+    pub subcategories: Vec<CategorySpec>,
+    /// Text to prefill new threads in this category with.
+    #[serde(default)]
+    pub thread_template: Option<String>,
+    /// Fields of an existing category which this crate does not know about, sent back as they
+    /// were so that replacing the category list does not reset them.
+    #[serde(flatten)]
+    pub unknown_fields: Map<String, Value>,
+}
+
+impl From<&Category> for CategorySpec {
+    fn from(value: &Category) -> Self {
+        Self {
+            name: value.name().clone(),
+            subcategories: value.subcategories().iter().map(Self::from).collect(),
+            thread_template: value.thread_template().clone(),
+            unknown_fields: value
+                .unknown_fields()
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
+        }
+    }
+}
+
+/// A digest email interval to set, sent to Ed Discussion as a number of minutes where 0 means
+/// "Never".
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DigestIntervalSetting {
+    /// No digest emails.
+    Never,
+    /// A digest email at most this often, in minutes. The UI's "Instant" is 1 minute.
+    Minutes(NonZeroU64),
+}
+
+impl DigestIntervalSetting {
+    /// The interval in minutes, or 0 for [`DigestIntervalSetting::Never`].
+    pub fn minutes(&self) -> u64 {
+        match self {
+            Self::Never => 0,
+            Self::Minutes(minutes) => minutes.get(),
+        }
+    }
+}
+
+impl From<u64> for DigestIntervalSetting {
+    fn from(value: u64) -> Self {
+        NonZeroU64::new(value).map_or(Self::Never, Self::Minutes)
+    }
+}
+
+impl Serialize for DigestIntervalSetting {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.minutes().serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for DigestIntervalSetting {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        u64::deserialize(deserializer).map(Self::from)
+    }
+}
+
+/// A partial update to a course's discussion settings, for
+/// [`crate::Client::update_course_settings`]. Fields left `None` are not changed.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "serde", derive(Deserialize))]
+pub struct CourseDiscussionSettingsPatch {
+    /// Whether private threads can be created.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub private: Option<bool>,
+    /// Whether all threads must be private.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub private_threads_only: Option<bool>,
+    /// Whether threads can be posted anonymously.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub anonymous: Option<bool>,
+    /// Whether replies can be posted anonymously.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub anonymous_comments: Option<bool>,
+    /// Whether staff can see who posted anonymously.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub anonymous_to_staff: Option<bool>,
+    /// Whether new threads must be approved by staff before they are visible.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub threads_require_approval: Option<bool>,
+    /// The comment sent to authors of rejected threads, which may use the placeholders of
+    /// [`crate::templates`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rejection_comment_template: Option<String>,
+    /// The full category tree, replacing the existing one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub categories: Option<Vec<CategorySpec>>,
+    /// Whether staff can use saved replies.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub saved_replies_enabled: Option<bool>,
+    /// The full list of saved replies, replacing the existing one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub saved_replies: Option<Vec<String>>,
+    /// Whether the course is read only, e.g. once the term is over.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub readonly: Option<bool>,
+    /// The default digest email interval.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub digest_interval: Option<DigestIntervalSetting>,
+}
+
+/// A single change a [`CourseDiscussionSettingsPatch`] would make, from
+/// [`CourseDiscussionSettingsPatch::diff`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SettingChange {
+    /// The setting's name, e.g. `"readonly"`.
+    pub field: &'static str,
+    /// The current value.
+    pub from: Value,
+    /// The value after the patch.
+    pub to: Value,
+}
+
+impl fmt::Display for SettingChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} -> {}", self.field, self.from, self.to)
+    }
+}
+
+impl CourseDiscussionSettingsPatch {
+    /// Whether the patch sets nothing.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// The changes applying this patch to `current` would make, leaving out fields the patch
+    /// sets to their current value.
+    pub fn diff(&self, current: &CourseDiscussionSettings) -> Vec<SettingChange> {
+        fn to_value(value: &impl Serialize) -> Value {
+            serde_json::to_value(value).expect("settings always serialize")
+        }
+
+        let current_categories: Vec<CategorySpec> = current
+            .categories()
+            .iter()
+            .map(CategorySpec::from)
+            .collect();
+        let current_interval = current.digest_interval().dissolve();
+
+        let fields = [
+            (
+                "private",
+                self.private.map(Value::from),
+                Value::from(*current.private()),
+            ),
+            (
+                "private_threads_only",
+                self.private_threads_only.map(Value::from),
+                Value::from(*current.private_threads_only()),
+            ),
+            (
+                "anonymous",
+                self.anonymous.map(Value::from),
+                Value::from(*current.anonymous()),
+            ),
+            (
+                "anonymous_comments",
+                self.anonymous_comments.map(Value::from),
+                Value::from(*current.anonymous_comments()),
+            ),
+            (
+                "anonymous_to_staff",
+                self.anonymous_to_staff.map(Value::from),
+                Value::from(*current.anonymous_to_staff()),
+            ),
+            (
+                "threads_require_approval",
+                self.threads_require_approval.map(Value::from),
+                Value::from(*current.threads_require_approval()),
+            ),
+            (
+                "rejection_comment_template",
+                self.rejection_comment_template.as_ref().map(to_value),
+                to_value(current.rejection_comment_template()),
+            ),
+            (
+                "categories",
+                self.categories.as_ref().map(to_value),
+                to_value(&current_categories),
+            ),
+            (
+                "saved_replies_enabled",
+                self.saved_replies_enabled.map(Value::from),
+                Value::from(*current.saved_replies_enabled()),
+            ),
+            (
+                "saved_replies",
+                self.saved_replies.as_ref().map(to_value),
+                to_value(current.saved_replies()),
+            ),
+            (
+                "readonly",
+                self.readonly.map(Value::from),
+                Value::from(*current.readonly()),
+            ),
+            (
+                "digest_interval",
+                self.digest_interval
+                    .map(|interval| Value::from(interval.minutes())),
+                Value::from(current_interval.map_or(0, NonZeroU64::get)),
+            ),
+        ];
+
+        fields
+            .into_iter()
+            .filter_map(|(field, to, from)| {
+                to.filter(|to| *to != from)
+                    .map(|to| SettingChange { field, from, to })
+            })
+            .collect()
+    }
+}
+
+#[derive(Serialize)]
+pub(crate) struct CourseSettingsBody<'a> {
+    settings: CourseSettingsBodyInner<'a>,
+}
+
+#[derive(Serialize)]
+struct CourseSettingsBodyInner<'a> {
+    discussion: &'a CourseDiscussionSettingsPatch,
+}
+
+impl CourseDiscussionSettingsPatch {
+    pub(crate) fn as_body(&self) -> CourseSettingsBody<'_> {
+        CourseSettingsBody {
+            settings: CourseSettingsBodyInner { discussion: self },
+        }
+    }
+}
+
+/// The UI theme, as set by [`UserSettingsPatch::theme`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "serde", derive(Deserialize))]
+#[serde(rename_all = "lowercase")]
+pub enum ThemeSetting {
+    /// Follow the operating system.
+    Os,
+    /// Always light.
+    Light,
+    /// Always dark.
+    Dark,
+}
+
+/// The thread list style, as set by [`UserSettingsPatch::discuss_feed_style`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "serde", derive(Deserialize))]
+#[serde(rename_all = "kebab-case")]
+pub enum ThreadListStyleSetting {
+    /// Titles with a preview of each thread.
+    Full,
+    /// Titles and categories.
+    Compact,
+    /// Titles only.
+    UltraCompact,
+}
+
+/// Which events send desktop notifications, as set by
+/// [`UserSettingsPatch::desktop_notifications_scopes`]. Fields left `None` are not changed.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "serde", derive(Deserialize))]
+pub struct DesktopNotificationScopesPatch {
+    /// Announcements.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub announcement: Option<bool>,
+    /// Every new thread. Not settable in the UI.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thread: Option<bool>,
+    /// Replies to the user's own threads and replies.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub direct_reply: Option<bool>,
+    /// Mentions of the user.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mention: Option<bool>,
+    /// Chat messages. Not settable in the UI.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chat: Option<bool>,
+    /// Activity on watched threads.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub watch: Option<bool>,
+}
+
+/// The value of `snooze_end` when notifications are not snoozed.
+pub const SNOOZE_NONE: &str = "0001-01-01T00:00:00Z";
+
+/// A partial update to the current user's settings, for [`crate::Client::update_user_settings`].
+/// Fields left `None` are not changed.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "serde", derive(Deserialize))]
+pub struct UserSettingsPatch {
+    /// How often to email a digest of new threads.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub digest_interval: Option<DigestIntervalSetting>,
+    /// "Thread List Style" in appearance settings.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub discuss_feed_style: Option<ThreadListStyleSetting>,
+    /// UI theme in appearance settings.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub theme: Option<ThemeSetting>,
+    /// UI language, e.g. `"en_us"`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+    /// Whether to follow the browser's time zone rather than `tz`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub set_tz_automatically: Option<bool>,
+    /// A tz database identifier, e.g. `"America/Los_Angeles"`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tz: Option<String>,
+    /// Whether emailed replies are posted.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reply_via_email: Option<bool>,
+    /// Whether to email announcements.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email_announcements: Option<bool>,
+    /// Whether to email activity on watched threads.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email_watched_threads: Option<bool>,
+    /// Whether to email replies to the user's threads.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email_thread_replies: Option<bool>,
+    /// Whether to email replies to the user's replies.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email_comment_replies: Option<bool>,
+    /// Whether to email mentions of the user.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email_mentions: Option<bool>,
+    /// Whether desktop notifications are on at all.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub desktop_notifications_enabled: Option<bool>,
+    /// Which events send desktop notifications.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub desktop_notifications_scopes: Option<DesktopNotificationScopesPatch>,
+    /// When snoozed notifications resume, as an ISO 8601 datetime; [`SNOOZE_NONE`] to stop
+    /// snoozing.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snooze_end: Option<String>,
+}
+
+/// A partial update to the current user's settings for a single course, for
+/// [`crate::Client::update_course_role_settings`]. Fields left `None` are not changed, and
+/// `Some(None)` removes the course's override so the user-wide setting applies.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "serde", derive(Deserialize))]
+pub struct CourseRoleSettingsPatch {
+    /// How often to email a digest of new threads in this course.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub digest_interval: Option<Option<DigestIntervalSetting>>,
+    /// Whether to email announcements in this course.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email_announcements: Option<Option<bool>>,
+}
+
+#[derive(Serialize)]
+pub(crate) struct SettingsBody<'a, T> {
+    settings: &'a T,
+}
+
+impl UserSettingsPatch {
+    pub(crate) fn as_body(&self) -> SettingsBody<'_, Self> {
+        SettingsBody { settings: self }
+    }
+}
+
+impl CourseRoleSettingsPatch {
+    pub(crate) fn as_body(&self) -> SettingsBody<'_, Self> {
+        SettingsBody { settings: self }
+    }
+}
+
+/// A course to create with [`crate::Client::create_realm_course`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "serde", derive(Deserialize))]
+pub struct NewCourse {
+    /// The short code, e.g. `"CS 101"`.
+    pub code: String,
+    /// The full name.
+    pub name: String,
+    /// The year, e.g. `"2025"`.
+    pub year: String,
+    /// The term within the year, e.g. `"Winter"`.
+    pub session: String,
+    /// The LMS context to link the course to, if it is being created for one. Such courses start
+    /// inactive if the realm's `course_inactive_on_lti_creation` is set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lti_context_id: Option<String>,
+}
+
+impl NewCourse {
+    pub(crate) fn as_body(&self, settings: &RealmSettings) -> Value {
+        let inactive = self.lti_context_id.is_some() && *settings.course_inactive_on_lti_creation();
+        let mut course = serde_json::to_value(self).expect("courses always serialize");
+        course["status"] = Value::from(if inactive { "inactive" } else { "active" });
+        serde_json::json!({ "course": course })
+    }
+}
+
+/// A partial update to a realm's theme, as part of a [`RealmSettingsPatch`]. Fields left `None`
+/// are not changed.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "serde", derive(Deserialize))]
+pub struct RealmThemePatch {
+    /// The logo shown in place of Ed's, as a URL.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logo: Option<String>,
+    /// The accent colour as a CSS hex colour, e.g. `"#0055aa"`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accent_color: Option<String>,
+}
+
+/// A partial update to a realm's settings, for [`crate::Client::update_realm_settings`]. Fields
+/// left `None` are not changed.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "serde", derive(Deserialize))]
+pub struct RealmSettingsPatch {
+    /// Whether courses created for an LMS start inactive.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub course_inactive_on_lti_creation: Option<bool>,
+    /// Whether courses can be created in the realm at all.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allow_course_creation: Option<bool>,
+    /// Whether courses can be created for an LMS.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lti_and_course_creation: Option<bool>,
+    /// Whether users are identified by their LMS `sourcedId` rather than their email.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sourced_id_as_unique_identifier: Option<bool>,
+    /// Whether courses can enable chat.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allow_chat: Option<bool>,
+    /// Whether users must set their name when they first log in.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub force_name_update: Option<bool>,
+    /// Whether courses can enable lessons and workspaces.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allow_lessons_and_workspaces_enable: Option<bool>,
+    /// The theme.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub theme: Option<RealmThemePatch>,
+}
+
+impl RealmSettingsPatch {
+    /// Whether the patch sets nothing.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub(crate) fn as_body(&self) -> SettingsBody<'_, Self> {
+        SettingsBody { settings: self }
+    }
+}
+
+/// Options to [`crate::Client::moderation_report`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct ModerationReportOptions {
+    /// Whether to look for flagged replies too. This fetches every thread with replies
+    /// individually, so is much slower in a large course.
+    pub include_replies: bool,
+}
+
+/// Options to [`crate::Client::get_chat_messages`], paging back from the newest message.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct ChatHistoryOptions {
+    /// The limit on the number of messages to return.
+    pub limit: u64,
+    /// Only return messages older than the message with this ID, where `None` means start from
+    /// the newest.
+    pub before: Option<u64>,
+}
+
+impl Default for ChatHistoryOptions {
+    fn default() -> Self {
+        Self {
+            limit: 50,
+            before: None,
+        }
+    }
+}
+
+impl ChatHistoryOptions {
+    pub(crate) fn as_params(&self) -> Vec<(&'static str, String)> {
+        let mut ret = vec![("limit", self.limit.to_string())];
+
+        if let Some(before) = self.before {
+            ret.push(("before", before.to_string()));
+        }
+
+        ret
+    }
+}
+
+/// Options to [`crate::Client::get_notifications`], paging back from the newest notification.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct NotificationOptions {
+    /// The limit on the number of notifications to return.
+    pub limit: u64,
+    /// Only return notifications older than the notification with this ID, where `None` means
+    /// start from the newest.
+    pub before: Option<u64>,
+    /// Whether to leave out notifications already read.
+    pub unread_only: bool,
+}
+
+impl Default for NotificationOptions {
+    fn default() -> Self {
+        Self {
+            limit: 30,
+            before: None,
+            unread_only: false,
+        }
+    }
+}
+
+impl NotificationOptions {
+    pub(crate) fn as_params(&self) -> Vec<(&'static str, String)> {
+        let mut ret = vec![("limit", self.limit.to_string())];
+
+        if let Some(before) = self.before {
+            ret.push(("before", before.to_string()));
+        }
+        if self.unread_only {
+            ret.push(("unread", String::from("true")));
+        }
+
+        ret
+    }
+}
+
+/// Options to [`crate::Client::get_user_activity`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct ActivityOptions {
+    /// Only include posts in this course, where `None` means every course you share with the
+    /// user.
+    pub course_id: Option<u64>,
+    /// The limit on the number of posts to return.
+    pub limit: u64,
+    /// The number of posts to skip, for paging.
+    pub offset: u64,
+}
+
+impl Default for ActivityOptions {
+    fn default() -> Self {
+        Self {
+            course_id: None,
+            limit: 30,
+            offset: 0,
+        }
+    }
+}
+
+impl ActivityOptions {
+    pub(crate) fn as_params(&self) -> Vec<(&'static str, String)> {
+        let mut ret = vec![
+            ("limit", self.limit.to_string()),
+            ("offset", self.offset.to_string()),
+        ];
+
+        if let Some(course_id) = self.course_id {
+            ret.push(("course_id", course_id.to_string()));
+        }
+
+        ret
+    }
+}
+
+/// Options to [`crate::Client::forum_report`] and
+/// [`crate::analytics::ForumReport::new`].
+#[cfg(feature = "analytics")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct AnalyticsOptions {
+    /// The tz database identifier to count activity by weekday and hour in, e.g. the course's
+    /// or the requesting user's `tz` setting. [`crate::Client::forum_report`] uses the
+    /// requesting user's setting when this is `"UTC"`, the default.
+    pub tz: String,
+    /// How many contributors to keep for each role.
+    pub top_contributors: usize,
+    /// Whether to count anonymous posts towards their real authors, where the response reveals
+    /// them. See [`crate::anonymity::can_reveal`].
+    pub reveal_anonymous: bool,
+}
+
+#[cfg(feature = "analytics")]
+impl Default for AnalyticsOptions {
+    fn default() -> Self {
+        Self {
+            tz: String::from("UTC"),
+            top_contributors: 10,
+            reveal_anonymous: false,
+        }
+    }
+}
+
+/// A source file to submit with [`crate::Client::submit_challenge`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "serde", derive(Deserialize))]
+pub struct SourceFile {
+    /// The path relative to the workspace root, with `/` separators.
+    pub path: String,
+    /// The file's contents.
+    pub content: String,
+}
+
+impl SourceFile {
+    /// A file at `path` with `content`.
+    pub fn new(path: impl Into<String>, content: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            content: content.into(),
+        }
+    }
+
+    /// Read the file at `path`, relative to the local directory `root`.
+    pub fn read(root: impl AsRef<Path>, path: &str) -> crate::Result<Self> {
+        let content = std::fs::read_to_string(root.as_ref().join(path))?;
+        Ok(Self::new(path, content))
+    }
+}
+
+/// Options to [`crate::Client::wait_for_submission`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct SubmissionPollOptions {
+    /// How long to wait between checks.
+    pub interval: Duration,
+    /// How long to wait in total before giving up, where `None` means wait forever.
+    pub timeout: Option<Duration>,
+}
+
+impl Default for SubmissionPollOptions {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(2),
+            timeout: Some(Duration::from_secs(300)),
+        }
+    }
+}
+
+/// Options to [`crate::sync::sync_workspace`].
+#[cfg(feature = "sync")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct WorkspaceSyncOptions {
+    /// The name of the [`crate::sync::Manifest`] file in the local directory, which is never
+    /// synced.
+    pub manifest: String,
+    /// Paths of files or directories to leave alone on both sides, e.g. `"build"`.
+    pub ignore: Vec<String>,
+    /// Only report what would be done.
+    pub dry_run: bool,
+}
+
+#[cfg(feature = "sync")]
+impl Default for WorkspaceSyncOptions {
+    fn default() -> Self {
+        Self {
+            manifest: String::from(".edstem-sync.json"),
+            ignore: Vec::new(),
+            dry_run: false,
+        }
+    }
+}
@@ -0,0 +1,280 @@
+//! Joining threads and replies with the users who wrote them.
+//!
+//! Thread listings and individual threads come with the users involved in a separate `users`
+//! list, while posts only carry `user_id` and `editor_id`. [`JoinedCourseThreads`] and
+//! [`JoinedThread`] index those users once and resolve each post's author, editor and role.
+//!
+//! ```no_run
+//! # async fn example(client: edstem::Client) -> edstem::Result<()> {
+//! use edstem::participants::JoinedThread;
+//!
+//! let course = client.get_course(1234u64).await?;
+//! let joined = JoinedThread::from(client.get_thread(5678u64).await?)
+//!     .with_labels(course.settings().role_labels().clone());
+//! for reply in joined.replies() {
+//!     let name = reply.author().map(|a| a.name().as_str()).unwrap_or("Anonymous");
+//!     println!("{name} ({})", reply.role_label().unwrap_or_default());
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashMap;
+
+use crate::model::{
+    course::{CourseRoleLabels, Role},
+    thread::{CourseThreads, PartialThread, Reply, Thread, ThreadResponse},
+    user::{ThreadParticipant, UserID},
+};
+
+/// The name shown for `role`, as relabelled by a course if `labels` are given.
+pub fn role_label<'a>(role: &'a Role, labels: Option<&'a CourseRoleLabels>) -> &'a str {
+    match (role, labels) {
+        (Role::Student, Some(labels)) => labels.student(),
+        (Role::Mentor, Some(labels)) => labels.mentor(),
+        (Role::Tutor, Some(labels)) => labels.tutor(),
+        (Role::Staff, Some(labels)) => labels.staff(),
+        (Role::Admin, Some(labels)) => labels.admin(),
+        (Role::Student, None) => "Student",
+        (Role::Mentor, None) => "Mentor",
+        (Role::Tutor, None) => "Tutor",
+        (Role::Staff, None) => "Staff",
+        (Role::Admin, None) => "Admin",
+        (Role::Other(other), _) => other,
+    }
+}
+
+/// Users by ID, with the course's role labels if known.
+#[derive(Clone, Debug, Default)]
+pub struct ParticipantIndex {
+    users: HashMap<UserID, ThreadParticipant>,
+    labels: Option<CourseRoleLabels>,
+}
+
+impl FromIterator<ThreadParticipant> for ParticipantIndex {
+    fn from_iter<I: IntoIterator<Item = ThreadParticipant>>(iter: I) -> Self {
+        Self {
+            users: iter.into_iter().map(|u| (*u.id(), u)).collect(),
+            labels: None,
+        }
+    }
+}
+
+impl Extend<ThreadParticipant> for ParticipantIndex {
+    fn extend<I: IntoIterator<Item = ThreadParticipant>>(&mut self, iter: I) {
+        self.users.extend(iter.into_iter().map(|u| (*u.id(), u)));
+    }
+}
+
+impl ParticipantIndex {
+    /// Use a course's names for roles, from its `settings.role_labels`.
+    pub fn with_labels(mut self, labels: CourseRoleLabels) -> Self {
+        self.labels = Some(labels);
+        self
+    }
+
+    /// The user with ID `id`, if known.
+    pub fn get(&self, id: UserID) -> Option<&ThreadParticipant> {
+        self.users.get(&id)
+    }
+
+    /// The role of the user with ID `id` in the course, if known.
+    pub fn role(&self, id: UserID) -> Option<&Role> {
+        self.get(id)?.course_role().as_ref()
+    }
+
+    /// The name shown for the role of the user with ID `id`, if known.
+    pub fn role_label(&self, id: UserID) -> Option<&str> {
+        self.role(id).map(|r| role_label(r, self.labels.as_ref()))
+    }
+
+    /// The number of users.
+    pub fn len(&self) -> usize {
+        self.users.len()
+    }
+
+    /// Whether there are no users.
+    pub fn is_empty(&self) -> bool {
+        self.users.is_empty()
+    }
+
+    /// Every user, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = &ThreadParticipant> {
+        self.users.values()
+    }
+}
+
+/// A thread or reply along with the users it refers to.
+#[derive(Clone, Copy, Debug)]
+pub struct Joined<'a, T> {
+    /// The thread or reply.
+    pub post: &'a T,
+    user_id: UserID,
+    editor_id: Option<UserID>,
+    index: &'a ParticipantIndex,
+}
+
+impl<'a, T> Joined<'a, T> {
+    /// The author, unless anonymous or not included in the response.
+    pub fn author(&self) -> Option<&'a ThreadParticipant> {
+        self.index.get(self.user_id)
+    }
+
+    /// Whoever last edited the post, if anyone.
+    pub fn editor(&self) -> Option<&'a ThreadParticipant> {
+        self.index.get(self.editor_id?)
+    }
+
+    /// The author's role in the course.
+    pub fn role(&self) -> Option<&'a Role> {
+        self.index.role(self.user_id)
+    }
+
+    /// The name shown for the author's role in the course.
+    pub fn role_label(&self) -> Option<&'a str> {
+        self.index.role_label(self.user_id)
+    }
+}
+
+/// A course's threads, from [`Client::get_course_threads`](crate::Client::get_course_threads),
+/// joined with their authors.
+#[derive(Clone, Debug)]
+pub struct JoinedCourseThreads {
+    threads: Vec<PartialThread>,
+    index: ParticipantIndex,
+}
+
+impl From<CourseThreads> for JoinedCourseThreads {
+    fn from(value: CourseThreads) -> Self {
+        let (_, threads, users, _) = value.dissolve();
+        Self {
+            threads,
+            index: users.into_iter().collect(),
+        }
+    }
+}
+
+impl JoinedCourseThreads {
+    /// Threads and users gathered separately, e.g. over several pages.
+    pub fn new(threads: Vec<PartialThread>, users: Vec<ThreadParticipant>) -> Self {
+        Self {
+            threads,
+            index: users.into_iter().collect(),
+        }
+    }
+
+    /// Use a course's names for roles, from its `settings.role_labels`.
+    pub fn with_labels(mut self, labels: CourseRoleLabels) -> Self {
+        self.index = self.index.with_labels(labels);
+        self
+    }
+
+    /// The users involved.
+    pub fn index(&self) -> &ParticipantIndex {
+        &self.index
+    }
+
+    /// Each thread, in the order listed.
+    pub fn threads(&self) -> impl Iterator<Item = Joined<'_, PartialThread>> {
+        self.threads.iter().map(|thread| Joined {
+            post: thread,
+            user_id: *thread.user_id(),
+            editor_id: *thread.editor_id(),
+            index: &self.index,
+        })
+    }
+}
+
+/// A thread, from [`Client::get_thread`](crate::Client::get_thread), joined with the authors of
+/// it and its replies.
+#[derive(Clone, Debug)]
+pub struct JoinedThread {
+    thread: Thread,
+    index: ParticipantIndex,
+}
+
+impl From<ThreadResponse> for JoinedThread {
+    fn from(value: ThreadResponse) -> Self {
+        let (thread, users, _) = value.dissolve();
+        Self {
+            thread,
+            index: users.into_iter().collect(),
+        }
+    }
+}
+
+impl JoinedThread {
+    /// Use a course's names for roles, from its `settings.role_labels`.
+    pub fn with_labels(mut self, labels: CourseRoleLabels) -> Self {
+        self.index = self.index.with_labels(labels);
+        self
+    }
+
+    /// The users involved.
+    pub fn index(&self) -> &ParticipantIndex {
+        &self.index
+    }
+
+    /// The thread itself.
+    pub fn thread(&self) -> Joined<'_, Thread> {
+        Joined {
+            post: &self.thread,
+            user_id: *self.thread.user_id(),
+            editor_id: *self.thread.editor_id(),
+            index: &self.index,
+        }
+    }
+
+    /// Every answer and comment, each followed by its own comments.
+    pub fn replies(&self) -> impl Iterator<Item = Joined<'_, Reply>> {
+        self.thread.replies().map(|(_, reply)| Joined {
+            post: reply,
+            user_id: *reply.user_id(),
+            editor_id: *reply.editor_id(),
+            index: &self.index,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn participant(id: u64, name: &str, course_role: &str) -> ThreadParticipant {
+        serde_json::from_value(json!({
+            "id": id,
+            "role": "user",
+            "name": name,
+            "avatar": null,
+            "course_role": course_role,
+            "tutorials": {},
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn looks_up_users_and_role_labels() {
+        let jane = participant(1, "Jane Doe", "student");
+        let id = *jane.id();
+        let mut index: ParticipantIndex = [jane].into_iter().collect();
+        assert_eq!(index.get(id).map(|u| u.name().as_str()), Some("Jane Doe"));
+        assert_eq!(index.role_label(id), Some("Student"));
+
+        // later copies of a user replace earlier ones
+        index.extend([participant(1, "Jane Doe", "tutor")]);
+        assert_eq!(index.len(), 1);
+        assert_eq!(index.role(id), Some(&Role::Tutor));
+
+        let labels = serde_json::from_value(json!({
+            "student": "Learner",
+            "mentor": "Mentor",
+            "tutor": "TA",
+            "staff": "Staff",
+            "admin": "Instructor",
+        }))
+        .unwrap();
+        assert_eq!(index.with_labels(labels).role_label(id), Some("TA"));
+    }
+}
@@ -0,0 +1,908 @@
+//! Relaying course activity to webhooks, for integrations not written in Rust.
+//!
+//! A [`Relay`] polls one or more courses with a [`CourseWatcher`] each, which compares every
+//! listing with the last to find new threads and replies, endorsements and resolutions. Each
+//! [`RelayEvent`] is POSTed as JSON to every [`Endpoint`] whose [`EventFilter`] it matches, signed
+//! with the endpoint's secret: the `X-Edstem-Signature` header is `sha256=` and the hex HMAC-SHA256
+//! of the body, as checked by [`verify`]. Each endpoint has its own queue, delivered to alongside
+//! the others, so one which is down or slow to answer delays only its own deliveries, which are
+//! retried in order with exponential backoff.
+//!
+//! The first poll of a course only records its state, so nothing already posted is relayed.
+//!
+//! ```no_run
+//! # async fn example(client: edstem::Client) -> edstem::Result<()> {
+//! use edstem::relay::{Relay, RelayConfig};
+//!
+//! let config = RelayConfig::from_toml(
+//!     r#"
+//!     courses = [1234]
+//!
+//!     [[endpoints]]
+//!     url = "https://example.com/hooks/ed"
+//!     secret = "hunter2"
+//!     events = ["thread_created", "reply_created"]
+//!     thread_types = ["question"]
+//!     "#,
+//! )?;
+//! Relay::new(client, config)
+//!     .run(|report| {
+//!         for failure in &report.dropped {
+//!             eprintln!("gave up on {} to {}: {}", failure.event_id, failure.url, failure.reason);
+//!         }
+//!     })
+//!     .await;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The `edstem-relay` binary runs a relay from such a TOML file.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt,
+    time::{Duration, SystemTime},
+};
+
+use futures_util::future::join_all;
+use hmac::{Hmac, Mac};
+use reqwest::header::CONTENT_TYPE;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use tokio::time::Instant;
+
+use crate::{
+    Client,
+    model::thread::{PartialThread, Reply, ThreadType},
+};
+
+/// The header carrying `sha256=` and the hex HMAC-SHA256 of the body.
+pub const SIGNATURE_HEADER: &str = "X-Edstem-Signature";
+/// The header carrying the [`EventKind`], e.g. `thread_created`.
+pub const EVENT_HEADER: &str = "X-Edstem-Event";
+/// The header carrying [`RelayEvent::id`].
+pub const DELIVERY_HEADER: &str = "X-Edstem-Delivery";
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn mac(secret: &str, body: &[u8]) -> Hmac<Sha256> {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC takes keys of any length");
+    mac.update(body);
+    mac
+}
+
+/// The value of the [`SIGNATURE_HEADER`] for `body` signed with `secret`.
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    format!("sha256={}", hex(&mac(secret, body).finalize().into_bytes()))
+}
+
+/// Whether `signature`, the value of the [`SIGNATURE_HEADER`], is that of `body` signed with
+/// `secret`. Compares in constant time.
+pub fn verify(secret: &str, body: &[u8], signature: &str) -> bool {
+    let Some(digest) = signature.strip_prefix("sha256=") else {
+        return false;
+    };
+    if digest.len() % 2 != 0 || !digest.is_ascii() {
+        return false;
+    }
+    let bytes: Option<Vec<u8>> = (0..digest.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&digest[i..i + 2], 16).ok())
+        .collect();
+    bytes.is_some_and(|bytes| mac(secret, body).verify_slice(&bytes).is_ok())
+}
+
+/// What a [`RelayEvent`] is about.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    /// A thread was posted, or became visible, e.g. by being approved.
+    ThreadCreated,
+    /// An answer or comment was posted.
+    ReplyCreated,
+    /// Staff endorsed a thread.
+    ThreadEndorsed,
+    /// Staff endorsed an answer or comment.
+    ReplyEndorsed,
+    /// A thread's last unresolved question or comment was resolved, or an answer was accepted.
+    ThreadResolved,
+}
+
+impl EventKind {
+    /// The name used in payloads and the [`EVENT_HEADER`], e.g. `thread_created`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::ThreadCreated => "thread_created",
+            Self::ReplyCreated => "reply_created",
+            Self::ThreadEndorsed => "thread_endorsed",
+            Self::ReplyEndorsed => "reply_endorsed",
+            Self::ThreadResolved => "thread_resolved",
+        }
+    }
+}
+
+/// Something that happened in a course, as sent to webhooks.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct RelayEvent {
+    /// Unique to this event and the same for every attempt to deliver it, so receivers can drop
+    /// duplicates.
+    pub id: String,
+    /// What happened.
+    #[serde(rename = "event")]
+    pub kind: EventKind,
+    /// The course it happened in.
+    pub course_id: u64,
+    /// The thread it happened in.
+    pub thread_id: u64,
+    /// The thread's number within the course, as shown in the UI.
+    pub thread_number: u64,
+    /// The thread's type.
+    pub thread_type: ThreadType,
+    /// The thread's title.
+    pub title: String,
+    /// The thread's category, possibly empty.
+    pub category: String,
+    /// The thread's subcategory, possibly empty.
+    pub subcategory: String,
+    /// The reply, for reply events.
+    pub reply_id: Option<u64>,
+    /// The author of the thread or reply, unless anonymous.
+    pub author_id: Option<u64>,
+    /// The plain text of the new thread or reply, for creation events.
+    pub text: Option<String>,
+    /// The thread's web address.
+    pub url: String,
+}
+
+/// Which events an [`Endpoint`] receives. Each empty list allows anything.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct EventFilter {
+    /// The kinds of event to send.
+    pub events: Vec<EventKind>,
+    /// The types of thread to send events for.
+    pub thread_types: Vec<ThreadType>,
+    /// The top-level categories to send events for.
+    pub categories: Vec<String>,
+}
+
+impl EventFilter {
+    /// Whether `event` passes the filter.
+    pub fn matches(&self, event: &RelayEvent) -> bool {
+        (self.events.is_empty() || self.events.contains(&event.kind))
+            && (self.thread_types.is_empty() || self.thread_types.contains(&event.thread_type))
+            && (self.categories.is_empty() || self.categories.contains(&event.category))
+    }
+}
+
+/// A webhook to POST events to.
+#[derive(Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Endpoint {
+    /// Where to POST.
+    pub url: String,
+    /// The key for the [`SIGNATURE_HEADER`].
+    pub secret: String,
+    /// Which events to send.
+    #[serde(flatten)]
+    pub filter: EventFilter,
+}
+
+impl fmt::Debug for Endpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Endpoint")
+            .field("url", &self.url)
+            .field("secret", &"[redacted]")
+            .field("filter", &self.filter)
+            .finish()
+    }
+}
+
+fn default_poll_interval_secs() -> u64 {
+    60
+}
+
+fn default_retry_base_secs() -> u64 {
+    5
+}
+
+fn default_max_attempts() -> u32 {
+    8
+}
+
+fn default_max_queue() -> usize {
+    1000
+}
+
+fn default_timeout_secs() -> u64 {
+    30
+}
+
+/// The courses a [`Relay`] watches and where it sends their events.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct RelayConfig {
+    /// The courses to watch, by ID.
+    pub courses: Vec<u64>,
+    /// Where to send events.
+    pub endpoints: Vec<Endpoint>,
+    /// How long [`Relay::run`] waits between polls.
+    #[serde(default = "default_poll_interval_secs")]
+    pub poll_interval_secs: u64,
+    /// How long to wait before retrying a failed delivery the first time, doubling with each
+    /// further failure up to an hour.
+    #[serde(default = "default_retry_base_secs")]
+    pub retry_base_secs: u64,
+    /// How many times to try a delivery before dropping it.
+    #[serde(default = "default_max_attempts")]
+    pub max_attempts: u32,
+    /// How many deliveries an endpoint may have queued before the oldest are dropped.
+    #[serde(default = "default_max_queue")]
+    pub max_queue: usize,
+    /// How long to wait for an endpoint to answer before counting a delivery as failed.
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+impl RelayConfig {
+    /// Watch `courses` and send everything to `endpoints`, with default timings.
+    pub fn new(courses: Vec<u64>, endpoints: Vec<Endpoint>) -> Self {
+        Self {
+            courses,
+            endpoints,
+            poll_interval_secs: default_poll_interval_secs(),
+            retry_base_secs: default_retry_base_secs(),
+            max_attempts: default_max_attempts(),
+            max_queue: default_max_queue(),
+            timeout_secs: default_timeout_secs(),
+        }
+    }
+
+    /// Parse a config from TOML.
+    pub fn from_toml(toml: &str) -> crate::Result<Self> {
+        Ok(toml::from_str(toml)?)
+    }
+}
+
+/// What was last seen of a thread.
+#[derive(Clone, Debug, Default)]
+struct ThreadState {
+    reply_count: u64,
+    updated_at: Option<String>,
+    is_endorsed: bool,
+    unresolved_count: u64,
+    is_accepted: bool,
+    /// whether each reply is endorsed, by ID; `None` until the thread is first fetched
+    replies: Option<HashMap<u64, bool>>,
+}
+
+/// Finds what changed in a course between polls.
+///
+/// Threads are compared as listed, and fetched in full when their reply count or `updated_at`
+/// changes, to find new and newly endorsed replies. An endorsement of a reply alone is noticed
+/// the next time its thread is fetched.
+#[derive(Clone, Debug)]
+pub struct CourseWatcher {
+    course_id: u64,
+    threads: Option<HashMap<u64, ThreadState>>,
+    /// when the last poll started, in milliseconds since the Unix epoch; unique to each poll
+    polled_at: u128,
+}
+
+impl CourseWatcher {
+    /// A watcher for a course, which has not yet seen it.
+    pub fn new(course_id: impl Into<u64>) -> Self {
+        Self {
+            course_id: course_id.into(),
+            threads: None,
+            polled_at: 0,
+        }
+    }
+
+    /// The course watched.
+    pub fn course_id(&self) -> u64 {
+        self.course_id
+    }
+
+    /// Fetch the course and return what changed since the last poll; nothing on the first. On
+    /// error, the next poll compares with the last successful one.
+    pub async fn poll(&mut self, client: &Client) -> crate::Result<Vec<RelayEvent>> {
+        let now = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |d| d.as_millis());
+        self.polled_at = now.max(self.polled_at + 1);
+        let polled_at = self.polled_at;
+        let threads = client.get_all_course_threads(self.course_id, None).await?;
+        let Some(known) = &self.threads else {
+            self.threads = Some(
+                threads
+                    .iter()
+                    .map(|t| (u64::from(*t.id()), ThreadState::seen(t)))
+                    .collect(),
+            );
+            return Ok(Vec::new());
+        };
+
+        let mut events = Vec::new();
+        let mut next = HashMap::with_capacity(threads.len());
+        for thread in &threads {
+            let id = u64::from(*thread.id());
+            let event = |kind| RelayEvent::for_thread(client, kind, thread);
+            let mut state = match known.get(&id) {
+                Some(state) => state.clone(),
+                None => {
+                    let mut created = event(EventKind::ThreadCreated);
+                    created.author_id = author(*thread.is_anonymous(), *thread.user_id());
+                    created.text = Some(thread.document().clone());
+                    events.push(created);
+                    ThreadState {
+                        replies: Some(HashMap::new()),
+                        ..Default::default()
+                    }
+                }
+            };
+
+            if *thread.is_endorsed() && !state.is_endorsed {
+                events.push(event(EventKind::ThreadEndorsed));
+            }
+            let resolved = state.unresolved_count > 0 && *thread.unresolved_count() == 0;
+            let accepted = !state.is_accepted && thread.accepted_id().is_some();
+            if resolved || accepted {
+                events.push(event(EventKind::ThreadResolved));
+            }
+
+            if *thread.reply_count() != state.reply_count
+                || *thread.updated_at() != state.updated_at
+            {
+                let full = client.get_thread(id).await?.dissolve().0;
+                let replies = full.replies_chronological();
+                let before = match state.replies.take() {
+                    Some(before) => before,
+                    // first look at the replies: all but the newest few were there last time
+                    None => {
+                        let added = thread.reply_count().saturating_sub(state.reply_count);
+                        let old = replies.len().saturating_sub(added as usize);
+                        replies[..old]
+                            .iter()
+                            .map(|r| (u64::from(*r.id()), *r.is_endorsed()))
+                            .collect()
+                    }
+                };
+
+                for reply in &replies {
+                    let (created, endorsed) = match before.get(&u64::from(*reply.id())) {
+                        None => (true, *reply.is_endorsed()),
+                        Some(was_endorsed) => (false, *reply.is_endorsed() && !was_endorsed),
+                    };
+                    if created {
+                        let mut created = event(EventKind::ReplyCreated).with_reply(reply);
+                        created.text = Some(reply.document().clone());
+                        events.push(created);
+                    }
+                    if endorsed {
+                        events.push(event(EventKind::ReplyEndorsed).with_reply(reply));
+                    }
+                }
+                state.replies = Some(
+                    replies
+                        .iter()
+                        .map(|r| (u64::from(*r.id()), *r.is_endorsed()))
+                        .collect(),
+                );
+            }
+
+            next.insert(
+                id,
+                ThreadState {
+                    replies: state.replies,
+                    ..ThreadState::seen(thread)
+                },
+            );
+        }
+
+        // the same change can happen again, e.g. a thread resolved, reopened and resolved, so
+        // the time of the poll keeps IDs unique
+        for event in &mut events {
+            event.id = match event.reply_id {
+                Some(reply_id) => format!(
+                    "{}:{}:{reply_id}:{polled_at}",
+                    event.kind.as_str(),
+                    event.thread_id
+                ),
+                None => format!("{}:{}:{polled_at}", event.kind.as_str(), event.thread_id),
+            };
+        }
+        self.threads = Some(next);
+        Ok(events)
+    }
+}
+
+impl ThreadState {
+    fn seen(thread: &PartialThread) -> Self {
+        Self {
+            reply_count: *thread.reply_count(),
+            updated_at: thread.updated_at().clone(),
+            is_endorsed: *thread.is_endorsed(),
+            unresolved_count: *thread.unresolved_count(),
+            is_accepted: thread.accepted_id().is_some(),
+            replies: None,
+        }
+    }
+}
+
+fn author(is_anonymous: bool, user_id: impl Into<u64>) -> Option<u64> {
+    let user_id = user_id.into();
+    (!is_anonymous && user_id != 0).then_some(user_id)
+}
+
+impl RelayEvent {
+    fn for_thread(client: &Client, kind: EventKind, thread: &PartialThread) -> Self {
+        let (course_id, thread_id) = (u64::from(*thread.course_id()), u64::from(*thread.id()));
+        Self {
+            id: String::new(),
+            kind,
+            course_id,
+            thread_id,
+            thread_number: *thread.number(),
+            thread_type: thread.type_().clone(),
+            title: thread.title().clone(),
+            category: thread.category().clone(),
+            subcategory: thread.subcategory().clone(),
+            reply_id: None,
+            author_id: None,
+            text: None,
+            url: client.thread_url(course_id, thread_id),
+        }
+    }
+
+    fn with_reply(mut self, reply: &Reply) -> Self {
+        self.reply_id = Some(u64::from(*reply.id()));
+        self.author_id = author(*reply.is_anonymous(), *reply.user_id());
+        self
+    }
+}
+
+/// A delivery which failed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DeliveryFailure {
+    /// The endpoint.
+    pub url: String,
+    /// The [`RelayEvent::id`].
+    pub event_id: String,
+    /// How many times it has been tried.
+    pub attempts: u32,
+    /// Why the last attempt failed, or why it was dropped untried.
+    pub reason: String,
+}
+
+/// What happened in one [`Relay::tick`].
+#[derive(Debug, Default)]
+pub struct RelayReport {
+    /// New events from every course.
+    pub events: Vec<RelayEvent>,
+    /// Courses which could not be polled, by ID; they are polled again next tick.
+    pub errors: Vec<(u64, crate::Error)>,
+    /// Deliveries made, by endpoint and [`RelayEvent::id`].
+    pub delivered: Vec<(String, String)>,
+    /// Deliveries which failed and will be retried.
+    pub retrying: Vec<DeliveryFailure>,
+    /// Deliveries given up on.
+    pub dropped: Vec<DeliveryFailure>,
+}
+
+#[derive(Clone, Debug)]
+struct Delivery {
+    event_id: String,
+    kind: EventKind,
+    body: Vec<u8>,
+    attempts: u32,
+    due: Instant,
+}
+
+#[derive(Clone, Debug)]
+struct EndpointQueue {
+    endpoint: Endpoint,
+    queue: VecDeque<Delivery>,
+}
+
+impl EndpointQueue {
+    /// Try the deliveries due by `now` in order, stopping at the first failure. Only the
+    /// deliveries in the report are filled in.
+    async fn deliver(
+        &mut self,
+        http: &reqwest::Client,
+        config: &RelayConfig,
+        now: Instant,
+    ) -> RelayReport {
+        let mut report = RelayReport::default();
+        while let Some(delivery) = self.queue.front_mut() {
+            if delivery.due > now {
+                break;
+            }
+            delivery.attempts += 1;
+            let result = http
+                .post(&self.endpoint.url)
+                .header(CONTENT_TYPE, "application/json")
+                .header(
+                    SIGNATURE_HEADER,
+                    sign(&self.endpoint.secret, &delivery.body),
+                )
+                .header(EVENT_HEADER, delivery.kind.as_str())
+                .header(DELIVERY_HEADER, &delivery.event_id)
+                .body(delivery.body.clone())
+                .send()
+                .await;
+            let reason = match result {
+                Ok(response) if response.status().is_success() => None,
+                Ok(response) => Some(format!("HTTP {}", response.status())),
+                Err(e) => Some(e.to_string()),
+            };
+
+            let Some(reason) = reason else {
+                let done = self.queue.pop_front().expect("front was just delivered");
+                report
+                    .delivered
+                    .push((self.endpoint.url.clone(), done.event_id));
+                continue;
+            };
+            let failure = DeliveryFailure {
+                url: self.endpoint.url.clone(),
+                event_id: delivery.event_id.clone(),
+                attempts: delivery.attempts,
+                reason,
+            };
+            if delivery.attempts >= config.max_attempts {
+                self.queue.pop_front();
+                report.dropped.push(failure);
+                continue;
+            }
+            let backoff = config
+                .retry_base_secs
+                .saturating_mul(1 << (delivery.attempts - 1).min(20))
+                .min(3600);
+            delivery.due = now + Duration::from_secs(backoff);
+            report.retrying.push(failure);
+            break;
+        }
+        report
+    }
+}
+
+/// Polls courses and delivers their events to webhooks. See the [module docs](self).
+#[derive(Clone, Debug)]
+pub struct Relay {
+    client: Client,
+    http: reqwest::Client,
+    config: RelayConfig,
+    watchers: Vec<CourseWatcher>,
+    queues: Vec<EndpointQueue>,
+}
+
+impl Relay {
+    /// A relay for the courses and endpoints in `config`, polling with `client`.
+    pub fn new(client: Client, config: RelayConfig) -> Self {
+        Self {
+            client,
+            http: reqwest::Client::builder()
+                .timeout(Duration::from_secs(config.timeout_secs))
+                .build()
+                .expect("a client with only a timeout set is valid"),
+            watchers: config
+                .courses
+                .iter()
+                .map(|&c| CourseWatcher::new(c))
+                .collect(),
+            queues: config
+                .endpoints
+                .iter()
+                .map(|endpoint| EndpointQueue {
+                    endpoint: endpoint.clone(),
+                    queue: VecDeque::new(),
+                })
+                .collect(),
+            config,
+        }
+    }
+
+    /// Use a custom `reqwest::Client` for deliveries, e.g. with a proxy. This replaces the one
+    /// with a timeout of `timeout_secs`, so set a timeout on it too.
+    pub fn with_http(mut self, http: reqwest::Client) -> Self {
+        self.http = http;
+        self
+    }
+
+    /// The number of deliveries waiting for each endpoint, by URL.
+    pub fn pending(&self) -> Vec<(&str, usize)> {
+        self.queues
+            .iter()
+            .map(|q| (q.endpoint.url.as_str(), q.queue.len()))
+            .collect()
+    }
+
+    /// Queue `event` for every endpoint it matches. Returns deliveries dropped to make room.
+    pub fn enqueue(&mut self, event: &RelayEvent) -> crate::Result<Vec<DeliveryFailure>> {
+        let body = serde_json::to_vec(event)?;
+        let mut dropped = Vec::new();
+        for queue in &mut self.queues {
+            if !queue.endpoint.filter.matches(event) {
+                continue;
+            }
+            queue.queue.push_back(Delivery {
+                event_id: event.id.clone(),
+                kind: event.kind,
+                body: body.clone(),
+                attempts: 0,
+                due: Instant::now(),
+            });
+            while queue.queue.len() > self.config.max_queue {
+                let oldest = queue
+                    .queue
+                    .pop_front()
+                    .expect("queue is over a non-negative size");
+                dropped.push(DeliveryFailure {
+                    url: queue.endpoint.url.clone(),
+                    event_id: oldest.event_id,
+                    attempts: oldest.attempts,
+                    reason: String::from("queue full"),
+                });
+            }
+        }
+        Ok(dropped)
+    }
+
+    /// Try every delivery which is due, oldest first for each endpoint. Endpoints are delivered
+    /// to concurrently. An endpoint's remaining deliveries wait behind one which fails, so each
+    /// endpoint receives events in order.
+    pub async fn deliver(&mut self, report: &mut RelayReport) {
+        let now = Instant::now();
+        let outcomes = join_all(
+            self.queues
+                .iter_mut()
+                .map(|queue| queue.deliver(&self.http, &self.config, now)),
+        )
+        .await;
+        for outcome in outcomes {
+            report.delivered.extend(outcome.delivered);
+            report.retrying.extend(outcome.retrying);
+            report.dropped.extend(outcome.dropped);
+        }
+    }
+
+    /// Poll every course once, queue the new events and try every delivery which is due.
+    pub async fn tick(&mut self) -> RelayReport {
+        let mut report = RelayReport::default();
+        for watcher in &mut self.watchers {
+            match watcher.poll(&self.client).await {
+                Ok(events) => report.events.extend(events),
+                Err(e) => report.errors.push((watcher.course_id(), e)),
+            }
+        }
+        for event in report.events.clone() {
+            match self.enqueue(&event) {
+                Ok(dropped) => report.dropped.extend(dropped),
+                Err(e) => report.errors.push((event.course_id, e)),
+            }
+        }
+        self.deliver(&mut report).await;
+        report
+    }
+
+    /// When the earliest delivery waiting to be retried is due, if any.
+    pub fn next_due(&self) -> Option<Instant> {
+        self.queues
+            .iter()
+            .filter_map(|q| q.queue.front().map(|d| d.due))
+            .min()
+    }
+
+    /// [`tick`](Self::tick) forever, every `poll_interval_secs`, passing each report to
+    /// `on_tick`. Between ticks, retries are delivered as they fall due, each with its own
+    /// report.
+    pub async fn run(mut self, mut on_tick: impl FnMut(&RelayReport)) {
+        let interval = Duration::from_secs(self.config.poll_interval_secs);
+        let mut next_poll = Instant::now();
+        loop {
+            let now = Instant::now();
+            if now >= next_poll {
+                next_poll = now + interval;
+                on_tick(&self.tick().await);
+            } else {
+                let mut report = RelayReport::default();
+                self.deliver(&mut report).await;
+                on_tick(&report);
+            }
+            let wake = self.next_due().map_or(next_poll, |due| due.min(next_poll));
+            tokio::time::sleep_until(wake).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signatures_verify() {
+        let signature = sign("hunter2", b"{}");
+        assert!(signature.starts_with("sha256="));
+        assert!(verify("hunter2", b"{}", &signature));
+        assert!(!verify("hunter3", b"{}", &signature));
+        assert!(!verify("hunter2", b"{ }", &signature));
+        assert!(!verify(
+            "hunter2",
+            b"{}",
+            signature.trim_start_matches("sha256=")
+        ));
+        assert!(!verify("hunter2", b"{}", "sha256=abc"));
+        assert!(!verify("hunter2", b"{}", "sha256=zz"));
+    }
+
+    #[test]
+    fn config_defaults() {
+        let config = RelayConfig::from_toml(
+            r#"
+            courses = [1]
+
+            [[endpoints]]
+            url = "http://localhost/hook"
+            secret = "s"
+            events = ["thread_resolved"]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.poll_interval_secs, 60);
+        assert_eq!(config.timeout_secs, 30);
+        assert_eq!(
+            config.endpoints[0].filter.events,
+            [EventKind::ThreadResolved]
+        );
+        assert!(config.endpoints[0].filter.thread_types.is_empty());
+    }
+
+    #[test]
+    fn debug_redacts_secrets() {
+        let config = RelayConfig::new(
+            vec![1],
+            vec![Endpoint {
+                url: String::from("http://localhost/hook"),
+                secret: String::from("hunter2"),
+                filter: EventFilter::default(),
+            }],
+        );
+        let debug = format!("{config:?}");
+        assert!(debug.contains("http://localhost/hook"));
+        assert!(!debug.contains("hunter2"));
+    }
+
+    fn event() -> RelayEvent {
+        RelayEvent {
+            id: String::from("thread_created:1:1"),
+            kind: EventKind::ThreadCreated,
+            course_id: 1,
+            thread_id: 1,
+            thread_number: 1,
+            thread_type: ThreadType::Question,
+            title: String::from("Help"),
+            category: String::new(),
+            subcategory: String::new(),
+            reply_id: None,
+            author_id: None,
+            text: None,
+            url: String::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn unanswered_deliveries_time_out() {
+        // accepts connections into its backlog but never answers
+        let silent = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", silent.local_addr().unwrap());
+        let mut config = RelayConfig::new(
+            Vec::new(),
+            vec![Endpoint {
+                url: url.clone(),
+                secret: String::from("s"),
+                filter: EventFilter::default(),
+            }],
+        );
+        config.timeout_secs = 1;
+        let mut relay = Relay::new(Client::new("token"), config);
+
+        relay.enqueue(&event()).unwrap();
+        let mut report = RelayReport::default();
+        tokio::time::timeout(Duration::from_secs(10), relay.deliver(&mut report))
+            .await
+            .expect("delivery should time out on its own");
+        assert_eq!(report.retrying.len(), 1);
+        assert_eq!(report.retrying[0].url, url);
+        assert_eq!(relay.pending(), [(url.as_str(), 1)]);
+    }
+
+    #[tokio::test]
+    async fn run_retries_before_the_next_poll() {
+        // fails the first delivery and accepts the rest
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            for (i, stream) in listener.incoming().enumerate() {
+                let mut stream = stream.unwrap();
+                let mut request = Vec::new();
+                let mut buf = [0; 4096];
+                while !String::from_utf8_lossy(&request).contains("\r\n\r\n{") {
+                    let n = std::io::Read::read(&mut stream, &mut buf).unwrap();
+                    if n == 0 {
+                        break;
+                    }
+                    request.extend_from_slice(&buf[..n]);
+                }
+                let status = if i == 0 {
+                    "500 Internal Server Error"
+                } else {
+                    "200 OK"
+                };
+                let response =
+                    format!("HTTP/1.1 {status}\r\ncontent-length: 0\r\nconnection: close\r\n\r\n");
+                std::io::Write::write_all(&mut stream, response.as_bytes()).unwrap();
+            }
+        });
+        let mut config = RelayConfig::new(
+            Vec::new(),
+            vec![Endpoint {
+                url,
+                secret: String::from("s"),
+                filter: EventFilter::default(),
+            }],
+        );
+        config.poll_interval_secs = 3600;
+        config.retry_base_secs = 1;
+        let mut relay = Relay::new(Client::new("token"), config);
+        relay.enqueue(&event()).unwrap();
+
+        let mut delivered = Vec::new();
+        let run = relay.run(|report| delivered.extend(report.delivered.clone()));
+        let _ = tokio::time::timeout(Duration::from_secs(3), run).await;
+        assert_eq!(delivered.len(), 1);
+    }
+
+    #[cfg(feature = "testing")]
+    mod watcher {
+        use serde_json::json;
+
+        use super::*;
+        use crate::testing::{MockServer, fixtures::ThreadFixture};
+
+        fn thread(unresolved_count: u64) -> crate::model::thread::Thread {
+            ThreadFixture {
+                overrides: json!({ "unresolved_count": unresolved_count })
+                    .as_object()
+                    .unwrap()
+                    .clone(),
+                ..Default::default()
+            }
+            .build()
+        }
+
+        #[tokio::test]
+        async fn repeated_changes_get_new_ids() {
+            let server = MockServer::start().await.unwrap();
+            let client = server.client();
+            let mut watcher = CourseWatcher::new(1u64);
+            server.add_thread(thread(1));
+            assert!(watcher.poll(&client).await.unwrap().is_empty());
+
+            let mut ids = Vec::new();
+            for _ in 0..2 {
+                server.add_thread(thread(0));
+                let events = watcher.poll(&client).await.unwrap();
+                assert_eq!(events.len(), 1);
+                assert_eq!(events[0].kind, EventKind::ThreadResolved);
+                ids.push(events[0].id.clone());
+
+                server.add_thread(thread(1));
+                assert!(watcher.poll(&client).await.unwrap().is_empty());
+            }
+            assert_ne!(ids[0], ids[1]);
+        }
+    }
+}
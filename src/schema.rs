@@ -0,0 +1,375 @@
+//! Handling of fields Ed Discussion sends which the models here do not (yet) know about.
+//!
+//! Ed Discussion adds fields to its responses without notice. Every model struct keeps any it
+//! does not recognize in an [`UnknownFields`] map rather than dropping them. A [`Client`] in
+//! [`SchemaMode::Strict`] instead fails the request with [`Error::Schema`], which suits schema
+//! drift monitoring; in either mode, every unknown field and every known field which failed to
+//! deserialize is noted in the client's [`SchemaReport`].
+//!
+//! [`Client`]: crate::Client
+//! [`Error::Schema`]: crate::Error::Schema
+
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, btree_map},
+    fmt::{self, Debug},
+    marker::PhantomData,
+    sync::{Arc, Mutex},
+};
+
+use serde::{Deserialize, Deserializer, de::DeserializeOwned};
+#[cfg(feature = "serde")]
+use serde::{Serialize, Serializer};
+use serde_json::Value;
+
+/// How a [`Client`](crate::Client) treats fields it does not know about.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SchemaMode {
+    /// Keep unknown fields in each struct's [`UnknownFields`].
+    #[default]
+    Lenient,
+    /// Fail with [`Error::Schema`](crate::Error::Schema) on any unknown field.
+    Strict,
+}
+
+/// Fields of a `T` which this crate does not know about, by name.
+pub struct UnknownFields<T> {
+    fields: BTreeMap<String, Value>,
+    owner: PhantomData<fn() -> T>,
+}
+
+impl<T> Clone for UnknownFields<T> {
+    fn clone(&self) -> Self {
+        Self {
+            fields: self.fields.clone(),
+            owner: PhantomData,
+        }
+    }
+}
+
+impl<T> Debug for UnknownFields<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fields.fmt(f)
+    }
+}
+
+impl<T> Default for UnknownFields<T> {
+    fn default() -> Self {
+        Self {
+            fields: BTreeMap::new(),
+            owner: PhantomData,
+        }
+    }
+}
+
+impl<T> UnknownFields<T> {
+    /// The value of an unknown field.
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.fields.get(name)
+    }
+
+    /// All unknown fields, in name order.
+    pub fn iter(&self) -> btree_map::Iter<'_, String, Value> {
+        self.fields.iter()
+    }
+
+    /// The number of unknown fields.
+    pub fn len(&self) -> usize {
+        self.fields.len()
+    }
+
+    /// Whether every field was known.
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a UnknownFields<T> {
+    type Item = (&'a String, &'a Value);
+    type IntoIter = btree_map::Iter<'a, String, Value>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// `T`'s name without its module path.
+fn owner_name<T>() -> &'static str {
+    let name = std::any::type_name::<T>();
+    name.rsplit("::").next().unwrap_or(name)
+}
+
+impl<'de, T> Deserialize<'de> for UnknownFields<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let fields = BTreeMap::<String, Value>::deserialize(deserializer)?;
+
+        if !fields.is_empty() {
+            let strict = CONTEXT.with_borrow_mut(|context| {
+                let Some(context) = context else {
+                    return false;
+                };
+                context.report.record_unknown(owner_name::<T>(), &fields);
+                context.unknown_rejected |= context.mode == SchemaMode::Strict;
+                context.mode == SchemaMode::Strict
+            });
+
+            if strict {
+                let names: Vec<_> = fields.keys().map(String::as_str).collect();
+                return Err(serde::de::Error::custom(format!(
+                    "unknown fields in {}: {}",
+                    owner_name::<T>(),
+                    names.join(", ")
+                )));
+            }
+        }
+
+        Ok(Self {
+            fields,
+            owner: PhantomData,
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T> Serialize for UnknownFields<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.fields.serialize(serializer)
+    }
+}
+
+/// A field seen by a [`SchemaReport`] which no model knows about.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UnknownFieldReport {
+    /// The model the field was found on, e.g. `Thread`.
+    pub owner: String,
+    /// The field's name.
+    pub name: String,
+    /// How many times it was seen.
+    pub count: u64,
+    /// The first value seen.
+    pub example: Value,
+}
+
+/// A known field seen by a [`SchemaReport`] which failed to deserialize, e.g. because its type
+/// changed or it went missing.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MismatchReport {
+    /// The path of the endpoint whose response failed, e.g. `/api/user`.
+    pub endpoint: String,
+    /// Where in the response, e.g. `threads[3].user_id`.
+    pub path: String,
+    /// What went wrong.
+    pub message: String,
+}
+
+#[derive(Debug, Default)]
+struct ReportInner {
+    unknown: BTreeMap<(String, String), UnknownFieldReport>,
+    mismatches: Vec<MismatchReport>,
+}
+
+/// Unknown and mismatched fields observed by a [`Client`](crate::Client), from
+/// [`Client::schema_report`](crate::Client::schema_report).
+///
+/// Clones share the same observations.
+#[derive(Clone, Debug, Default)]
+pub struct SchemaReport {
+    inner: Arc<Mutex<ReportInner>>,
+}
+
+impl SchemaReport {
+    fn record_unknown(&self, owner: &str, fields: &BTreeMap<String, Value>) {
+        let mut inner = self.inner.lock().expect("schema report poisoned");
+        for (name, value) in fields {
+            inner
+                .unknown
+                .entry((String::from(owner), name.clone()))
+                .or_insert_with(|| UnknownFieldReport {
+                    owner: String::from(owner),
+                    name: name.clone(),
+                    count: 0,
+                    example: value.clone(),
+                })
+                .count += 1;
+        }
+    }
+
+    fn record_mismatch(&self, mismatch: MismatchReport) {
+        self.inner
+            .lock()
+            .expect("schema report poisoned")
+            .mismatches
+            .push(mismatch);
+    }
+
+    /// Every unknown field seen, by model then field name.
+    pub fn unknown_fields(&self) -> Vec<UnknownFieldReport> {
+        let inner = self.inner.lock().expect("schema report poisoned");
+        inner.unknown.values().cloned().collect()
+    }
+
+    /// Every failure to deserialize a known field, oldest first.
+    pub fn mismatches(&self) -> Vec<MismatchReport> {
+        let inner = self.inner.lock().expect("schema report poisoned");
+        inner.mismatches.clone()
+    }
+
+    /// Whether nothing unexpected has been seen.
+    pub fn is_empty(&self) -> bool {
+        let inner = self.inner.lock().expect("schema report poisoned");
+        inner.unknown.is_empty() && inner.mismatches.is_empty()
+    }
+
+    /// Forget everything seen so far.
+    pub fn clear(&self) {
+        *self.inner.lock().expect("schema report poisoned") = ReportInner::default();
+    }
+}
+
+struct Context {
+    mode: SchemaMode,
+    report: SchemaReport,
+    /// whether an error is from strict mode rejecting an unknown field rather than a mismatch
+    unknown_rejected: bool,
+}
+
+thread_local! {
+    static CONTEXT: RefCell<Option<Context>> = const { RefCell::new(None) };
+}
+
+/// Deserialize a response from `endpoint`, applying `mode` and noting anything unexpected in
+/// `report`.
+pub(crate) fn deserialize<T>(
+    body: &[u8],
+    endpoint: &str,
+    mode: SchemaMode,
+    report: &SchemaReport,
+) -> crate::Result<T>
+where
+    T: DeserializeOwned,
+{
+    CONTEXT.set(Some(Context {
+        mode,
+        report: report.clone(),
+        unknown_rejected: false,
+    }));
+    let result = serde_path_to_error::deserialize(&mut serde_json::Deserializer::from_slice(body));
+    let unknown_rejected = CONTEXT
+        .take()
+        .is_some_and(|context| context.unknown_rejected);
+
+    result.map_err(|e| {
+        let path = e.path().to_string();
+        let source = e.into_inner();
+        if !source.is_data() {
+            return crate::Error::Json(source);
+        }
+
+        if !unknown_rejected {
+            report.record_mismatch(MismatchReport {
+                endpoint: String::from(endpoint),
+                path: path.clone(),
+                message: source.to_string(),
+            });
+        }
+        crate::Error::Schema { path, source }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[derive(Debug, Deserialize)]
+    struct Inner {
+        id: u64,
+        #[serde(flatten)]
+        unknown_fields: UnknownFields<Inner>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Outer {
+        items: Vec<Inner>,
+        #[serde(flatten)]
+        unknown_fields: UnknownFields<Outer>,
+    }
+
+    fn body(value: Value) -> Vec<u8> {
+        serde_json::to_vec(&value).unwrap()
+    }
+
+    #[test]
+    fn lenient_keeps_and_reports_unknown_fields() {
+        let report = SchemaReport::default();
+        let body = body(json!({"items": [{"id": 1, "new": true}, {"id": 2, "new": false}]}));
+        let outer: Outer = deserialize(&body, "/api/test", SchemaMode::Lenient, &report).unwrap();
+
+        assert_eq!(outer.items[0].id, 1);
+        assert_eq!(outer.items[0].unknown_fields.get("new"), Some(&json!(true)));
+        assert!(outer.unknown_fields.is_empty());
+        assert_eq!(
+            report.unknown_fields(),
+            [UnknownFieldReport {
+                owner: String::from("Inner"),
+                name: String::from("new"),
+                count: 2,
+                example: json!(true),
+            }]
+        );
+        assert!(report.mismatches().is_empty());
+    }
+
+    #[test]
+    fn strict_rejects_unknown_fields() {
+        let report = SchemaReport::default();
+        let body = body(json!({"items": [{"id": 1}, {"id": 2, "new": true}]}));
+        let result: crate::Result<Outer> =
+            deserialize(&body, "/api/test", SchemaMode::Strict, &report);
+
+        match result {
+            Err(crate::Error::Schema { path, source }) => {
+                assert_eq!(path, "items[1]");
+                assert!(source.to_string().contains("unknown fields in Inner: new"));
+            }
+            other => panic!("expected a schema error, got {other:?}"),
+        }
+        assert_eq!(report.unknown_fields().len(), 1);
+        // rejecting an unknown field is not a mismatch
+        assert!(report.mismatches().is_empty());
+    }
+
+    #[test]
+    fn reports_mismatches() {
+        let report = SchemaReport::default();
+        let body = body(json!({"items": [{"id": "one"}]}));
+        let result: crate::Result<Outer> =
+            deserialize(&body, "/api/test", SchemaMode::Lenient, &report);
+
+        assert!(matches!(result, Err(crate::Error::Schema { .. })));
+        let mismatches = report.mismatches();
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(mismatches[0].endpoint, "/api/test");
+        assert_eq!(mismatches[0].path, "items[0].id");
+
+        report.clear();
+        assert!(report.is_empty());
+    }
+
+    #[test]
+    fn malformed_json_is_not_a_schema_error() {
+        let report = SchemaReport::default();
+        let result: crate::Result<Outer> =
+            deserialize(b"{\"items\": [", "/api/test", SchemaMode::Strict, &report);
+
+        assert!(matches!(result, Err(crate::Error::Json(_))));
+        assert!(report.is_empty());
+    }
+}
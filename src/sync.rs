@@ -0,0 +1,416 @@
+//! Two-way synchronisation between an Ed workspace and a local directory.
+//!
+//! [`sync_workspace`] compares the SHA-256 of every file
+//! on each side with a [`Manifest`] of the hashes both sides had after the last sync, kept in the
+//! local directory. A file changed on only one side since then is copied to the other, and a file
+//! deleted on only one side is deleted from the other. A file changed or deleted differently on
+//! both sides is reported as a [`SyncConflict`] and left alone on both, to be reported again
+//! until the two sides agree.
+//!
+//! ```no_run
+//! # async fn example(client: edstem::Client) -> edstem::Result<()> {
+//! use edstem::{opts::WorkspaceSyncOptions, sync::sync_workspace};
+//!
+//! let report =
+//!     sync_workspace(&client, 1234u64, "assignment-1", WorkspaceSyncOptions::default()).await?;
+//! for conflict in &report.conflicts {
+//!     eprintln!("{} changed on both sides", conflict.path);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::{Component, Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{Client, model::workspace::WorkspaceFileKind, opts::WorkspaceSyncOptions};
+
+/// Sync a workspace with the local directory `root` in both directions, reporting files
+/// changed on both sides instead of overwriting either.
+///
+/// This downloads every file in the workspace, so is slow for a large workspace.
+pub async fn sync_workspace(
+    client: &Client,
+    workspace_id: impl Into<u64>,
+    root: impl AsRef<Path>,
+    options: WorkspaceSyncOptions,
+) -> crate::Result<SyncReport> {
+    let workspace_id = workspace_id.into();
+    let root = root.as_ref();
+    let manifest_path = root.join(&options.manifest);
+    let mut manifest = match Manifest::load(&manifest_path)? {
+        Some(manifest) if manifest.workspace_id != workspace_id => {
+            return Err(crate::Error::Refused(format!(
+                "{} is synced with workspace {}",
+                root.display(),
+                manifest.workspace_id
+            )));
+        }
+        Some(manifest) => manifest,
+        None => Manifest {
+            workspace_id,
+            ..Default::default()
+        },
+    };
+
+    let local = scan_local(root, &options.manifest, &options.ignore)?;
+    let mut remote = BTreeMap::new();
+    for file in client.get_workspace_files(workspace_id).await? {
+        if *file.kind() == WorkspaceFileKind::File && !is_ignored(file.path(), &options.ignore) {
+            let content = client
+                .download_workspace_file(workspace_id, file.path())
+                .await?;
+            remote.insert(file.path().clone(), content);
+        }
+    }
+    let (local_hashes, remote_hashes) = (hashes(&local), hashes(&remote));
+
+    let mut report = SyncReport::default();
+    for action in plan(&manifest.files, &local_hashes, &remote_hashes) {
+        if !options.dry_run {
+            match &action {
+                SyncAction::Download(path) => {
+                    let dest = local_path(root, path)?;
+                    if let Some(parent) = dest.parent() {
+                        fs::create_dir_all(parent)?;
+                    }
+                    fs::write(dest, &remote[path])?;
+                }
+                SyncAction::Upload(path) => {
+                    client
+                        .upload_workspace_file(workspace_id, path, &local[path])
+                        .await?;
+                }
+                SyncAction::DeleteLocal(path) => fs::remove_file(local_path(root, path)?)?,
+                SyncAction::DeleteRemote(path) => {
+                    client.delete_workspace_file(workspace_id, path).await?
+                }
+                SyncAction::Conflict(_) => {}
+            }
+        }
+        report.record(action);
+    }
+    if options.dry_run {
+        return Ok(report);
+    }
+
+    manifest.files = synced(&manifest.files, &local_hashes, &remote_hashes, &report);
+    manifest.save(&manifest_path)?;
+    Ok(report)
+}
+
+/// The lowercase hex SHA-256 of `content`.
+pub fn hash(content: &[u8]) -> String {
+    Sha256::digest(content)
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+/// The state of both sides after the last sync, stored as JSON in the local directory.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Manifest {
+    /// The workspace the directory is synced with.
+    pub workspace_id: u64,
+    /// The hash of every file present on both sides, by path.
+    pub files: BTreeMap<String, String>,
+}
+
+impl Manifest {
+    /// Read the manifest at `path`, or `None` if there is none yet.
+    pub fn load(path: &Path) -> crate::Result<Option<Self>> {
+        match fs::read(path) {
+            Ok(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Write the manifest to `path`.
+    pub fn save(&self, path: &Path) -> crate::Result<()> {
+        fs::write(path, serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// A file changed differently on both sides since the last sync.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct SyncConflict {
+    /// The path, relative to both roots.
+    pub path: String,
+    /// The hash of the local file, or `None` if it was deleted.
+    pub local: Option<String>,
+    /// The hash of the workspace file, or `None` if it was deleted.
+    pub remote: Option<String>,
+}
+
+/// What to do with one path to bring both sides into agreement.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SyncAction {
+    /// Copy the workspace file over the local one.
+    Download(String),
+    /// Copy the local file over the workspace one.
+    Upload(String),
+    /// Delete the local file.
+    DeleteLocal(String),
+    /// Delete the workspace file.
+    DeleteRemote(String),
+    /// Leave both sides alone.
+    Conflict(SyncConflict),
+}
+
+/// Decide what to do with each path, given the hashes by path in the `base` manifest and on the
+/// `local` and `remote` sides. Paths which agree on both sides need nothing and are omitted.
+pub fn plan(
+    base: &BTreeMap<String, String>,
+    local: &BTreeMap<String, String>,
+    remote: &BTreeMap<String, String>,
+) -> Vec<SyncAction> {
+    let paths: BTreeSet<&String> = base
+        .keys()
+        .chain(local.keys())
+        .chain(remote.keys())
+        .collect();
+
+    paths
+        .into_iter()
+        .filter_map(|path| {
+            let (base, local, remote) = (base.get(path), local.get(path), remote.get(path));
+            let path = path.clone();
+            match (local == remote, local == base, remote == base) {
+                (true, _, _) => None,
+                (_, true, _) if remote.is_some() => Some(SyncAction::Download(path)),
+                (_, true, _) => Some(SyncAction::DeleteLocal(path)),
+                (_, _, true) if local.is_some() => Some(SyncAction::Upload(path)),
+                (_, _, true) => Some(SyncAction::DeleteRemote(path)),
+                _ => Some(SyncAction::Conflict(SyncConflict {
+                    path,
+                    local: local.cloned(),
+                    remote: remote.cloned(),
+                })),
+            }
+        })
+        .collect()
+}
+
+/// The outcome of [`sync_workspace`]. Paths are
+/// relative to both roots.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct SyncReport {
+    /// Files copied from the workspace.
+    pub downloaded: Vec<String>,
+    /// Files copied to the workspace.
+    pub uploaded: Vec<String>,
+    /// Files deleted locally because they were deleted from the workspace.
+    pub deleted_local: Vec<String>,
+    /// Files deleted from the workspace because they were deleted locally.
+    pub deleted_remote: Vec<String>,
+    /// Files left alone because they changed on both sides.
+    pub conflicts: Vec<SyncConflict>,
+}
+
+impl SyncReport {
+    fn record(&mut self, action: SyncAction) {
+        match action {
+            SyncAction::Download(path) => self.downloaded.push(path),
+            SyncAction::Upload(path) => self.uploaded.push(path),
+            SyncAction::DeleteLocal(path) => self.deleted_local.push(path),
+            SyncAction::DeleteRemote(path) => self.deleted_remote.push(path),
+            SyncAction::Conflict(conflict) => self.conflicts.push(conflict),
+        }
+    }
+
+    /// Whether both sides already agreed.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// The hash of each file in `files`, by path.
+fn hashes(files: &BTreeMap<String, Vec<u8>>) -> BTreeMap<String, String> {
+    files
+        .iter()
+        .map(|(path, content)| (path.clone(), hash(content)))
+        .collect()
+}
+
+/// The hashes for the next [`Manifest`], once the actions in `report` have been carried out.
+/// Conflicts keep their old hashes, so they are reported until both sides agree.
+fn synced(
+    base: &BTreeMap<String, String>,
+    local: &BTreeMap<String, String>,
+    remote: &BTreeMap<String, String>,
+    report: &SyncReport,
+) -> BTreeMap<String, String> {
+    let mut files: BTreeMap<String, String> = local
+        .iter()
+        .filter(|(path, hash)| remote.get(*path) == Some(hash))
+        .map(|(path, hash)| (path.clone(), hash.clone()))
+        .collect();
+    for path in &report.downloaded {
+        files.insert(path.clone(), remote[path].clone());
+    }
+    for path in &report.uploaded {
+        files.insert(path.clone(), local[path].clone());
+    }
+    for conflict in &report.conflicts {
+        if let Some(hash) = base.get(&conflict.path) {
+            files.insert(conflict.path.clone(), hash.clone());
+        }
+    }
+    files
+}
+
+/// Whether `path` is excluded by one of the `ignore` prefixes, each a file or directory.
+fn is_ignored(path: &str, ignore: &[String]) -> bool {
+    ignore.iter().any(|prefix| {
+        let prefix = prefix.trim_end_matches('/');
+        path == prefix
+            || path
+                .strip_prefix(prefix)
+                .is_some_and(|rest| rest.starts_with('/'))
+    })
+}
+
+/// Where `path`, as named by the workspace, lives under `root`. Refuses paths which would escape
+/// `root`.
+fn local_path(root: &Path, path: &str) -> crate::Result<PathBuf> {
+    let relative = Path::new(path);
+    if !relative
+        .components()
+        .all(|c| matches!(c, Component::Normal(_)))
+    {
+        return Err(crate::Error::Refused(format!(
+            "workspace path {path:?} is outside the local directory"
+        )));
+    }
+    Ok(root.join(relative))
+}
+
+/// Every file under `root` by `/`-separated relative path, except those ignored and the manifest.
+fn scan_local(
+    root: &Path,
+    manifest: &str,
+    ignore: &[String],
+) -> crate::Result<BTreeMap<String, Vec<u8>>> {
+    let mut files = BTreeMap::new();
+    let mut dirs = vec![(root.to_path_buf(), String::new())];
+    while let Some((dir, prefix)) = dirs.pop() {
+        for entry in fs::read_dir(&dir)? {
+            let entry = entry?;
+            let Some(name) = entry.file_name().to_str().map(String::from) else {
+                continue;
+            };
+            let path = format!("{prefix}{name}");
+            if path == manifest || is_ignored(&path, ignore) {
+                continue;
+            }
+
+            let file_type = entry.file_type()?;
+            if file_type.is_dir() {
+                dirs.push((entry.path(), format!("{path}/")));
+            } else if file_type.is_file() {
+                files.insert(path, fs::read(entry.path())?);
+            }
+        }
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn files(entries: &[(&str, &str)]) -> BTreeMap<String, String> {
+        entries
+            .iter()
+            .map(|(path, hash)| (String::from(*path), String::from(*hash)))
+            .collect()
+    }
+
+    #[test]
+    fn plan_copies_one_sided_changes() {
+        let base = files(&[("a", "1"), ("b", "1"), ("c", "1"), ("d", "1")]);
+        let local = files(&[("a", "1"), ("b", "2"), ("d", "1"), ("new", "1")]);
+        let remote = files(&[("a", "2"), ("b", "1"), ("c", "1")]);
+        assert_eq!(
+            plan(&base, &local, &remote),
+            [
+                SyncAction::Download(String::from("a")),
+                SyncAction::Upload(String::from("b")),
+                SyncAction::DeleteRemote(String::from("c")),
+                SyncAction::DeleteLocal(String::from("d")),
+                SyncAction::Upload(String::from("new")),
+            ]
+        );
+    }
+
+    #[test]
+    fn plan_reports_conflicts() {
+        let base = files(&[("edited", "1"), ("gone", "1")]);
+        let local = files(&[("edited", "2"), ("added", "1")]);
+        let remote = files(&[("edited", "3"), ("gone", "2"), ("added", "2")]);
+        let conflict = |path: &str, local: Option<&str>, remote: Option<&str>| {
+            SyncAction::Conflict(SyncConflict {
+                path: String::from(path),
+                local: local.map(String::from),
+                remote: remote.map(String::from),
+            })
+        };
+        assert_eq!(
+            plan(&base, &local, &remote),
+            [
+                conflict("added", Some("1"), Some("2")),
+                conflict("edited", Some("2"), Some("3")),
+                conflict("gone", None, Some("2")),
+            ]
+        );
+    }
+
+    #[test]
+    fn plan_leaves_agreeing_paths_alone() {
+        let base = files(&[("same", "1"), ("both", "1"), ("deleted", "1")]);
+        let both = files(&[("same", "1"), ("both", "2"), ("added", "1")]);
+        assert!(plan(&base, &both, &both).is_empty());
+    }
+
+    #[test]
+    fn synced_keeps_conflicts_at_their_old_hashes() {
+        let base = files(&[("a", "1"), ("conflict", "1")]);
+        let local = files(&[("a", "1"), ("conflict", "2"), ("up", "1")]);
+        let remote = files(&[("a", "1"), ("conflict", "3"), ("down", "1")]);
+        let mut report = SyncReport::default();
+        for action in plan(&base, &local, &remote) {
+            report.record(action);
+        }
+        assert_eq!(
+            synced(&base, &local, &remote, &report),
+            files(&[("a", "1"), ("conflict", "1"), ("down", "1"), ("up", "1")])
+        );
+    }
+
+    #[test]
+    fn ignored_prefixes_match_whole_components() {
+        let ignore = [String::from("build/")];
+        assert!(is_ignored("build", &ignore));
+        assert!(is_ignored("build/out.o", &ignore));
+        assert!(!is_ignored("builder.py", &ignore));
+    }
+
+    #[test]
+    fn local_paths_stay_under_root() {
+        let root = Path::new("root");
+        assert_eq!(
+            local_path(root, "src/main.py").unwrap(),
+            root.join("src/main.py")
+        );
+        assert!(local_path(root, "../escape").is_err());
+        assert!(local_path(root, "/etc/passwd").is_err());
+    }
+}
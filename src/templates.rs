@@ -0,0 +1,305 @@
+//! Filling saved replies with details of the thread being answered.
+//!
+//! A [`ReplyTemplate`] is plain text with `{{placeholder}}`s, typically one of a course's saved
+//! replies from [`Client::get_saved_replies`]. The placeholders are:
+//!
+//! - `{{name}}`: the thread author's name, or "Anonymous"
+//! - `{{first_name}}`: the first word of the author's name
+//! - `{{title}}`: the thread title
+//! - `{{number}}`: the thread number, as shown in the UI
+//! - `{{link}}`: the thread's web address, which becomes a link when posted
+//!
+//! ```no_run
+//! # async fn example(client: edstem::Client) -> edstem::Result<()> {
+//! use edstem::{opts::PostReplyOptions, templates::ReplyTemplate};
+//!
+//! let thread = client.get_thread(1234u64).await?.dissolve().0;
+//! let template = ReplyTemplate::new("Hi {{first_name}}, this is answered in the FAQ.");
+//! template
+//!     .post(&client, &thread, None, PostReplyOptions::default())
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    Client,
+    document::{Block, Document, Inline},
+    model::{
+        thread::{PartialThread, ReplyResponse, Thread},
+        user::ThreadParticipant,
+    },
+    opts::PostReplyOptions,
+};
+
+/// The name used for `{{name}}` when the author is unknown or anonymous.
+pub const ANONYMOUS_NAME: &str = "Anonymous";
+
+fn author_name(author: Option<&ThreadParticipant>, is_anonymous: bool) -> String {
+    match author {
+        Some(author) if !is_anonymous => author.name().clone(),
+        _ => String::from(ANONYMOUS_NAME),
+    }
+}
+
+/// Values for the placeholders in a [`ReplyTemplate`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TemplateContext {
+    /// The thread author's name.
+    pub name: String,
+    /// The thread title.
+    pub title: String,
+    /// The thread number.
+    pub number: u64,
+    /// The thread's web address.
+    pub link: String,
+}
+
+impl TemplateContext {
+    /// Values for replying to `thread`, whose author is `author` if known.
+    pub fn for_thread(
+        client: &Client,
+        thread: &Thread,
+        author: Option<&ThreadParticipant>,
+    ) -> Self {
+        Self {
+            name: author_name(author, *thread.is_anonymous()),
+            title: thread.title().clone(),
+            number: *thread.number(),
+            link: client.thread_url(*thread.course_id(), *thread.id()),
+        }
+    }
+
+    /// Values for replying to `thread` as listed in a course, whose author is `author` if known.
+    pub fn for_partial_thread(
+        client: &Client,
+        thread: &PartialThread,
+        author: Option<&ThreadParticipant>,
+    ) -> Self {
+        Self {
+            name: author_name(author, *thread.is_anonymous()),
+            title: thread.title().clone(),
+            number: *thread.number(),
+            link: client.thread_url(*thread.course_id(), *thread.id()),
+        }
+    }
+
+    fn value(&self, placeholder: &str) -> Option<String> {
+        match placeholder {
+            "name" => Some(self.name.clone()),
+            "first_name" => self.name.split_whitespace().next().map(String::from),
+            "title" => Some(self.title.clone()),
+            "number" => Some(self.number.to_string()),
+            "link" => Some(self.link.clone()),
+            _ => None,
+        }
+    }
+}
+
+/// A reply with `{{placeholder}}`s. See the [module docs](self).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReplyTemplate {
+    text: String,
+}
+
+impl From<String> for ReplyTemplate {
+    fn from(value: String) -> Self {
+        Self { text: value }
+    }
+}
+
+/// Pieces of template text: literal text and placeholder names.
+fn pieces(text: &str) -> impl Iterator<Item = Result<&str, &str>> {
+    let mut rest = text;
+    std::iter::from_fn(move || {
+        if rest.is_empty() {
+            return None;
+        }
+        let Some(start) = rest.find("{{") else {
+            return Some(Ok(std::mem::take(&mut rest)));
+        };
+        if start > 0 {
+            let literal = &rest[..start];
+            rest = &rest[start..];
+            return Some(Ok(literal));
+        }
+        match rest.find("}}") {
+            Some(end) => {
+                let placeholder = rest[2..end].trim();
+                rest = &rest[end + 2..];
+                Some(Err(placeholder))
+            }
+            None => Some(Ok(std::mem::take(&mut rest))),
+        }
+    })
+}
+
+impl ReplyTemplate {
+    /// A template from its text.
+    pub fn new(text: &str) -> Self {
+        Self {
+            text: String::from(text),
+        }
+    }
+
+    /// The template text.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// The names of the placeholders used, in order of appearance.
+    pub fn placeholders(&self) -> Vec<&str> {
+        pieces(&self.text).filter_map(Result::err).collect()
+    }
+
+    /// Fill in every placeholder.
+    ///
+    /// Fails with [`Error::Template`](crate::Error::Template) on a placeholder this module does
+    /// not know, rather than posting it as is.
+    pub fn render(&self, context: &TemplateContext) -> crate::Result<String> {
+        pieces(&self.text)
+            .map(|piece| match piece {
+                Ok(literal) => Ok(String::from(literal)),
+                Err(placeholder) => context.value(placeholder).ok_or_else(|| {
+                    crate::Error::Template(format!("unknown placeholder {{{{{placeholder}}}}}"))
+                }),
+            })
+            .collect()
+    }
+
+    /// Fill in every placeholder and make a post body, with the thread's address as a link.
+    pub fn to_document(&self, context: &TemplateContext) -> crate::Result<Document> {
+        let mut document = Document::from_plain_text(&self.render(context)?);
+        if context.link.is_empty() {
+            return Ok(document);
+        }
+
+        for block in &mut document.blocks {
+            let Block::Paragraph(content) = block else {
+                continue;
+            };
+            *content = std::mem::take(content)
+                .into_iter()
+                .flat_map(|inline| link_inlines(inline, &context.link))
+                .collect();
+        }
+        Ok(document)
+    }
+
+    /// Fill in the template for `thread` and post it as a reply.
+    pub async fn post(
+        &self,
+        client: &Client,
+        thread: &Thread,
+        author: Option<&ThreadParticipant>,
+        options: PostReplyOptions,
+    ) -> crate::Result<ReplyResponse> {
+        let context = TemplateContext::for_thread(client, thread, author);
+        client
+            .post_thread_reply(*thread.id(), &self.to_document(&context)?, options)
+            .await
+    }
+}
+
+/// Split text around occurrences of `link`, making each a hyperlink.
+fn link_inlines(inline: Inline, link: &str) -> Vec<Inline> {
+    let Inline::Text(text) = inline else {
+        return vec![inline];
+    };
+
+    let mut out = Vec::new();
+    let mut rest = text.as_str();
+    while let Some(at) = rest.find(link) {
+        if at > 0 {
+            out.push(Inline::Text(String::from(&rest[..at])));
+        }
+        out.push(Inline::Link {
+            href: String::from(link),
+            content: vec![Inline::Text(String::from(link))],
+        });
+        rest = &rest[at + link.len()..];
+    }
+    if !rest.is_empty() {
+        out.push(Inline::Text(String::from(rest)));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context() -> TemplateContext {
+        TemplateContext {
+            name: String::from("Jane Doe"),
+            title: String::from("Segfault in A1"),
+            number: 42,
+            link: String::from("https://edstem.org/us/courses/1/discussion/7"),
+        }
+    }
+
+    #[test]
+    fn renders_placeholders() {
+        let template =
+            ReplyTemplate::new("Hi {{first_name}} ({{ name }}), re #{{number}} {{title}}.");
+        assert_eq!(
+            template.placeholders(),
+            ["first_name", "name", "number", "title"]
+        );
+        assert_eq!(
+            template.render(&context()).unwrap(),
+            "Hi Jane (Jane Doe), re #42 Segfault in A1."
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_placeholders() {
+        let template = ReplyTemplate::new("Hi {{nickname}}");
+        assert!(matches!(
+            template.render(&context()),
+            Err(crate::Error::Template(message)) if message == "unknown placeholder {{nickname}}"
+        ));
+    }
+
+    #[test]
+    fn leaves_unclosed_braces() {
+        let template = ReplyTemplate::new("a {{b");
+        assert!(template.placeholders().is_empty());
+        assert_eq!(template.render(&context()).unwrap(), "a {{b");
+    }
+
+    #[test]
+    fn anonymous_authors_are_not_named() {
+        let participant: ThreadParticipant = serde_json::from_value(serde_json::json!({
+            "id": 1,
+            "role": "user",
+            "name": "Jane Doe",
+            "avatar": null,
+            "course_role": "student",
+            "tutorials": {},
+        }))
+        .unwrap();
+        assert_eq!(author_name(Some(&participant), false), "Jane Doe");
+        assert_eq!(author_name(Some(&participant), true), ANONYMOUS_NAME);
+        assert_eq!(author_name(None, false), ANONYMOUS_NAME);
+    }
+
+    #[test]
+    fn links_the_thread_address() {
+        let context = context();
+        let document = ReplyTemplate::new("See {{link}} for more.")
+            .to_document(&context)
+            .unwrap();
+        assert_eq!(
+            document.blocks,
+            [Block::Paragraph(vec![
+                Inline::Text(String::from("See ")),
+                Inline::Link {
+                    href: context.link.clone(),
+                    content: vec![Inline::Text(context.link.clone())],
+                },
+                Inline::Text(String::from(" for more.")),
+            ])]
+        );
+    }
+}
@@ -0,0 +1,1023 @@
+//! Model values to seed a [`MockServer`](super::MockServer) with.
+//!
+//! Each fixture fills every field Ed Discussion would send with a plausible default. Fields
+//! without a dedicated option can be set through `overrides`, a JSON object merged over the
+//! generated value before it is deserialized, e.g. `{"is_pinned": true}` for a thread.
+
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value, json};
+
+use crate::{
+    document::Document,
+    model::{
+        challenge::{Challenge, TestCaseResult},
+        chat::{Channel, ChatMessage, DirectConversation},
+        course::{Course, SelfUserCourse},
+        lab::Lab,
+        lesson::{Lesson, LessonModule, LessonProgress, QuizResponse, Slide},
+        notification::Notification,
+        realm::{Realm, RealmUser},
+        thread::{Reply, Thread},
+        user::{CourseMember, SelfUser, ThreadParticipant},
+        workspace::Workspace,
+    },
+};
+
+/// The timestamp fixtures use unless told otherwise.
+pub const DEFAULT_TIMESTAMP: &str = "2025-01-06T09:00:00.000000+00:00";
+
+fn build<T: DeserializeOwned>(mut value: Value, overrides: Map<String, Value>) -> T {
+    if let Value::Object(ref mut map) = value {
+        map.extend(overrides);
+    }
+    serde_json::from_value(value).expect("fixture does not match model")
+}
+
+fn content(body: &str) -> String {
+    Document::from_plain_text(body).to_xml()
+}
+
+/// A course as it appears in [`UserFixture::courses`].
+#[derive(Clone, Debug)]
+pub struct CourseFixture {
+    /// The course ID.
+    pub id: u64,
+    /// The short code, e.g. `"CS 101"`.
+    pub code: String,
+    /// The full name.
+    pub name: String,
+    /// The requesting user's role in the course, e.g. `"student"` or `"admin"`.
+    pub role: String,
+    /// Fields to set on the course itself.
+    pub overrides: Map<String, Value>,
+}
+
+impl Default for CourseFixture {
+    fn default() -> Self {
+        Self {
+            id: 1,
+            code: String::from("CS 101"),
+            name: String::from("Introduction to Programming"),
+            role: String::from("student"),
+            overrides: Map::new(),
+        }
+    }
+}
+
+impl CourseFixture {
+    fn to_value(&self, user_id: u64) -> Value {
+        let mut course = course_value(self.id, &self.code, &self.name);
+        if let Value::Object(ref mut map) = course {
+            map.extend(self.overrides.clone());
+        }
+        json!({
+            "course": course,
+            "role": {
+                "user_id": user_id,
+                "course_id": self.id,
+                "lab_id": null,
+                "role": self.role,
+                "tutorial": null,
+                "digest": true,
+                "settings": {"digest_interval": null, "email_announcements": null},
+                "created_at": DEFAULT_TIMESTAMP,
+                "deleted_at": null,
+            },
+            "lab": null,
+            "last_active": DEFAULT_TIMESTAMP,
+        })
+    }
+
+    /// Build the [`SelfUserCourse`] for a user with ID `user_id`.
+    pub fn build(self, user_id: u64) -> SelfUserCourse {
+        build(self.to_value(user_id), Map::new())
+    }
+}
+
+pub(crate) fn course_value(id: u64, code: &str, name: &str) -> Value {
+    let workspace = json!({
+        "default_type": "",
+        "student_creation_disabled": false,
+        "remote_desktop": false,
+        "remote_app": false,
+        "saturn_override": false,
+        "saturn_default_kernel": "",
+        "disable_student_workspace_upload": false,
+        "extra_paths": "",
+        "settings": {"rstudio_layout": ""},
+    });
+    let discussion = json!({
+        "private": true,
+        "private_threads_only": false,
+        "anonymous_comments": true,
+        "anonymous_comments_override": false,
+        "anonymous": true,
+        "anonymous_to_staff": false,
+        "threads_require_approval": false,
+        "unread_indicator_hidden": false,
+        "deleted": false,
+        "categories": [
+            {"name": "General", "subcategories": [], "thread_template": null},
+        ],
+        "thread_templates_enabled": false,
+        "category_unselected": false,
+        "snippet_langauges": [],
+        "default_snippet_language": "python",
+        "rejection_comment_template": null,
+        "bot_enabled": false,
+        "bot_enabled_v2": false,
+        "bot_name": "",
+        "bot_avatar": "",
+        "full_announcement_emails": false,
+        "no_digests": false,
+        "digest_interval": null,
+        "saved_replies_enabled": false,
+        "saved_replies": [],
+        "sortable_feed": true,
+        "default_feed_sort": "new",
+        "thread_numbers": true,
+        "comment_numbers": true,
+        "tutorial_badge_visible_to_all": false,
+        "tutorial_badge_visible_anon": false,
+        "readonly": false,
+        "show_all_pinned_threads": false,
+        "comment_endorsements": true,
+    });
+    json!({
+        "id": id,
+        "realm_id": 1,
+        "code": code,
+        "name": name,
+        "year": "2025",
+        "session": "Winter",
+        "status": "active",
+        "features": {"analytics": false, "discussion": true},
+        "settings": {
+            "default_page": "discussion",
+            "user_lab_enrollment": false,
+            "lab_user_agent_regex": "",
+            "lockdown_user_agent_regex": "",
+            "access_codes_enabled": false,
+            "access_codes_public": false,
+            "setup_status": "complete",
+            "discussion": discussion,
+            "chat": {
+                "student_dm_student": false,
+                "student_dm_staff": true,
+                "channels_enabled": false,
+            },
+            "lesson": {
+                "quiz_question_auto_submit": false,
+                "karel_slide_enabled": false,
+                "workspace_partition_slide_enabled": false,
+                "autoplay_videos": false,
+                "hide_video_download": false,
+            },
+            "workspace": workspace,
+            "challenge_workspace": workspace,
+            "code_editor": {},
+            "theme": {"logo": "", "background": "", "foreground": ""},
+            "role_labels": {
+                "student": "Student",
+                "mentor": "Mentor",
+                "tutor": "Tutor",
+                "staff": "Staff",
+                "admin": "Admin",
+            },
+        },
+        "created_at": DEFAULT_TIMESTAMP,
+        "is_lab_regex_active": false,
+    })
+}
+
+/// Build a [`Course`], as listed to realm admins, in realm 1.
+pub fn course(id: u64, code: &str, name: &str) -> Course {
+    build(course_value(id, code, name), Map::new())
+}
+
+/// A realm, i.e. an institution.
+#[derive(Clone, Debug)]
+pub struct RealmFixture {
+    /// The realm ID.
+    pub id: u64,
+    /// The name.
+    pub name: String,
+    /// Whether courses can be created.
+    pub allow_course_creation: bool,
+    /// Whether courses can be created for an LMS.
+    pub lti_and_course_creation: bool,
+    /// Whether courses created for an LMS start inactive.
+    pub course_inactive_on_lti_creation: bool,
+    /// Fields to set on the realm.
+    pub overrides: Map<String, Value>,
+}
+
+impl Default for RealmFixture {
+    fn default() -> Self {
+        Self {
+            id: 1,
+            name: String::from("Test University"),
+            allow_course_creation: true,
+            lti_and_course_creation: true,
+            course_inactive_on_lti_creation: false,
+            overrides: Map::new(),
+        }
+    }
+}
+
+impl RealmFixture {
+    /// Build the [`Realm`].
+    pub fn build(self) -> Realm {
+        build(
+            json!({
+                "id": self.id,
+                "name": self.name,
+                "type": "university",
+                "domain": "example.edu",
+                "associated_domains": "",
+                "features": {},
+                "settings": {
+                    "course_inactive_on_lti_creation": self.course_inactive_on_lti_creation,
+                    "allow_course_creation": self.allow_course_creation,
+                    "lti_and_course_creation": self.lti_and_course_creation,
+                    "discuss_shared_category": "",
+                    "theme": {"logo": "", "accent_color": ""},
+                    "sourced_id_as_unique_identifier": false,
+                    "allow_chat": true,
+                    "force_name_update": false,
+                    "realm_admin_capability": {
+                        "discussion": true,
+                        "chat": true,
+                        "workspaces": true,
+                        "lessons": true,
+                    },
+                    "allow_lessons_and_workspaces_enable": true,
+                },
+                "affiliate_realm_id": null,
+            }),
+            self.overrides,
+        )
+    }
+}
+
+/// Build a [`RealmUser`], i.e. an entry of a realm's user list.
+///
+/// `role` is `"user"` or `"admin"`.
+pub fn realm_user(id: u64, realm_id: u64, name: &str, email: &str, role: &str) -> RealmUser {
+    build(
+        json!({
+            "id": id,
+            "realm_id": realm_id,
+            "name": name,
+            "email": email,
+            "role": role,
+            "activated": true,
+            "is_lti": false,
+            "is_sso": false,
+            "created_at": DEFAULT_TIMESTAMP,
+            "last_active": DEFAULT_TIMESTAMP,
+        }),
+        Map::new(),
+    )
+}
+
+/// The user making requests, i.e. the response to
+/// [`Client::get_self_user`](crate::Client::get_self_user).
+#[derive(Clone, Debug)]
+pub struct UserFixture {
+    /// The user ID.
+    pub id: u64,
+    /// The user's display name.
+    pub name: String,
+    /// The user's email address.
+    pub email: String,
+    /// The courses the user is enrolled in.
+    pub courses: Vec<CourseFixture>,
+    /// Fields to set on the `user` object.
+    pub overrides: Map<String, Value>,
+}
+
+impl Default for UserFixture {
+    fn default() -> Self {
+        Self {
+            id: 1,
+            name: String::from("Test User"),
+            email: String::from("test@example.edu"),
+            courses: vec![CourseFixture::default()],
+            overrides: Map::new(),
+        }
+    }
+}
+
+impl UserFixture {
+    /// Build the [`SelfUser`].
+    pub fn build(self) -> SelfUser {
+        let mut user = json!({
+            "id": self.id,
+            "role": "user",
+            "name": self.name,
+            "email": self.email,
+            "username": null,
+            "avatar": null,
+            "features": {},
+            "settings": {
+                "digest_interval": null,
+                "discuss_feed_style": "full",
+                "accessible": false,
+                "locale": "",
+                "theme": "os",
+                "character_key_shortcuts_disabled": false,
+                "set_tz_automatically": true,
+                "tz": "America/Los_Angeles",
+                "reply_via_email": false,
+                "email_announcements": true,
+                "email_watched_threads": true,
+                "email_thread_replies": true,
+                "email_comment_replies": true,
+                "email_mentions": true,
+                "mention_direct_message_digest_interval": "",
+                "channel_digest_interval": "",
+                "allow_password_login": true,
+                "desktop_notifications_enabled": false,
+                "desktop_notifications_scopes": {
+                    "announcement": true,
+                    "thread": false,
+                    "direct_reply": true,
+                    "mention": true,
+                    "chat": true,
+                    "watch": true,
+                },
+                "snooze_end": "0001-01-01T00:00:00Z",
+                "deactivated": false,
+            },
+            "activated": true,
+            "created_at": DEFAULT_TIMESTAMP,
+            "course_role": null,
+            "secondary_emails": [],
+            "has_password": true,
+            "is_lti": false,
+            "is_sso": false,
+            "can_change_name": true,
+            "has_pats": true,
+            "realm_id": null,
+        });
+        if let Value::Object(ref mut map) = user {
+            map.extend(self.overrides);
+        }
+
+        build(
+            json!({
+                "courses": self.courses.iter().map(|c| c.to_value(self.id)).collect::<Vec<_>>(),
+                "push_key": "",
+                "realms": [],
+                "time": DEFAULT_TIMESTAMP,
+                "user": user,
+            }),
+            Map::new(),
+        )
+    }
+}
+
+/// Build a [`ThreadParticipant`], i.e. an entry of `users` alongside a course's threads.
+///
+/// `course_role` is e.g. `"student"` or `"staff"`.
+pub fn participant(id: u64, name: &str, course_role: &str) -> ThreadParticipant {
+    build(
+        json!({
+            "id": id,
+            "role": "user",
+            "name": name,
+            "avatar": null,
+            "course_role": course_role,
+            "tutorials": {},
+        }),
+        Map::new(),
+    )
+}
+
+/// A thread, stored by the mock server with all of its replies.
+#[derive(Clone, Debug)]
+pub struct ThreadFixture {
+    /// The thread ID.
+    pub id: u64,
+    /// The course the thread belongs to.
+    pub course_id: u64,
+    /// The author; 0 if anonymous.
+    pub user_id: u64,
+    /// The user-facing number within the course.
+    pub number: u64,
+    /// `"question"`, `"post"` or `"announcement"`.
+    pub thread_type: String,
+    /// The title.
+    pub title: String,
+    /// The body as plain text; blank lines separate paragraphs.
+    pub body: String,
+    /// The top-level category.
+    pub category: String,
+    /// When the thread was posted.
+    pub created_at: String,
+    /// Fields to set on the thread.
+    pub overrides: Map<String, Value>,
+}
+
+impl Default for ThreadFixture {
+    fn default() -> Self {
+        Self {
+            id: 1,
+            course_id: 1,
+            user_id: 1,
+            number: 1,
+            thread_type: String::from("question"),
+            title: String::from("Test thread"),
+            body: String::from("Test thread body."),
+            category: String::from("General"),
+            created_at: String::from(DEFAULT_TIMESTAMP),
+            overrides: Map::new(),
+        }
+    }
+}
+
+impl ThreadFixture {
+    /// Build the [`Thread`], with no replies.
+    pub fn build(self) -> Thread {
+        build(
+            json!({
+                "id": self.id,
+                "user_id": self.user_id,
+                "course_id": self.course_id,
+                "original_id": null,
+                "editor_id": null,
+                "accepted_id": null,
+                "duplicate_id": null,
+                "number": self.number,
+                "type": self.thread_type,
+                "title": self.title,
+                "content": content(&self.body),
+                "document": self.body,
+                "category": self.category,
+                "subcategory": "",
+                "subsubcategory": "",
+                "flag_count": 0,
+                "star_count": 0,
+                "view_count": 0,
+                "unique_view_count": 0,
+                "vote_count": 0,
+                "reply_count": 0,
+                "unresolved_count": 0,
+                "is_locked": false,
+                "is_pinned": false,
+                "is_private": false,
+                "is_endorsed": false,
+                "is_student_answered": false,
+                "is_staff_answered": false,
+                "is_archived": false,
+                "is_anonymous": false,
+                "is_megathread": false,
+                "anonymous_comments": false,
+                "approved_status": "approved",
+                "created_at": self.created_at,
+                "updated_at": null,
+                "deleted_at": null,
+                "pinned_at": null,
+                "anonymous_id": 0,
+                "vote": 0,
+                "is_seen": false,
+                "is_starred": false,
+                "is_watched": null,
+                "glanced_at": null,
+                "new_reply_count": 0,
+                "duplicate_title": null,
+                "answers": [],
+                "comments": [],
+            }),
+            self.overrides,
+        )
+    }
+}
+
+/// A comment or answer, to be added to a thread on a mock server.
+#[derive(Clone, Debug)]
+pub struct ReplyFixture {
+    /// The reply ID.
+    pub id: u64,
+    /// The thread replied to.
+    pub thread_id: u64,
+    /// The course of that thread.
+    pub course_id: u64,
+    /// The author; 0 if anonymous.
+    pub user_id: u64,
+    /// `"comment"` or `"answer"`.
+    pub reply_type: String,
+    /// The body as plain text; blank lines separate paragraphs.
+    pub body: String,
+    /// When the reply was posted.
+    pub created_at: String,
+    /// Fields to set on the reply.
+    pub overrides: Map<String, Value>,
+}
+
+impl Default for ReplyFixture {
+    fn default() -> Self {
+        Self {
+            id: 1,
+            thread_id: 1,
+            course_id: 1,
+            user_id: 1,
+            reply_type: String::from("comment"),
+            body: String::from("Test reply."),
+            created_at: String::from(DEFAULT_TIMESTAMP),
+            overrides: Map::new(),
+        }
+    }
+}
+
+impl ReplyFixture {
+    /// Build the [`Reply`], with no nested comments.
+    pub fn build(self) -> Reply {
+        build(self.to_value(), Map::new())
+    }
+
+    pub(crate) fn to_value(&self) -> Value {
+        let mut value = json!({
+            "id": self.id,
+            "user_id": self.user_id,
+            "course_id": self.course_id,
+            "thread_id": self.thread_id,
+            "original_id": null,
+            "parent_id": null,
+            "editor_id": null,
+            "number": 0,
+            "type": self.reply_type,
+            "kind": "normal",
+            "content": content(&self.body),
+            "document": self.body,
+            "flag_count": 0,
+            "vote_count": 0,
+            "is_endorsed": false,
+            "is_anonymous": false,
+            "is_private": false,
+            "is_resolved": false,
+            "created_at": self.created_at,
+            "updated_at": null,
+            "deleted_at": null,
+            "anonymous_id": 0,
+            "vote": 0,
+            "comments": [],
+        });
+        if let Value::Object(ref mut map) = value {
+            map.extend(self.overrides.clone());
+        }
+        value
+    }
+}
+
+/// Build a chat [`Channel`] in a course.
+pub fn channel(id: u64, course_id: u64, name: &str) -> Channel {
+    build(
+        json!({
+            "id": id,
+            "course_id": course_id,
+            "name": name,
+            "description": "",
+            "created_at": DEFAULT_TIMESTAMP,
+            "last_message_at": null,
+        }),
+        Map::new(),
+    )
+}
+
+/// Build a [`DirectConversation`] between `user_ids` in a course.
+pub fn direct_conversation(id: u64, course_id: u64, user_ids: &[u64]) -> DirectConversation {
+    build(
+        json!({
+            "id": id,
+            "course_id": course_id,
+            "user_ids": user_ids,
+            "created_at": DEFAULT_TIMESTAMP,
+            "last_message_at": null,
+        }),
+        Map::new(),
+    )
+}
+
+/// A message, to be added to a channel or direct conversation on a mock server.
+#[derive(Clone, Debug)]
+pub struct ChatMessageFixture {
+    /// The message ID.
+    pub id: u64,
+    /// The channel or direct conversation.
+    pub channel_id: u64,
+    /// The author.
+    pub user_id: u64,
+    /// The body as plain text; blank lines separate paragraphs.
+    pub body: String,
+    /// When the message was sent.
+    pub created_at: String,
+    /// Fields to set on the message.
+    pub overrides: Map<String, Value>,
+}
+
+impl Default for ChatMessageFixture {
+    fn default() -> Self {
+        Self {
+            id: 1,
+            channel_id: 1,
+            user_id: 1,
+            body: String::from("Test message."),
+            created_at: String::from(DEFAULT_TIMESTAMP),
+            overrides: Map::new(),
+        }
+    }
+}
+
+impl ChatMessageFixture {
+    /// Build the [`ChatMessage`].
+    pub fn build(self) -> ChatMessage {
+        build(self.to_value(), Map::new())
+    }
+
+    pub(crate) fn to_value(&self) -> Value {
+        let mut value = json!({
+            "id": self.id,
+            "channel_id": self.channel_id,
+            "user_id": self.user_id,
+            "content": content(&self.body),
+            "document": self.body,
+            "created_at": self.created_at,
+            "updated_at": null,
+            "deleted_at": null,
+        });
+        if let Value::Object(ref mut map) = value {
+            map.extend(self.overrides.clone());
+        }
+        value
+    }
+}
+
+/// Build a [`LessonModule`] in a course.
+pub fn lesson_module(id: u64, course_id: u64, name: &str) -> LessonModule {
+    build(
+        json!({
+            "id": id,
+            "course_id": course_id,
+            "name": name,
+            "created_at": DEFAULT_TIMESTAMP,
+        }),
+        Map::new(),
+    )
+}
+
+/// A lesson, stored by the mock server with all of its slides.
+#[derive(Clone, Debug)]
+pub struct LessonFixture {
+    /// The lesson ID.
+    pub id: u64,
+    /// The course the lesson belongs to.
+    pub course_id: u64,
+    /// The module the lesson is in, if any.
+    pub module_id: Option<u64>,
+    /// The title.
+    pub title: String,
+    /// The position within its module.
+    pub index: u64,
+    /// Fields to set on the lesson.
+    pub overrides: Map<String, Value>,
+}
+
+impl Default for LessonFixture {
+    fn default() -> Self {
+        Self {
+            id: 1,
+            course_id: 1,
+            module_id: None,
+            title: String::from("Test lesson"),
+            index: 1,
+            overrides: Map::new(),
+        }
+    }
+}
+
+impl LessonFixture {
+    /// Build the [`Lesson`], with no slides.
+    pub fn build(self) -> Lesson {
+        build(
+            json!({
+                "id": self.id,
+                "course_id": self.course_id,
+                "module_id": self.module_id,
+                "user_id": 1,
+                "title": self.title,
+                "index": self.index,
+                "state": "active",
+                "is_hidden": false,
+                "available_at": null,
+                "due_at": null,
+                "created_at": DEFAULT_TIMESTAMP,
+                "updated_at": null,
+                "slides": [],
+            }),
+            self.overrides,
+        )
+    }
+}
+
+/// A slide, to be added to a lesson on a mock server.
+#[derive(Clone, Debug)]
+pub struct SlideFixture {
+    /// The slide ID.
+    pub id: u64,
+    /// The lesson the slide is in.
+    pub lesson_id: u64,
+    /// The course of that lesson.
+    pub course_id: u64,
+    /// `"document"`, `"quiz"`, `"code"`, `"video"`, etc.
+    pub slide_type: String,
+    /// The title.
+    pub title: String,
+    /// The position in the lesson.
+    pub index: u64,
+    /// The content as plain text; blank lines separate paragraphs.
+    pub body: String,
+    /// Fields to set on the slide.
+    pub overrides: Map<String, Value>,
+}
+
+impl Default for SlideFixture {
+    fn default() -> Self {
+        Self {
+            id: 1,
+            lesson_id: 1,
+            course_id: 1,
+            slide_type: String::from("document"),
+            title: String::from("Test slide"),
+            index: 1,
+            body: String::from("Test slide body."),
+            overrides: Map::new(),
+        }
+    }
+}
+
+impl SlideFixture {
+    /// Build the [`Slide`].
+    pub fn build(self) -> Slide {
+        build(
+            json!({
+                "id": self.id,
+                "lesson_id": self.lesson_id,
+                "course_id": self.course_id,
+                "type": self.slide_type,
+                "title": self.title,
+                "index": self.index,
+                "content": content(&self.body),
+                "is_hidden": false,
+                "created_at": DEFAULT_TIMESTAMP,
+            }),
+            self.overrides,
+        )
+    }
+}
+
+/// Build a [`Lab`] in a course, run by the staff in `tutor_ids`.
+pub fn lab(id: u64, course_id: u64, name: &str, tutor_ids: &[u64]) -> Lab {
+    build(
+        json!({
+            "id": id,
+            "course_id": course_id,
+            "name": name,
+            "location": null,
+            "tutor_ids": tutor_ids,
+            "created_at": DEFAULT_TIMESTAMP,
+        }),
+        Map::new(),
+    )
+}
+
+/// Build a [`CourseMember`], i.e. an entry of a course's roster.
+///
+/// `course_role` is e.g. `"student"` or `"staff"`.
+pub fn course_member(
+    id: u64,
+    course_id: u64,
+    name: &str,
+    email: &str,
+    course_role: &str,
+) -> CourseMember {
+    build(
+        json!({
+            "id": id,
+            "course_id": course_id,
+            "name": name,
+            "email": email,
+            "course_role": course_role,
+            "lab_id": null,
+            "created_at": DEFAULT_TIMESTAMP,
+        }),
+        Map::new(),
+    )
+}
+
+/// Build a user's [`LessonProgress`] through a lesson of `total_slides` slides.
+///
+/// `status` is `"not_started"`, `"in_progress"` or `"completed"`.
+pub fn lesson_progress(
+    user_id: u64,
+    lesson_id: u64,
+    status: &str,
+    completed_slides: u64,
+    total_slides: u64,
+) -> LessonProgress {
+    let completed = status == "completed";
+    build(
+        json!({
+            "user_id": user_id,
+            "lesson_id": lesson_id,
+            "status": status,
+            "completed_slides": completed_slides,
+            "total_slides": total_slides,
+            "first_viewed_at": (status != "not_started").then_some(DEFAULT_TIMESTAMP),
+            "completed_at": completed.then_some(DEFAULT_TIMESTAMP),
+        }),
+        Map::new(),
+    )
+}
+
+/// Build a user's marked [`QuizResponse`] to a question on slide `slide_id` of a lesson.
+pub fn quiz_response(
+    user_id: u64,
+    lesson_id: u64,
+    slide_id: u64,
+    question_id: u64,
+    score: f64,
+    max_score: f64,
+) -> QuizResponse {
+    build(
+        json!({
+            "user_id": user_id,
+            "lesson_id": lesson_id,
+            "slide_id": slide_id,
+            "question_id": question_id,
+            "response": [0],
+            "is_correct": score >= max_score,
+            "score": score,
+            "max_score": max_score,
+            "submitted_at": DEFAULT_TIMESTAMP,
+        }),
+        Map::new(),
+    )
+}
+
+/// A code challenge, to be served by a mock server.
+#[derive(Clone, Debug)]
+pub struct ChallengeFixture {
+    /// The challenge ID.
+    pub id: u64,
+    /// The course the challenge is in.
+    pub course_id: u64,
+    /// The title.
+    pub title: String,
+    /// The instructions as plain text; blank lines separate paragraphs.
+    pub body: String,
+    /// Scaffold files, as `(path, content)`.
+    pub files: Vec<(String, String)>,
+    /// Fields to set on the challenge.
+    pub overrides: Map<String, Value>,
+}
+
+impl Default for ChallengeFixture {
+    fn default() -> Self {
+        Self {
+            id: 1,
+            course_id: 1,
+            title: String::from("Test challenge"),
+            body: String::from("Print hello."),
+            files: vec![(String::from("main.py"), String::new())],
+            overrides: Map::new(),
+        }
+    }
+}
+
+impl ChallengeFixture {
+    /// Build the [`Challenge`].
+    pub fn build(self) -> Challenge {
+        build(
+            json!({
+                "id": self.id,
+                "course_id": self.course_id,
+                "lesson_id": null,
+                "slide_id": null,
+                "title": self.title,
+                "content": content(&self.body),
+                "files": self
+                    .files
+                    .iter()
+                    .map(|(path, content)| json!({"path": path, "content": content}))
+                    .collect::<Vec<_>>(),
+                "created_at": DEFAULT_TIMESTAMP,
+            }),
+            self.overrides,
+        )
+    }
+}
+
+/// Build a [`TestCaseResult`] worth one mark, which passed if `actual` output is `expected`.
+pub fn test_case_result(name: &str, expected: &str, actual: &str) -> TestCaseResult {
+    let passed = expected == actual;
+    build(
+        json!({
+            "name": name,
+            "passed": passed,
+            "is_hidden": false,
+            "score": if passed { 1.0 } else { 0.0 },
+            "max_score": 1.0,
+            "expected_output": expected,
+            "actual_output": actual,
+            "feedback": null,
+        }),
+        Map::new(),
+    )
+}
+
+/// Build a [`Workspace`] owned by `user_id`.
+pub fn workspace(id: u64, course_id: u64, user_id: u64, name: &str) -> Workspace {
+    build(
+        json!({
+            "id": id,
+            "course_id": course_id,
+            "user_id": user_id,
+            "name": name,
+            "type": "standard",
+            "challenge_id": null,
+            "created_at": DEFAULT_TIMESTAMP,
+            "updated_at": null,
+        }),
+        Map::new(),
+    )
+}
+
+/// An entry in a workspace's file tree: a file with `content`, or a directory.
+pub(crate) fn workspace_file(path: &str, content: Option<&[u8]>) -> Value {
+    json!({
+        "path": path,
+        "type": if content.is_some() { "file" } else { "directory" },
+        "size": content.map_or(0, <[u8]>::len),
+        "modified_at": DEFAULT_TIMESTAMP,
+    })
+}
+
+/// A notification for the user making requests.
+#[derive(Clone, Debug)]
+pub struct NotificationFixture {
+    /// The notification ID.
+    pub id: u64,
+    /// `"announcement"`, `"thread"`, `"direct_reply"`, `"mention"`, `"chat"` or `"watch"`.
+    pub notification_type: String,
+    /// The course, if any.
+    pub course_id: Option<u64>,
+    /// The thread, if any.
+    pub thread_id: Option<u64>,
+    /// The title, e.g. of the thread.
+    pub title: String,
+    /// The excerpt.
+    pub body: String,
+    /// Whether it has been read.
+    pub read: bool,
+    /// Fields to set on the notification.
+    pub overrides: Map<String, Value>,
+}
+
+impl Default for NotificationFixture {
+    fn default() -> Self {
+        Self {
+            id: 1,
+            notification_type: String::from("direct_reply"),
+            course_id: Some(1),
+            thread_id: Some(1),
+            title: String::from("Test thread"),
+            body: String::from("Test reply body."),
+            read: false,
+            overrides: Map::new(),
+        }
+    }
+}
+
+impl NotificationFixture {
+    /// Build the [`Notification`].
+    pub fn build(self) -> Notification {
+        build(
+            json!({
+                "id": self.id,
+                "type": self.notification_type,
+                "course_id": self.course_id,
+                "thread_id": self.thread_id,
+                "comment_id": null,
+                "channel_id": null,
+                "actor_id": null,
+                "title": self.title,
+                "body": self.body,
+                "read": self.read,
+                "created_at": DEFAULT_TIMESTAMP,
+            }),
+            self.overrides,
+        )
+    }
+}
@@ -0,0 +1,1481 @@
+//! A local stand-in for Ed Discussion, for testing code built on [`Client`] without network access.
+//!
+//! [`MockServer`] serves the endpoints [`Client`] uses from data seeded with [`fixtures`], records
+//! every request it receives, and can be told to fail requests with the errors Ed Discussion
+//! produces. Point a client at it with [`MockServer::client`] or [`MockServer::base_url`].
+//!
+//! ```no_run
+//! # async fn example() -> edstem::Result<()> {
+//! use edstem::testing::{MockServer, fixtures::{ThreadFixture, UserFixture}};
+//!
+//! let server = MockServer::start().await?;
+//! server.set_user(UserFixture::default().build());
+//! server.add_thread(ThreadFixture::default().build());
+//!
+//! let threads = server.client().get_course_threads(1u64, None).await?;
+//! assert_eq!(threads.threads().len(), 1);
+//! assert_eq!(server.requests()[0].path, "/api/courses/1/threads");
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{
+    collections::{BTreeMap, VecDeque},
+    convert::Infallible,
+    net::SocketAddr,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use http_body_util::{BodyExt, Full};
+use hyper::{
+    body::{Bytes, Incoming},
+    server::conn::http1,
+    service::service_fn,
+};
+use hyper_util::rt::TokioIo;
+use serde::Serialize;
+use serde_json::{Value, json};
+use tokio::{net::TcpListener, task::JoinHandle};
+
+use crate::{
+    Client, ClientOptions,
+    model::{
+        challenge::{Challenge, TestCaseResult},
+        chat::{Channel, ChatMessage, DirectConversation},
+        course::Course,
+        lab::Lab,
+        lesson::{Lesson, LessonModule, LessonProgress, QuizResponse, Slide},
+        notification::Notification,
+        realm::{Realm, RealmUser},
+        thread::{Reply, Thread},
+        user::{CourseMember, SelfUser, ThreadParticipant},
+        workspace::Workspace,
+    },
+};
+
+pub mod fixtures;
+#[cfg(test)]
+mod tests;
+
+/// The token [`MockServer::client`] authenticates with.
+pub const MOCK_TOKEN: &str = "mock-token";
+
+/// A request received by a [`MockServer`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RecordedRequest {
+    /// The HTTP method, e.g. `"GET"`.
+    pub method: String,
+    /// The path, without the query string.
+    pub path: String,
+    /// Decoded query parameters in order.
+    pub query: Vec<(String, String)>,
+    /// The `Authorization` header, if sent.
+    pub authorization: Option<String>,
+    /// The raw request body.
+    pub body: Vec<u8>,
+}
+
+impl RecordedRequest {
+    /// The first value of a query parameter, if present.
+    pub fn query_param(&self, key: &str) -> Option<&str> {
+        self.query
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// The body parsed as JSON, if it is valid JSON.
+    pub fn json(&self) -> Option<Value> {
+        serde_json::from_slice(&self.body).ok()
+    }
+}
+
+/// A failure for a [`MockServer`] to respond with instead of serving a request.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Failure {
+    /// 401, as for a bad or revoked token.
+    Unauthorized,
+    /// 404, as for a missing or inaccessible resource.
+    NotFound,
+    /// 429 with a `Retry-After` header, in seconds.
+    RateLimited {
+        /// The value of the `Retry-After` header.
+        retry_after: u64,
+    },
+    /// 200 with a body that is not valid JSON.
+    MalformedJson,
+    /// Any other status, with an Ed-style JSON error body.
+    Status(u16),
+}
+
+struct MockResponse {
+    status: u16,
+    headers: Vec<(&'static str, String)>,
+    body: Vec<u8>,
+}
+
+impl MockResponse {
+    fn json(status: u16, value: &impl Serialize) -> Self {
+        Self {
+            status,
+            headers: vec![("content-type", String::from("application/json"))],
+            body: serde_json::to_vec(value).expect("values always serialize"),
+        }
+    }
+
+    fn bytes(body: Vec<u8>) -> Self {
+        Self {
+            status: 200,
+            headers: vec![("content-type", String::from("application/octet-stream"))],
+            body,
+        }
+    }
+
+    fn ok(value: &impl Serialize) -> Self {
+        Self::json(200, value)
+    }
+
+    fn error(status: u16, code: &str, message: &str) -> Self {
+        Self::json(status, &json!({"code": code, "message": message}))
+    }
+
+    fn not_found() -> Self {
+        Self::error(404, "not_found", "Not found")
+    }
+
+    fn bad_request(message: &str) -> Self {
+        Self::error(400, "bad_request", message)
+    }
+
+    fn from_failure(failure: &Failure) -> Self {
+        match failure {
+            Failure::Unauthorized => Self::error(401, "unauthorized", "Unauthorized"),
+            Failure::NotFound => Self::not_found(),
+            Failure::RateLimited { retry_after } => {
+                let mut response = Self::error(429, "rate_limited", "Too many requests");
+                response
+                    .headers
+                    .push(("retry-after", retry_after.to_string()));
+                response
+            }
+            Failure::MalformedJson => Self {
+                status: 200,
+                headers: vec![("content-type", String::from("application/json"))],
+                body: b"{\"truncated\": ".to_vec(),
+            },
+            Failure::Status(status) => Self::error(*status, "error", "Injected failure"),
+        }
+    }
+}
+
+#[derive(Default)]
+struct State {
+    token: Option<String>,
+    user: Option<Value>,
+    participants: Vec<Value>,
+    /// full threads, including replies, by ID
+    threads: BTreeMap<u64, Value>,
+    /// chat channels and direct conversations by ID
+    channels: BTreeMap<u64, Value>,
+    /// chat messages by channel ID, oldest first
+    messages: BTreeMap<u64, Vec<Value>>,
+    lesson_modules: BTreeMap<u64, Value>,
+    /// full lessons, including slides, by ID
+    lessons: BTreeMap<u64, Value>,
+    /// course rosters, as seen by staff
+    members: Vec<Value>,
+    labs: BTreeMap<u64, Value>,
+    realms: BTreeMap<u64, Value>,
+    /// every course in any realm by ID, besides the seeded user's own
+    realm_courses: BTreeMap<u64, Value>,
+    realm_users: Vec<Value>,
+    /// the seeded user's notifications by ID
+    notifications: BTreeMap<u64, Value>,
+    lesson_progress: Vec<Value>,
+    quiz_responses: Vec<Value>,
+    challenges: BTreeMap<u64, Value>,
+    /// the results every submission to a challenge is marked with, by challenge ID
+    challenge_results: BTreeMap<u64, Vec<Value>>,
+    /// challenge submissions by ID, each advancing a step through marking whenever fetched
+    submissions: BTreeMap<u64, Value>,
+    workspaces: BTreeMap<u64, Value>,
+    /// file contents by workspace ID, then path; directories are implied by paths
+    workspace_files: BTreeMap<u64, BTreeMap<String, Vec<u8>>>,
+    requests: Vec<RecordedRequest>,
+    fail_next: VecDeque<Failure>,
+    fail_matching: Vec<(String, String, Failure)>,
+    next_id: u64,
+}
+
+fn to_value(value: &impl Serialize) -> Value {
+    serde_json::to_value(value).expect("models always serialize")
+}
+
+fn find_reply(replies: &mut [Value], id: u64) -> Option<&mut Value> {
+    for reply in replies {
+        if reply["id"] == id {
+            return Some(reply);
+        }
+        if let Some(Value::Array(comments)) = reply.get_mut("comments")
+            && let Some(found) = find_reply(comments, id)
+        {
+            return Some(found);
+        }
+    }
+    None
+}
+
+fn contains_reply(replies: &[Value], id: u64) -> bool {
+    replies.iter().any(|reply| {
+        reply["id"] == id
+            || reply["comments"]
+                .as_array()
+                .is_some_and(|c| contains_reply(c, id))
+    })
+}
+
+fn reply_list(thread: &Value, parent: u64) -> Option<&'static str> {
+    ["answers", "comments"].into_iter().find(|key| {
+        thread[*key]
+            .as_array()
+            .is_some_and(|r| contains_reply(r, parent))
+    })
+}
+
+/// Apply a partial update, recursing into objects present in both.
+fn merge(target: &mut Value, patch: Value) {
+    match (target, patch) {
+        (Value::Object(target), Value::Object(patch)) => {
+            for (key, value) in patch {
+                match target.get_mut(&key) {
+                    Some(existing) if existing.is_object() => merge(existing, value),
+                    _ => {
+                        target.insert(key, value);
+                    }
+                }
+            }
+        }
+        (target, patch) => *target = patch,
+    }
+}
+
+fn partial_thread(thread: &Value) -> Value {
+    let mut partial = thread.clone();
+    if let Value::Object(ref mut map) = partial {
+        let answered = map
+            .remove("answers")
+            .is_some_and(|a| a.as_array().is_some_and(|a| !a.is_empty()));
+        map.remove("comments");
+        map.insert(String::from("is_answered"), Value::Bool(answered));
+        map.entry("user").or_insert(Value::Null);
+    }
+    partial
+}
+
+impl State {
+    fn self_user_id(&self) -> u64 {
+        self.user
+            .as_ref()
+            .and_then(|u| u["user"]["id"].as_u64())
+            .unwrap_or(1)
+    }
+
+    fn next_id(&mut self) -> u64 {
+        self.next_id += 1;
+        self.next_id
+    }
+
+    fn reply_mut(&mut self, id: u64) -> Option<&mut Value> {
+        self.threads.values_mut().find_map(|thread| {
+            let key = reply_list(thread, id)?;
+            match &mut thread[key] {
+                Value::Array(replies) => find_reply(replies, id),
+                _ => None,
+            }
+        })
+    }
+
+    fn thread_by_number(&self, course_id: u64, number: u64) -> Option<&Value> {
+        self.threads
+            .values()
+            .find(|t| t["course_id"] == course_id && t["number"] == number)
+    }
+
+    fn respond(&mut self, request: &RecordedRequest) -> MockResponse {
+        if let Some(token) = &self.token
+            && request.authorization.as_deref() != Some(&format!("Bearer {token}"))
+        {
+            return MockResponse::from_failure(&Failure::Unauthorized);
+        }
+
+        if let Some((_, _, failure)) = self
+            .fail_matching
+            .iter()
+            .find(|(method, path, _)| *method == request.method && *path == request.path)
+        {
+            return MockResponse::from_failure(failure);
+        }
+        if let Some(failure) = self.fail_next.pop_front() {
+            return MockResponse::from_failure(&failure);
+        }
+
+        self.route(request)
+    }
+
+    fn route(&mut self, request: &RecordedRequest) -> MockResponse {
+        let segments: Vec<&str> = request.path.trim_matches('/').split('/').collect();
+        let id = |s: &str| s.parse::<u64>().ok();
+
+        match (request.method.as_str(), segments.as_slice()) {
+            ("GET", ["api", "user"]) => match &self.user {
+                Some(user) => MockResponse::ok(user),
+                None => MockResponse::error(401, "unauthorized", "No user seeded"),
+            },
+            ("PUT", ["api", "user", "settings"]) => self.update_user_settings(request),
+            ("PUT", ["api", "courses", course, "role", "settings"]) => match id(course) {
+                Some(course) => self.update_course_role_settings(course, request),
+                None => MockResponse::not_found(),
+            },
+            ("GET", ["api", "notifications"]) => self.notifications(request),
+            ("POST", ["api", "notifications", "read"]) => {
+                let Some(body) = request.json() else {
+                    return MockResponse::bad_request("expected ids");
+                };
+                let ids: Vec<&Value> = body["ids"].as_array().into_iter().flatten().collect();
+                for notification in self.notifications.values_mut() {
+                    if body["all"] == true || ids.contains(&&notification["id"]) {
+                        notification["read"] = json!(true);
+                    }
+                }
+                MockResponse::ok(&json!({}))
+            }
+            ("DELETE", ["api", "notifications"]) => {
+                self.notifications.clear();
+                MockResponse::ok(&json!({}))
+            }
+            ("GET", ["api", "users", user, "activity"]) => match id(user) {
+                Some(user) => self.user_activity(user, request),
+                None => MockResponse::not_found(),
+            },
+            ("GET", ["api", "realms", realm]) => {
+                match id(realm).and_then(|r| self.realms.get(&r)) {
+                    Some(realm) => MockResponse::ok(&json!({"realm": realm})),
+                    None => MockResponse::not_found(),
+                }
+            }
+            ("PUT", ["api", "realms", realm, "settings"]) => {
+                let Some(realm) = id(realm).and_then(|r| self.realms.get_mut(&r)) else {
+                    return MockResponse::not_found();
+                };
+                let Some(patch) = request.json().map(|b| b["settings"].clone()) else {
+                    return MockResponse::bad_request("expected settings");
+                };
+                merge(&mut realm["settings"], patch);
+                MockResponse::ok(&json!({"realm": realm}))
+            }
+            ("GET", ["api", "realms", realm, "courses"]) => {
+                match id(realm).filter(|r| self.realms.contains_key(r)) {
+                    Some(realm) => MockResponse::ok(&json!({"courses": self.realm_courses(realm)})),
+                    None => MockResponse::not_found(),
+                }
+            }
+            ("POST", ["api", "realms", realm, "courses"]) => {
+                match id(realm).filter(|r| self.realms.contains_key(r)) {
+                    Some(realm) => self.create_realm_course(realm, request),
+                    None => MockResponse::not_found(),
+                }
+            }
+            ("GET", ["api", "realms", realm, "users"]) => {
+                match id(realm).filter(|r| self.realms.contains_key(r)) {
+                    Some(realm) => MockResponse::ok(&json!({
+                        "users": self
+                            .realm_users
+                            .iter()
+                            .filter(|u| u["realm_id"] == realm)
+                            .collect::<Vec<_>>(),
+                    })),
+                    None => MockResponse::not_found(),
+                }
+            }
+            ("GET", ["api", "courses", course]) => {
+                match id(course).and_then(|c| self.course_mut(c)) {
+                    Some(course) => MockResponse::ok(&json!({"course": course})),
+                    None => MockResponse::not_found(),
+                }
+            }
+            ("PUT", ["api", "courses", course, "settings"]) => match id(course) {
+                Some(course) => self.update_course_settings(course, request),
+                None => MockResponse::not_found(),
+            },
+            ("GET", ["api", "courses", course, "threads"]) => match id(course) {
+                Some(course) => self.course_threads(course, request),
+                None => MockResponse::not_found(),
+            },
+            ("GET", ["api", "courses", course, "threads", number]) => {
+                match (id(course), id(number)) {
+                    (Some(course), Some(number)) => match self.thread_by_number(course, number) {
+                        Some(thread) => {
+                            MockResponse::ok(&json!({"thread": thread, "users": self.participants}))
+                        }
+                        None => MockResponse::not_found(),
+                    },
+                    _ => MockResponse::not_found(),
+                }
+            }
+            ("GET", ["api", "threads", thread]) => {
+                match id(thread).and_then(|t| self.threads.get(&t)) {
+                    Some(thread) => {
+                        MockResponse::ok(&json!({"thread": thread, "users": self.participants}))
+                    }
+                    None => MockResponse::not_found(),
+                }
+            }
+            ("POST", ["api", "threads", thread, action @ ("approve" | "reject")]) => {
+                let status = match *action {
+                    "approve" => "approved",
+                    _ => "rejected",
+                };
+                match id(thread).and_then(|t| self.threads.get_mut(&t)) {
+                    Some(thread) => {
+                        thread["approved_status"] = json!(status);
+                        MockResponse::ok(&json!({}))
+                    }
+                    None => MockResponse::not_found(),
+                }
+            }
+            (
+                "POST",
+                [
+                    "api",
+                    kind @ ("threads" | "comments"),
+                    id_,
+                    action @ ("flag" | "unflag"),
+                ],
+            ) => {
+                let Some(target) = id(id_).and_then(|id| match *kind {
+                    "threads" => self.threads.get_mut(&id),
+                    _ => self.reply_mut(id),
+                }) else {
+                    return MockResponse::not_found();
+                };
+                let count = match *action {
+                    "flag" => target["flag_count"].as_u64().unwrap_or_default() + 1,
+                    _ => 0,
+                };
+                target["flag_count"] = json!(count);
+                MockResponse::ok(&json!({}))
+            }
+            (
+                "GET",
+                [
+                    "api",
+                    "courses",
+                    course,
+                    "chat",
+                    kind @ ("channels" | "direct"),
+                ],
+            ) => {
+                let Some(course) = id(course) else {
+                    return MockResponse::not_found();
+                };
+                let self_id = self.self_user_id();
+                let in_course = self.channels.values().filter(|c| c["course_id"] == course);
+                match *kind {
+                    "channels" => MockResponse::ok(&json!({
+                        "channels": in_course.filter(|c| c.get("user_ids").is_none()).collect::<Vec<_>>(),
+                    })),
+                    _ => MockResponse::ok(&json!({
+                        "conversations": in_course
+                            .filter(|c| {
+                                c["user_ids"].as_array().is_some_and(|u| u.contains(&json!(self_id)))
+                            })
+                            .collect::<Vec<_>>(),
+                    })),
+                }
+            }
+            ("GET", ["api", "chat", "channels", channel, "messages"]) => {
+                match id(channel).filter(|c| self.channels.contains_key(c)) {
+                    Some(channel) => self.chat_messages(channel, request),
+                    None => MockResponse::not_found(),
+                }
+            }
+            ("POST", ["api", "chat", "channels", channel, "messages"]) => {
+                match id(channel).filter(|c| self.channels.contains_key(c)) {
+                    Some(channel) => self.send_chat_message(channel, request),
+                    None => MockResponse::not_found(),
+                }
+            }
+            ("GET", ["api", "courses", course, "lessons"]) => match id(course) {
+                Some(course) => MockResponse::ok(&json!({
+                    "lessons": self
+                        .lessons
+                        .values()
+                        .filter(|l| l["course_id"] == course)
+                        .map(|l| {
+                            let mut listed = l.clone();
+                            listed["slides"] = Value::Null;
+                            listed
+                        })
+                        .collect::<Vec<_>>(),
+                    "modules": self
+                        .lesson_modules
+                        .values()
+                        .filter(|m| m["course_id"] == course)
+                        .collect::<Vec<_>>(),
+                })),
+                None => MockResponse::not_found(),
+            },
+            ("GET", ["api", "courses", course, "admin", "users"]) => match id(course) {
+                Some(course) => MockResponse::ok(&json!({
+                    "users": self
+                        .members
+                        .iter()
+                        .filter(|m| m["course_id"] == course)
+                        .collect::<Vec<_>>(),
+                })),
+                None => MockResponse::not_found(),
+            },
+            ("GET", ["api", "courses", course, "labs"]) => match id(course) {
+                Some(course) => MockResponse::ok(&json!({
+                    "labs": self
+                        .labs
+                        .values()
+                        .filter(|l| l["course_id"] == course)
+                        .collect::<Vec<_>>(),
+                })),
+                None => MockResponse::not_found(),
+            },
+            ("GET", ["api", "labs", lab, "users"]) => {
+                match id(lab).filter(|l| self.labs.contains_key(l)) {
+                    Some(lab) => MockResponse::ok(&json!({
+                        "users": self
+                            .members
+                            .iter()
+                            .filter(|m| m["lab_id"] == lab)
+                            .collect::<Vec<_>>(),
+                    })),
+                    None => MockResponse::not_found(),
+                }
+            }
+            ("PUT", ["api", "courses", course, "admin", "users", user]) => {
+                match (id(course), id(user)) {
+                    (Some(course), Some(user)) => self.set_user_lab(course, user, request),
+                    _ => MockResponse::not_found(),
+                }
+            }
+            (
+                "GET",
+                [
+                    "api",
+                    "lessons",
+                    lesson,
+                    kind @ ("progress" | "quiz_responses"),
+                ],
+            ) => {
+                let Some(lesson) = id(lesson).filter(|l| self.lessons.contains_key(l)) else {
+                    return MockResponse::not_found();
+                };
+                let (key, values) = match *kind {
+                    "progress" => ("progress", &self.lesson_progress),
+                    _ => ("responses", &self.quiz_responses),
+                };
+                MockResponse::ok(&json!({
+                    key: values
+                        .iter()
+                        .filter(|v| v["lesson_id"] == lesson)
+                        .collect::<Vec<_>>(),
+                }))
+            }
+            ("GET", ["api", "lessons", lesson]) => {
+                match id(lesson).and_then(|l| self.lessons.get(&l)) {
+                    Some(lesson) => MockResponse::ok(&json!({"lesson": lesson})),
+                    None => MockResponse::not_found(),
+                }
+            }
+            ("GET", ["api", "challenges", challenge]) => {
+                match id(challenge).and_then(|c| self.challenges.get(&c)) {
+                    Some(challenge) => MockResponse::ok(&json!({"challenge": challenge})),
+                    None => MockResponse::not_found(),
+                }
+            }
+            ("GET", ["api", "challenges", challenge, "submissions"]) => {
+                let Some(challenge) = id(challenge).filter(|c| self.challenges.contains_key(c))
+                else {
+                    return MockResponse::not_found();
+                };
+                let self_id = self.self_user_id();
+                MockResponse::ok(&json!({
+                    "submissions": self
+                        .submissions
+                        .values()
+                        .rev()
+                        .filter(|s| s["challenge_id"] == challenge && s["user_id"] == self_id)
+                        .collect::<Vec<_>>(),
+                }))
+            }
+            ("POST", ["api", "challenges", challenge, "submissions"]) => {
+                match id(challenge).filter(|c| self.challenges.contains_key(c)) {
+                    Some(challenge) => self.submit_challenge(challenge, request),
+                    None => MockResponse::not_found(),
+                }
+            }
+            ("GET", ["api", "submissions", submission]) => match id(submission) {
+                Some(submission) => self.mark_submission(submission),
+                None => MockResponse::not_found(),
+            },
+            ("GET", ["api", "courses", course, "workspaces"]) => match id(course) {
+                Some(course) => {
+                    let self_id = self.self_user_id();
+                    MockResponse::ok(&json!({
+                        "workspaces": self
+                            .workspaces
+                            .values()
+                            .filter(|w| w["course_id"] == course && w["user_id"] == self_id)
+                            .collect::<Vec<_>>(),
+                    }))
+                }
+                None => MockResponse::not_found(),
+            },
+            ("GET", ["api", "workspaces", workspace]) => {
+                match id(workspace).and_then(|w| self.workspaces.get(&w)) {
+                    Some(workspace) => MockResponse::ok(&json!({"workspace": workspace})),
+                    None => MockResponse::not_found(),
+                }
+            }
+            (method, ["api", "workspaces", workspace, "files", rest @ ..]) => {
+                match id(workspace).filter(|w| self.workspaces.contains_key(w)) {
+                    Some(workspace) => self.workspace_files(workspace, method, rest, request),
+                    None => MockResponse::not_found(),
+                }
+            }
+            ("POST", ["api", "threads", thread, "comments"]) => match id(thread) {
+                Some(thread) => self.post_reply(thread, None, request),
+                None => MockResponse::not_found(),
+            },
+            ("POST", ["api", "comments", parent, "comments"]) => {
+                let Some(parent) = id(parent) else {
+                    return MockResponse::not_found();
+                };
+                let thread = self
+                    .threads
+                    .iter()
+                    .find(|(_, thread)| reply_list(thread, parent).is_some())
+                    .map(|(id, _)| *id);
+                match thread {
+                    Some(thread) => self.post_reply(thread, Some(parent), request),
+                    None => MockResponse::not_found(),
+                }
+            }
+            _ => MockResponse::not_found(),
+        }
+    }
+
+    /// The seeded user's course with this ID.
+    fn course_mut(&mut self, course_id: u64) -> Option<&mut Value> {
+        self.user.as_mut()?["courses"]
+            .as_array_mut()?
+            .iter_mut()
+            .map(|c| &mut c["course"])
+            .find(|c| c["id"] == course_id)
+    }
+
+    fn update_user_settings(&mut self, request: &RecordedRequest) -> MockResponse {
+        let Some(patch) = request.json().map(|b| b["settings"].clone()) else {
+            return MockResponse::bad_request("expected settings");
+        };
+        let Some(user) = self.user.as_mut().map(|u| &mut u["user"]) else {
+            return MockResponse::error(401, "unauthorized", "No user seeded");
+        };
+
+        merge(&mut user["settings"], patch);
+        MockResponse::ok(&json!({"user": user}))
+    }
+
+    fn update_course_role_settings(
+        &mut self,
+        course_id: u64,
+        request: &RecordedRequest,
+    ) -> MockResponse {
+        let Some(patch) = request.json().map(|b| b["settings"].clone()) else {
+            return MockResponse::bad_request("expected settings");
+        };
+        let role = self
+            .user
+            .as_mut()
+            .and_then(|u| u["courses"].as_array_mut())
+            .and_then(|courses| courses.iter_mut().find(|c| c["course"]["id"] == course_id))
+            .map(|c| &mut c["role"]);
+        let Some(role) = role else {
+            return MockResponse::not_found();
+        };
+
+        merge(&mut role["settings"], patch);
+        MockResponse::ok(&json!({"role": role}))
+    }
+
+    fn update_course_settings(
+        &mut self,
+        course_id: u64,
+        request: &RecordedRequest,
+    ) -> MockResponse {
+        let Some(Value::Object(patch)) =
+            request.json().map(|b| b["settings"]["discussion"].clone())
+        else {
+            return MockResponse::bad_request("expected discussion settings");
+        };
+        let Some(course) = self.course_mut(course_id) else {
+            return MockResponse::not_found();
+        };
+
+        merge(&mut course["settings"]["discussion"], Value::Object(patch));
+        MockResponse::ok(&json!({"course": course}))
+    }
+
+    fn course_threads(&self, course_id: u64, request: &RecordedRequest) -> MockResponse {
+        let limit = request
+            .query_param("limit")
+            .and_then(|l| l.parse().ok())
+            .unwrap_or(20);
+        let offset = request
+            .query_param("offset")
+            .and_then(|o| o.parse().ok())
+            .unwrap_or(0);
+        let self_id = self.self_user_id();
+
+        let mut threads: Vec<&Value> = self
+            .threads
+            .values()
+            .filter(|t| t["course_id"] == course_id)
+            .filter(|t| match request.query_param("filter") {
+                Some("unread") => t["is_seen"] == false,
+                Some("new_replies") => t["is_seen"] == true && t["new_reply_count"] != 0,
+                Some("unanswered") => {
+                    t["type"] == "question" && t["answers"].as_array().is_none_or(|a| a.is_empty())
+                }
+                Some("unresolved") => t["unresolved_count"] != 0,
+                Some("endorsed") => t["is_endorsed"] == true,
+                Some("watching") => t["is_watched"] == true,
+                Some("starred") => t["is_starred"] == true,
+                Some("private") => t["is_private"] == true,
+                Some("public") => t["is_private"] == false,
+                Some("mine") => t["user_id"] == self_id,
+                _ => true,
+            })
+            .collect();
+        // newest first, which is the only sort the client knows
+        threads.sort_by_key(|t| std::cmp::Reverse(t["number"].as_u64()));
+
+        MockResponse::ok(&json!({
+            "sort_key": "",
+            "threads": threads
+                .into_iter()
+                .skip(offset)
+                .take(limit)
+                .map(partial_thread)
+                .collect::<Vec<_>>(),
+            "users": self.participants,
+        }))
+    }
+
+    fn chat_messages(&self, channel_id: u64, request: &RecordedRequest) -> MockResponse {
+        let limit = request
+            .query_param("limit")
+            .and_then(|l| l.parse().ok())
+            .unwrap_or(50);
+        let before = request.query_param("before").and_then(|b| b.parse().ok());
+
+        let messages: Vec<&Value> = self
+            .messages
+            .get(&channel_id)
+            .into_iter()
+            .flatten()
+            .rev()
+            .filter(|m| before.is_none_or(|b: u64| m["id"].as_u64().is_some_and(|id| id < b)))
+            .take(limit)
+            .collect();
+
+        MockResponse::ok(&json!({"messages": messages, "users": self.participants}))
+    }
+
+    fn send_chat_message(&mut self, channel_id: u64, request: &RecordedRequest) -> MockResponse {
+        let Some(content) = request
+            .json()
+            .and_then(|b| b["message"]["content"].as_str().map(String::from))
+        else {
+            return MockResponse::bad_request("expected a message");
+        };
+
+        let mut message = fixtures::ChatMessageFixture {
+            id: self.next_id(),
+            channel_id,
+            user_id: self.self_user_id(),
+            ..Default::default()
+        }
+        .to_value();
+        message["content"] = json!(content);
+        message["document"] = json!(
+            crate::document::Document::parse(&content)
+                .map(|d| d.to_plain_text())
+                .unwrap_or_default()
+        );
+
+        self.insert_chat_message(message.clone());
+        MockResponse::ok(&json!({"message": message}))
+    }
+
+    fn submit_challenge(&mut self, challenge_id: u64, request: &RecordedRequest) -> MockResponse {
+        if request
+            .json()
+            .is_none_or(|b| b["files"].as_array().is_none_or(Vec::is_empty))
+        {
+            return MockResponse::bad_request("expected files");
+        }
+
+        let id = self.next_id();
+        let submission = json!({
+            "id": id,
+            "challenge_id": challenge_id,
+            "user_id": self.self_user_id(),
+            "status": "queued",
+            "score": null,
+            "max_score": null,
+            "error": null,
+            "created_at": fixtures::DEFAULT_TIMESTAMP,
+            "marked_at": null,
+            "results": [],
+        });
+        self.submissions.insert(id, submission.clone());
+        MockResponse::ok(&json!({"submission": submission}))
+    }
+
+    /// Respond with a submission, then move it on: queued, running, then completed.
+    fn mark_submission(&mut self, id: u64) -> MockResponse {
+        let Some(submission) = self.submissions.get_mut(&id) else {
+            return MockResponse::not_found();
+        };
+        let response = MockResponse::ok(&json!({"submission": submission}));
+
+        match submission["status"].as_str() {
+            Some("queued") => submission["status"] = json!("running"),
+            Some("running") => {
+                let challenge_id = submission["challenge_id"].as_u64().unwrap_or_default();
+                let results = self
+                    .challenge_results
+                    .get(&challenge_id)
+                    .cloned()
+                    .unwrap_or_default();
+                let total = |key: &str| results.iter().filter_map(|r| r[key].as_f64()).sum::<f64>();
+                submission["score"] = json!(total("score"));
+                submission["max_score"] = json!(total("max_score"));
+                submission["status"] = json!("completed");
+                submission["marked_at"] = json!(fixtures::DEFAULT_TIMESTAMP);
+                submission["results"] = json!(results);
+            }
+            _ => {}
+        }
+        response
+    }
+
+    fn workspace_files(
+        &mut self,
+        workspace_id: u64,
+        method: &str,
+        rest: &[&str],
+        request: &RecordedRequest,
+    ) -> MockResponse {
+        let files = self.workspace_files.entry(workspace_id).or_default();
+        let path = request.query_param("path").map(String::from);
+
+        match (method, rest, path) {
+            ("GET", [], _) => {
+                let mut entries: BTreeMap<String, Value> = BTreeMap::new();
+                for (path, content) in files.iter() {
+                    let mut dir = path.as_str();
+                    while let Some((parent, _)) = dir.rsplit_once('/') {
+                        entries
+                            .insert(String::from(parent), fixtures::workspace_file(parent, None));
+                        dir = parent;
+                    }
+                    entries.insert(path.clone(), fixtures::workspace_file(path, Some(content)));
+                }
+                MockResponse::ok(&json!({"files": entries.into_values().collect::<Vec<_>>()}))
+            }
+            ("GET", ["raw"], Some(path)) => match files.get(&path) {
+                Some(content) => MockResponse::bytes(content.clone()),
+                None => MockResponse::not_found(),
+            },
+            ("PUT", ["raw"], Some(path)) => {
+                let file = fixtures::workspace_file(&path, Some(&request.body));
+                files.insert(path, request.body.clone());
+                MockResponse::ok(&json!({"file": file}))
+            }
+            ("DELETE", [], Some(path)) => {
+                let prefix = format!("{path}/");
+                let before = files.len();
+                files.retain(|p, _| *p != path && !p.starts_with(&prefix));
+                if files.len() == before {
+                    MockResponse::not_found()
+                } else {
+                    MockResponse::ok(&json!({}))
+                }
+            }
+            (_, _, None) => MockResponse::bad_request("expected a path"),
+            _ => MockResponse::not_found(),
+        }
+    }
+
+    fn set_user_lab(
+        &mut self,
+        course_id: u64,
+        user_id: u64,
+        request: &RecordedRequest,
+    ) -> MockResponse {
+        let Some(lab_id) = request.json().map(|b| b["lab_id"].clone()) else {
+            return MockResponse::bad_request("expected a lab_id");
+        };
+        if let Some(lab) = lab_id.as_u64()
+            && self
+                .labs
+                .get(&lab)
+                .is_none_or(|l| l["course_id"] != course_id)
+        {
+            return MockResponse::bad_request("no such lab in this course");
+        }
+
+        match self
+            .members
+            .iter_mut()
+            .find(|m| m["course_id"] == course_id && m["id"] == user_id)
+        {
+            Some(member) => {
+                member["lab_id"] = lab_id;
+                MockResponse::ok(&json!({"user": member}))
+            }
+            None => MockResponse::not_found(),
+        }
+    }
+
+    /// The courses in a realm, including the seeded user's own.
+    fn realm_courses(&self, realm_id: u64) -> Vec<Value> {
+        let own = self
+            .user
+            .iter()
+            .flat_map(|u| u["courses"].as_array().into_iter().flatten())
+            .map(|c| &c["course"]);
+        let courses: BTreeMap<u64, &Value> = own
+            .chain(self.realm_courses.values())
+            .filter(|c| c["realm_id"] == realm_id)
+            .map(|c| (c["id"].as_u64().unwrap_or_default(), c))
+            .collect();
+        courses.into_values().cloned().collect()
+    }
+
+    fn create_realm_course(&mut self, realm_id: u64, request: &RecordedRequest) -> MockResponse {
+        let Some(new) = request.json().map(|b| b["course"].clone()) else {
+            return MockResponse::bad_request("expected a course");
+        };
+        let field = |key: &str| new[key].as_str().unwrap_or_default().to_owned();
+
+        let mut id = self.next_id();
+        while self.course_mut(id).is_some() {
+            id = self.next_id();
+        }
+        let mut course = fixtures::course_value(id, &field("code"), &field("name"));
+        course["realm_id"] = json!(realm_id);
+        for key in ["year", "session", "status"] {
+            if new[key].is_string() {
+                course[key] = new[key].clone();
+            }
+        }
+        self.realm_courses.insert(id, course.clone());
+        MockResponse::ok(&json!({"course": course}))
+    }
+
+    fn notifications(&self, request: &RecordedRequest) -> MockResponse {
+        let limit = request
+            .query_param("limit")
+            .and_then(|l| l.parse().ok())
+            .unwrap_or(30);
+        let before = request.query_param("before").and_then(|b| b.parse().ok());
+        let unread_only = request.query_param("unread") == Some("true");
+
+        let notifications: Vec<&Value> = self
+            .notifications
+            .values()
+            .rev()
+            .filter(|n| before.is_none_or(|b: u64| n["id"].as_u64().is_some_and(|id| id < b)))
+            .filter(|n| !unread_only || n["read"] == false)
+            .take(limit)
+            .collect();
+
+        MockResponse::ok(&json!({
+            "notifications": notifications,
+            "unread_count": self.notifications.values().filter(|n| n["read"] == false).count(),
+        }))
+    }
+
+    fn user_activity(&self, user_id: u64, request: &RecordedRequest) -> MockResponse {
+        fn replies<'a>(list: &'a Value, out: &mut Vec<&'a Value>) {
+            for reply in list.as_array().into_iter().flatten() {
+                out.push(reply);
+                replies(&reply["comments"], out);
+            }
+        }
+
+        let limit = request
+            .query_param("limit")
+            .and_then(|l| l.parse().ok())
+            .unwrap_or(30);
+        let offset = request
+            .query_param("offset")
+            .and_then(|o| o.parse().ok())
+            .unwrap_or(0);
+        let course_id = request
+            .query_param("course_id")
+            .and_then(|c| c.parse().ok());
+        // others' anonymous posts are not theirs to see
+        let visible = |post: &Value| {
+            post["user_id"] == user_id
+                && (post["is_anonymous"] == false || user_id == self.self_user_id())
+        };
+
+        let mut items = Vec::new();
+        for thread in self.threads.values() {
+            if course_id.is_some_and(|c: u64| thread["course_id"] != c) {
+                continue;
+            }
+            let item = |type_: &str, post: &Value, comment_id: &Value| {
+                json!({
+                    "type": type_,
+                    "course_id": thread["course_id"],
+                    "thread_id": thread["id"],
+                    "thread_title": thread["title"],
+                    "thread_number": thread["number"],
+                    "comment_id": comment_id,
+                    "document": post["document"],
+                    "created_at": post["created_at"],
+                })
+            };
+
+            if visible(thread) {
+                items.push(item("thread", thread, &Value::Null));
+            }
+            let mut posts = Vec::new();
+            replies(&thread["answers"], &mut posts);
+            replies(&thread["comments"], &mut posts);
+            for post in posts.into_iter().filter(|p| visible(p)) {
+                items.push(item(
+                    post["type"].as_str().unwrap_or("comment"),
+                    post,
+                    &post["id"],
+                ));
+            }
+        }
+        items.sort_by(|a, b| b["created_at"].as_str().cmp(&a["created_at"].as_str()));
+
+        MockResponse::ok(&json!({
+            "items": items.into_iter().skip(offset).take(limit).collect::<Vec<_>>(),
+        }))
+    }
+
+    fn insert_chat_message(&mut self, message: Value) -> bool {
+        let channel_id = message["channel_id"].as_u64().unwrap_or_default();
+        let Some(channel) = self.channels.get_mut(&channel_id) else {
+            return false;
+        };
+        channel["last_message_at"] = message["created_at"].clone();
+        self.messages.entry(channel_id).or_default().push(message);
+        true
+    }
+
+    fn post_reply(
+        &mut self,
+        thread_id: u64,
+        parent_id: Option<u64>,
+        request: &RecordedRequest,
+    ) -> MockResponse {
+        let Some(comment) = request.json().and_then(|b| b.get("comment").cloned()) else {
+            return MockResponse::bad_request("expected a comment");
+        };
+        let Some(thread) = self.threads.get(&thread_id) else {
+            return MockResponse::not_found();
+        };
+
+        let reply_type = match parent_id {
+            Some(_) => "comment",
+            None => comment["type"].as_str().unwrap_or("comment"),
+        };
+        let mut reply = fixtures::ReplyFixture {
+            thread_id,
+            course_id: thread["course_id"].as_u64().unwrap_or_default(),
+            user_id: self.self_user_id(),
+            reply_type: String::from(reply_type),
+            ..Default::default()
+        }
+        .to_value();
+        let id = self.next_id();
+        let content = comment["content"].as_str().unwrap_or_default();
+        reply["id"] = json!(id);
+        reply["parent_id"] = json!(parent_id);
+        reply["content"] = json!(content);
+        reply["document"] = json!(
+            crate::document::Document::parse(content)
+                .map(|d| d.to_plain_text())
+                .unwrap_or_default()
+        );
+        reply["is_private"] = comment["is_private"].clone();
+        reply["is_anonymous"] = comment["is_anonymous"].clone();
+
+        self.insert_reply(thread_id, parent_id, reply.clone());
+        MockResponse::ok(&json!({"comment": reply}))
+    }
+
+    fn insert_reply(&mut self, thread_id: u64, parent_id: Option<u64>, reply: Value) -> bool {
+        let Some(thread) = self.threads.get_mut(&thread_id) else {
+            return false;
+        };
+        let siblings = match parent_id {
+            Some(parent) => match reply_list(thread, parent).map(|key| &mut thread[key]) {
+                Some(Value::Array(replies)) => {
+                    find_reply(replies, parent).map(|p| &mut p["comments"])
+                }
+                _ => None,
+            },
+            None if reply["type"] == "answer" => Some(&mut thread["answers"]),
+            None => Some(&mut thread["comments"]),
+        };
+        let Some(Value::Array(siblings)) = siblings else {
+            return false;
+        };
+        siblings.push(reply);
+
+        let count = thread["reply_count"].as_u64().unwrap_or_default();
+        thread["reply_count"] = json!(count + 1);
+        true
+    }
+}
+
+/// An in-process HTTP server imitating Ed Discussion. See the [module docs](self).
+///
+/// The server runs on the current Tokio runtime until dropped.
+pub struct MockServer {
+    addr: SocketAddr,
+    state: Arc<Mutex<State>>,
+    task: JoinHandle<()>,
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+async fn handle(
+    state: Arc<Mutex<State>>,
+    request: hyper::Request<Incoming>,
+) -> Result<hyper::Response<Full<Bytes>>, Infallible> {
+    let (parts, body) = request.into_parts();
+    let body = body
+        .collect()
+        .await
+        .map(|b| b.to_bytes().to_vec())
+        .unwrap_or_default();
+    let query = reqwest::Url::parse(&format!("http://mock{}", parts.uri))
+        .map(|u| u.query_pairs().into_owned().collect())
+        .unwrap_or_default();
+
+    let recorded = RecordedRequest {
+        method: parts.method.to_string(),
+        path: String::from(parts.uri.path()),
+        query,
+        authorization: parts
+            .headers
+            .get("authorization")
+            .and_then(|h| h.to_str().ok())
+            .map(String::from),
+        body,
+    };
+
+    let response = {
+        let mut state = state.lock().expect("mock server state poisoned");
+        state.requests.push(recorded.clone());
+        state.respond(&recorded)
+    };
+
+    let mut builder = hyper::Response::builder().status(response.status);
+    for (key, value) in response.headers {
+        builder = builder.header(key, value);
+    }
+    Ok(builder
+        .body(Full::new(Bytes::from(response.body)))
+        .expect("responses are always valid"))
+}
+
+impl MockServer {
+    /// Start a server on a free local port. It has no data until seeded.
+    pub async fn start() -> crate::Result<Self> {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).await?;
+        let addr = listener.local_addr()?;
+        let state = Arc::new(Mutex::new(State::default()));
+
+        let task_state = state.clone();
+        let task = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let state = task_state.clone();
+                tokio::spawn(async move {
+                    let service = service_fn(move |request| handle(state.clone(), request));
+                    // a client hanging up mid-request is not the server's problem
+                    let _ = http1::Builder::new()
+                        .serve_connection(TokioIo::new(stream), service)
+                        .await;
+                });
+            }
+        });
+
+        Ok(Self { addr, state, task })
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().expect("mock server state poisoned")
+    }
+
+    /// The URL to use as [`ClientOptions::base_url`].
+    pub fn base_url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// A [`Client`] for this server, authenticating with [`MOCK_TOKEN`].
+    pub fn client(&self) -> Client {
+        Client::new_with_opts(
+            MOCK_TOKEN,
+            ClientOptions {
+                base_url: Some(self.base_url()),
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Reject requests not bearing `token` with 401. By default any token is accepted.
+    pub fn expect_token(&self, token: &str) {
+        self.state().token = Some(String::from(token));
+    }
+
+    /// Set the user returned by GET /api/user.
+    pub fn set_user(&self, user: SelfUser) {
+        self.state().user = Some(to_value(&user));
+    }
+
+    /// Add a user to the `users` returned alongside every course's threads.
+    pub fn add_participant(&self, participant: ThreadParticipant) {
+        self.state().participants.push(to_value(&participant));
+    }
+
+    /// Add a thread, replacing any with the same ID.
+    pub fn add_thread(&self, thread: Thread) {
+        let value = to_value(&thread);
+        let mut state = self.state();
+        let id = value["id"].as_u64().unwrap_or_default();
+        state.next_id = state.next_id.max(id);
+        state.threads.insert(id, value);
+    }
+
+    /// Add a reply to the thread it names, nested under reply `parent_id` if given. Returns
+    /// whether the thread (and parent) were found.
+    pub fn add_reply(&self, reply: Reply, parent_id: Option<u64>) -> bool {
+        let value = to_value(&reply);
+        let mut state = self.state();
+        let id = value["id"].as_u64().unwrap_or_default();
+        state.next_id = state.next_id.max(id);
+        let thread_id = value["thread_id"].as_u64().unwrap_or_default();
+        state.insert_reply(thread_id, parent_id, value)
+    }
+
+    /// Add a chat channel, replacing any with the same ID.
+    pub fn add_channel(&self, channel: Channel) {
+        let value = to_value(&channel);
+        let id = value["id"].as_u64().unwrap_or_default();
+        self.state().channels.insert(id, value);
+    }
+
+    /// Add a direct conversation, replacing any with the same ID. The seeded user only sees it
+    /// if they are one of its `user_ids`.
+    pub fn add_direct_conversation(&self, conversation: DirectConversation) {
+        let value = to_value(&conversation);
+        let id = value["id"].as_u64().unwrap_or_default();
+        self.state().channels.insert(id, value);
+    }
+
+    /// Add a message to the channel or direct conversation it names, after any already there.
+    /// Returns whether the channel was found.
+    pub fn add_chat_message(&self, message: ChatMessage) -> bool {
+        let value = to_value(&message);
+        let mut state = self.state();
+        let id = value["id"].as_u64().unwrap_or_default();
+        state.next_id = state.next_id.max(id);
+        state.insert_chat_message(value)
+    }
+
+    /// Add a lesson module, replacing any with the same ID.
+    pub fn add_lesson_module(&self, module: LessonModule) {
+        let value = to_value(&module);
+        let id = value["id"].as_u64().unwrap_or_default();
+        self.state().lesson_modules.insert(id, value);
+    }
+
+    /// Add a lesson, replacing any with the same ID.
+    pub fn add_lesson(&self, lesson: Lesson) {
+        let mut value = to_value(&lesson);
+        if value["slides"].is_null() {
+            value["slides"] = json!([]);
+        }
+        let id = value["id"].as_u64().unwrap_or_default();
+        self.state().lessons.insert(id, value);
+    }
+
+    /// Add a slide to the lesson it names, ordered by `index`. Returns whether the lesson was
+    /// found.
+    pub fn add_slide(&self, slide: Slide) -> bool {
+        let value = to_value(&slide);
+        let lesson_id = value["lesson_id"].as_u64().unwrap_or_default();
+        let mut state = self.state();
+        let Some(Value::Array(slides)) =
+            state.lessons.get_mut(&lesson_id).map(|l| &mut l["slides"])
+        else {
+            return false;
+        };
+        slides.push(value);
+        slides.sort_by_key(|s| s["index"].as_u64());
+        true
+    }
+
+    /// Add a user to a course's roster, as returned to staff.
+    pub fn add_course_member(&self, member: CourseMember) {
+        self.state().members.push(to_value(&member));
+    }
+
+    /// Add a realm, replacing any with the same ID.
+    pub fn add_realm(&self, realm: Realm) {
+        let value = to_value(&realm);
+        let id = value["id"].as_u64().unwrap_or_default();
+        self.state().realms.insert(id, value);
+    }
+
+    /// Add a course to the realm it names, as listed to realm admins, replacing any with the
+    /// same ID.
+    pub fn add_realm_course(&self, course: Course) {
+        let value = to_value(&course);
+        let mut state = self.state();
+        let id = value["id"].as_u64().unwrap_or_default();
+        state.next_id = state.next_id.max(id);
+        state.realm_courses.insert(id, value);
+    }
+
+    /// Add a user to the realm they name.
+    pub fn add_realm_user(&self, user: RealmUser) {
+        self.state().realm_users.push(to_value(&user));
+    }
+
+    /// Add a notification for the seeded user, replacing any with the same ID.
+    pub fn add_notification(&self, notification: Notification) {
+        let value = to_value(&notification);
+        let id = value["id"].as_u64().unwrap_or_default();
+        self.state().notifications.insert(id, value);
+    }
+
+    /// Add a lab, replacing any with the same ID.
+    pub fn add_lab(&self, lab: Lab) {
+        let value = to_value(&lab);
+        let id = value["id"].as_u64().unwrap_or_default();
+        self.state().labs.insert(id, value);
+    }
+
+    /// Add a user's progress through a lesson.
+    pub fn add_lesson_progress(&self, progress: LessonProgress) {
+        self.state().lesson_progress.push(to_value(&progress));
+    }
+
+    /// Add a user's answer to a quiz question.
+    pub fn add_quiz_response(&self, response: QuizResponse) {
+        self.state().quiz_responses.push(to_value(&response));
+    }
+
+    /// Add a code challenge, replacing any with the same ID.
+    pub fn add_challenge(&self, challenge: Challenge) {
+        let value = to_value(&challenge);
+        let id = value["id"].as_u64().unwrap_or_default();
+        self.state().challenges.insert(id, value);
+    }
+
+    /// Mark every later submission to challenge `challenge_id` with `results`. Submissions are
+    /// queued, then running, then completed, moving on a step each time they are fetched.
+    pub fn set_challenge_results(&self, challenge_id: u64, results: Vec<TestCaseResult>) {
+        self.state()
+            .challenge_results
+            .insert(challenge_id, results.iter().map(to_value).collect());
+    }
+
+    /// Add a workspace, replacing any with the same ID. The seeded user only sees it in their
+    /// course's list if they own it.
+    pub fn add_workspace(&self, workspace: Workspace) {
+        let value = to_value(&workspace);
+        let id = value["id"].as_u64().unwrap_or_default();
+        self.state().workspaces.insert(id, value);
+    }
+
+    /// Create or replace the file at `path` in a workspace.
+    pub fn add_workspace_file(&self, workspace_id: u64, path: &str, content: impl Into<Vec<u8>>) {
+        self.state()
+            .workspace_files
+            .entry(workspace_id)
+            .or_default()
+            .insert(String::from(path), content.into());
+    }
+
+    /// The current contents of the file at `path` in a workspace, including uploads.
+    pub fn workspace_file(&self, workspace_id: u64, path: &str) -> Option<Vec<u8>> {
+        self.state()
+            .workspace_files
+            .get(&workspace_id)?
+            .get(path)
+            .cloned()
+    }
+
+    /// The current state of a thread, including replies posted through the server.
+    pub fn thread(&self, id: u64) -> Option<Thread> {
+        self.state()
+            .threads
+            .get(&id)
+            .map(|t| serde_json::from_value(t.clone()).expect("stored threads are valid"))
+    }
+
+    /// Every request received so far, oldest first.
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.state().requests.clone()
+    }
+
+    /// Forget all requests received so far.
+    pub fn clear_requests(&self) {
+        self.state().requests.clear();
+    }
+
+    /// Respond to the next request with `failure`. Calls queue up, one failure per request.
+    pub fn fail_next(&self, failure: Failure) {
+        self.state().fail_next.push_back(failure);
+    }
+
+    /// Respond to every request with this method and path with `failure`, until
+    /// [`clear_failures`](Self::clear_failures) is called.
+    pub fn fail_matching(&self, method: &str, path: &str, failure: Failure) {
+        self.state()
+            .fail_matching
+            .push((method.to_uppercase(), String::from(path), failure));
+    }
+
+    /// Remove all pending and standing failures.
+    pub fn clear_failures(&self) {
+        let mut state = self.state();
+        state.fail_next.clear();
+        state.fail_matching.clear();
+    }
+}
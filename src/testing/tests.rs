@@ -0,0 +1,670 @@
+use std::time::Duration;
+
+use reqwest::StatusCode;
+use serde_json::{Map, json};
+
+use super::{
+    Failure, MOCK_TOKEN, MockServer,
+    fixtures::{
+        self, ChallengeFixture, ChatMessageFixture, CourseFixture, LessonFixture,
+        NotificationFixture, RealmFixture, ReplyFixture, SlideFixture, ThreadFixture, UserFixture,
+    },
+};
+use crate::{
+    Error,
+    document::Document,
+    model::{LessonStatus, thread::ApprovalStatus},
+    opts::{
+        CourseDiscussionSettingsPatch, GetCourseThreadsFilterKey, ModerationReportOptions,
+        PostReplyKind, PostReplyOptions, SourceFile, SubmissionPollOptions,
+    },
+};
+
+fn overrides(value: serde_json::Value) -> Map<String, serde_json::Value> {
+    match value {
+        serde_json::Value::Object(map) => map,
+        _ => panic!("overrides must be an object"),
+    }
+}
+
+fn thread(id: u64, number: u64) -> ThreadFixture {
+    ThreadFixture {
+        id,
+        number,
+        title: format!("Thread {number}"),
+        ..Default::default()
+    }
+}
+
+fn assert_status(result: crate::Result<impl std::fmt::Debug>, status: u16) {
+    match result {
+        Err(Error::Status { status: got, .. }) => {
+            assert_eq!(got, StatusCode::from_u16(status).unwrap())
+        }
+        other => panic!("expected HTTP {status}, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn self_user_and_settings() {
+    let server = MockServer::start().await.unwrap();
+    server.set_user(
+        UserFixture {
+            courses: vec![CourseFixture {
+                role: String::from("admin"),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+        .build(),
+    );
+    let client = server.client();
+
+    let user = client.get_self_user().await.unwrap();
+    assert_eq!(user.user().name(), "Test User");
+    assert_eq!(user.courses().len(), 1);
+
+    let updated = client
+        .snooze_notifications("2030-01-01T00:00:00Z")
+        .await
+        .unwrap();
+    assert_eq!(updated.settings().snooze_end(), "2030-01-01T00:00:00Z");
+
+    let requests = server.requests();
+    assert_eq!(
+        requests[0].authorization.as_deref(),
+        Some("Bearer mock-token")
+    );
+    assert_eq!(requests[1].method, "PUT");
+    assert_eq!(requests[1].path, "/api/user/settings");
+}
+
+#[tokio::test]
+async fn course_settings() {
+    let server = MockServer::start().await.unwrap();
+    server.set_user(UserFixture::default().build());
+    let client = server.client();
+
+    let patch = CourseDiscussionSettingsPatch {
+        anonymous: Some(false),
+        ..Default::default()
+    };
+    let course = client.update_course_settings(1u64, &patch).await.unwrap();
+    assert!(!course.settings().discussion().anonymous());
+    assert!(
+        !client
+            .get_course(1u64)
+            .await
+            .unwrap()
+            .settings()
+            .discussion()
+            .anonymous()
+    );
+    assert_status(client.get_course(2u64).await, 404);
+}
+
+#[cfg(feature = "analytics")]
+#[tokio::test]
+async fn forum_report_time_zone() {
+    use crate::opts::AnalyticsOptions;
+
+    let server = MockServer::start().await.unwrap();
+    server.set_user(UserFixture::default().build());
+    server.add_thread(
+        ThreadFixture {
+            created_at: String::from("2025-01-06T03:00:00Z"),
+            ..Default::default()
+        }
+        .build(),
+    );
+    let client = server.client();
+
+    // Monday in UTC but Sunday evening in the user's America/Los_Angeles
+    let report = client
+        .forum_report(1u64, AnalyticsOptions::default())
+        .await
+        .unwrap();
+    assert_eq!(report.activity.by_weekday, [0, 0, 0, 0, 0, 0, 1]);
+
+    let options = AnalyticsOptions {
+        tz: String::from("Asia/Tokyo"),
+        ..Default::default()
+    };
+    let report = client.forum_report(1u64, options).await.unwrap();
+    assert_eq!(report.activity.by_weekday, [1, 0, 0, 0, 0, 0, 0]);
+}
+
+#[tokio::test]
+async fn saved_replies() {
+    let server = MockServer::start().await.unwrap();
+    server.set_user(UserFixture::default().build());
+    let client = server.client();
+
+    client.add_saved_reply(1u64, "one").await.unwrap();
+    client.add_saved_reply(1u64, "two").await.unwrap();
+    client.update_saved_reply(1u64, 0, "uno").await.unwrap();
+    assert_eq!(client.remove_saved_reply(1u64, 1).await.unwrap(), ["uno"]);
+    assert!(matches!(
+        client.update_saved_reply(1u64, 1, "dos").await,
+        Err(Error::NotFound(_))
+    ));
+    assert!(matches!(
+        client.remove_saved_reply(1u64, 1).await,
+        Err(Error::NotFound(_))
+    ));
+}
+
+#[tokio::test]
+async fn threads_paginate() {
+    let server = MockServer::start().await.unwrap();
+    for n in 1..=250 {
+        server.add_thread(thread(n, n).build());
+    }
+    let client = server.client();
+
+    let threads = client.get_all_course_threads(1u64, None).await.unwrap();
+    assert_eq!(threads.len(), 250);
+    assert_eq!(*threads[0].number(), 250);
+    assert_eq!(*threads[249].number(), 1);
+
+    let offsets: Vec<_> = server
+        .requests()
+        .iter()
+        .map(|r| r.query_param("offset").map(String::from))
+        .collect();
+    assert_eq!(
+        offsets,
+        [Some("0"), Some("100"), Some("200")].map(|o| o.map(String::from))
+    );
+}
+
+#[tokio::test]
+async fn threads_filter() {
+    let server = MockServer::start().await.unwrap();
+    server.add_thread(thread(1, 1).build());
+    server.add_thread(
+        ThreadFixture {
+            overrides: overrides(json!({"is_endorsed": true})),
+            ..thread(2, 2)
+        }
+        .build(),
+    );
+    let client = server.client();
+
+    let endorsed = client
+        .get_all_course_threads(1u64, Some(GetCourseThreadsFilterKey::Endorsed))
+        .await
+        .unwrap();
+    assert_eq!(endorsed.len(), 1);
+    assert_eq!(*endorsed[0].number(), 2);
+    assert_eq!(server.requests()[0].query_param("filter"), Some("endorsed"));
+}
+
+#[tokio::test]
+async fn thread_by_id_and_number() {
+    let server = MockServer::start().await.unwrap();
+    server.add_participant(fixtures::participant(1, "Test User", "student"));
+    server.add_thread(thread(10, 3).build());
+    let client = server.client();
+
+    let (by_id, users, _) = client.get_thread(10u64).await.unwrap().dissolve();
+    assert_eq!(by_id.title(), "Thread 3");
+    assert_eq!(users.len(), 1);
+
+    let by_number = client.get_thread_by_number(1u64, 3).await.unwrap();
+    assert_eq!(u64::from(*by_number.thread().id()), 10);
+    assert_status(client.get_thread_by_number(1u64, 4).await, 404);
+}
+
+#[tokio::test]
+async fn replies() {
+    let server = MockServer::start().await.unwrap();
+    server.add_thread(thread(1, 1).build());
+    assert!(server.add_reply(ReplyFixture::default().build(), None));
+    let client = server.client();
+
+    let answer = client
+        .post_thread_reply(
+            1u64,
+            &Document::from_plain_text("An answer."),
+            PostReplyOptions {
+                kind: PostReplyKind::Answer,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    client
+        .post_comment_reply(
+            *answer.comment().id(),
+            &Document::from_plain_text("Thanks!"),
+            PostReplyOptions::default(),
+        )
+        .await
+        .unwrap();
+
+    let body = server.requests()[0].json().unwrap();
+    assert_eq!(body["comment"]["type"], "answer");
+
+    let thread = server.thread(1).unwrap();
+    assert_eq!(*thread.reply_count(), 3);
+    assert_eq!(thread.answers().len(), 1);
+    assert_eq!(thread.answers()[0].comments().len(), 1);
+}
+
+#[tokio::test]
+async fn approval() {
+    let server = MockServer::start().await.unwrap();
+    for n in 1..=3 {
+        server.add_thread(
+            ThreadFixture {
+                overrides: overrides(json!({"approved_status": "pending"})),
+                ..thread(n, n)
+            }
+            .build(),
+        );
+    }
+    let client = server.client();
+
+    client.approve_thread(1u64).await.unwrap();
+    client.reject_thread(2u64, None).await.unwrap();
+
+    let pending = client.get_pending_threads(1u64).await.unwrap();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(u64::from(*pending[0].id()), 3);
+    assert_eq!(
+        *server.thread(2).unwrap().approved_status(),
+        ApprovalStatus::Rejected
+    );
+}
+
+#[tokio::test]
+async fn flags_and_moderation_report() {
+    let server = MockServer::start().await.unwrap();
+    for n in 1..=3 {
+        server.add_thread(thread(n, n).build());
+    }
+    for (id, thread_id) in [(11, 1), (12, 1), (21, 2)] {
+        assert!(
+            server.add_reply(
+                ReplyFixture {
+                    id,
+                    thread_id,
+                    ..Default::default()
+                }
+                .build(),
+                None,
+            )
+        );
+    }
+    let client = server.client();
+
+    client.flag_thread(1u64, "spam").await.unwrap();
+    client.flag_thread(2u64, "spam").await.unwrap();
+    client.flag_thread(2u64, "rude").await.unwrap();
+    client.flag_reply(21u64, "spam").await.unwrap();
+    client.flag_reply(12u64, "spam").await.unwrap();
+    client.flag_reply(12u64, "rude").await.unwrap();
+    assert_eq!(server.requests()[0].json().unwrap()["reason"], "spam");
+
+    let report = client
+        .moderation_report(
+            1u64,
+            ModerationReportOptions {
+                include_replies: true,
+            },
+        )
+        .await
+        .unwrap();
+    let threads: Vec<u64> = report.threads.iter().map(|t| u64::from(*t.id())).collect();
+    let replies: Vec<(u64, u64)> = report
+        .replies
+        .iter()
+        .map(|r| (u64::from(*r.thread.id()), u64::from(*r.reply.id())))
+        .collect();
+    assert_eq!(threads, [2, 1]);
+    assert_eq!(replies, [(1, 12), (2, 21)]);
+    assert_eq!(report.total_flags(), 6);
+
+    let flagged = client.get_flagged_threads(1u64).await.unwrap();
+    assert_eq!(flagged.len(), 2);
+
+    for id in [1u64, 2] {
+        client.clear_thread_flags(id).await.unwrap();
+    }
+    for id in [12u64, 21] {
+        client.clear_reply_flags(id).await.unwrap();
+    }
+    let report = client
+        .moderation_report(
+            1u64,
+            ModerationReportOptions {
+                include_replies: true,
+            },
+        )
+        .await
+        .unwrap();
+    assert!(report.is_empty());
+    assert_status(client.flag_thread(4u64, "spam").await, 404);
+}
+
+#[tokio::test]
+async fn chat() {
+    let server = MockServer::start().await.unwrap();
+    server.set_user(UserFixture::default().build());
+    server.add_channel(fixtures::channel(1, 1, "general"));
+    server.add_direct_conversation(fixtures::direct_conversation(2, 1, &[1, 2]));
+    server.add_direct_conversation(fixtures::direct_conversation(3, 1, &[2, 3]));
+    for id in 1..=120 {
+        assert!(
+            server.add_chat_message(
+                ChatMessageFixture {
+                    id,
+                    ..Default::default()
+                }
+                .build()
+            )
+        );
+    }
+    let client = server.client();
+
+    assert_eq!(client.get_chat_channels(1u64).await.unwrap().len(), 1);
+    assert_eq!(
+        client.get_direct_conversations(1u64).await.unwrap().len(),
+        1
+    );
+
+    let history = client.get_all_chat_messages(1u64).await.unwrap();
+    let ids: Vec<u64> = history.messages.iter().map(|m| (*m.id()).into()).collect();
+    assert_eq!(ids, (1..=120).collect::<Vec<_>>());
+
+    let sent = client
+        .send_chat_message(1u64, &Document::from_plain_text("hello"))
+        .await
+        .unwrap();
+    assert_eq!(u64::from(*sent.channel_id()), 1);
+}
+
+#[tokio::test]
+async fn lessons_and_labs() {
+    let server = MockServer::start().await.unwrap();
+    server.add_lesson_module(fixtures::lesson_module(1, 1, "Week 1"));
+    server.add_lesson(
+        LessonFixture {
+            module_id: Some(1),
+            ..Default::default()
+        }
+        .build(),
+    );
+    server.add_lab(fixtures::lab(1, 1, "Lab A", &[2]));
+    server.add_course_member(fixtures::course_member(
+        2,
+        1,
+        "Tutor",
+        "tutor@example.edu",
+        "tutor",
+    ));
+    server.add_course_member(fixtures::course_member(
+        3,
+        1,
+        "Student",
+        "student@example.edu",
+        "student",
+    ));
+    let client = server.client();
+
+    let lessons = client.get_course_lessons(1u64).await.unwrap();
+    assert_eq!(lessons.by_module().len(), 1);
+    assert_eq!(
+        client.get_lesson(1u64).await.unwrap().title(),
+        "Test lesson"
+    );
+
+    assert_eq!(client.get_labs(1u64).await.unwrap().len(), 1);
+    assert_eq!(client.get_course_members(1u64).await.unwrap().len(), 2);
+    let moved = client.move_to_lab(1u64, 3u64, 1u64).await.unwrap();
+    assert!(moved.lab_id().is_some());
+    assert_eq!(client.get_lab_members(1u64).await.unwrap().len(), 1);
+    client.remove_from_lab(1u64, 3u64).await.unwrap();
+    assert!(client.get_lab_members(1u64).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn gradebook() {
+    let server = MockServer::start().await.unwrap();
+    server.add_lesson_module(fixtures::lesson_module(1, 1, "Week 1"));
+    server.add_lesson(
+        LessonFixture {
+            module_id: Some(1),
+            title: String::from("Quiz"),
+            ..Default::default()
+        }
+        .build(),
+    );
+    server.add_lesson(
+        LessonFixture {
+            id: 2,
+            title: String::from("Welcome"),
+            ..Default::default()
+        }
+        .build(),
+    );
+    assert!(
+        server.add_slide(
+            SlideFixture {
+                slide_type: String::from("quiz"),
+                overrides: overrides(json!({"questions": [
+                    {"id": 1, "max_score": 1.0},
+                    {"id": 2, "max_score": 2.0},
+                ]})),
+                ..Default::default()
+            }
+            .build()
+        )
+    );
+    for (id, name, email, role) in [
+        (2, "Tutor", "tutor@example.edu", "staff"),
+        (3, "Student", "student@example.edu", "student"),
+        (4, "Absent", "absent@example.edu", "student"),
+    ] {
+        server.add_course_member(fixtures::course_member(id, 1, name, email, role));
+    }
+    server.add_lesson_progress(fixtures::lesson_progress(3, 1, "completed", 1, 1));
+    server.add_quiz_response(fixtures::quiz_response(3, 1, 1, 1, 1.0, 1.0));
+    let client = server.client();
+
+    let gradebook = client.gradebook(1u64).await.unwrap();
+    let lessons: Vec<_> = gradebook
+        .lessons
+        .iter()
+        .map(|l| (l.title.as_str(), l.max_score))
+        .collect();
+    assert_eq!(lessons, [("Welcome", 0.0), ("Quiz", 3.0)]);
+    assert_eq!(gradebook.rows.len(), 2);
+    let student = gradebook.row("student@example.edu").unwrap();
+    assert_eq!(student.entries[1].status, LessonStatus::Completed);
+    assert_eq!(student.entries[1].score, 1.0);
+    let absent = gradebook.row("absent@example.edu").unwrap();
+    assert_eq!(absent.entries[1].status, LessonStatus::NotStarted);
+}
+
+#[tokio::test]
+async fn challenges() {
+    let server = MockServer::start().await.unwrap();
+    server.add_challenge(ChallengeFixture::default().build());
+    server.set_challenge_results(
+        1,
+        vec![fixtures::test_case_result("prints hello", "hello", "hello")],
+    );
+    let client = server.client();
+
+    assert_eq!(client.get_challenge(1u64).await.unwrap().files().len(), 1);
+    let submission = client
+        .submit_challenge_and_wait(
+            1u64,
+            &[SourceFile::new("main.py", "print('hello')")],
+            SubmissionPollOptions {
+                interval: Duration::from_millis(1),
+                timeout: Some(Duration::from_secs(5)),
+            },
+        )
+        .await
+        .unwrap();
+    assert!(!submission.status().is_pending());
+    assert_eq!(submission.results().len(), 1);
+    assert_eq!(
+        client.get_challenge_submissions(1u64).await.unwrap().len(),
+        1
+    );
+}
+
+#[tokio::test]
+async fn workspaces() {
+    let server = MockServer::start().await.unwrap();
+    server.add_workspace(fixtures::workspace(1, 1, 1, "scratch"));
+    server.add_workspace_file(1, "src/main.py", "print(1)");
+    let client = server.client();
+
+    assert_eq!(client.get_workspaces(1u64).await.unwrap().len(), 1);
+    assert_eq!(client.get_workspace(1u64).await.unwrap().name(), "scratch");
+    assert_eq!(
+        client
+            .download_workspace_file(1u64, "src/main.py")
+            .await
+            .unwrap(),
+        b"print(1)"
+    );
+
+    client
+        .upload_workspace_file(1u64, "notes.txt", b"hi")
+        .await
+        .unwrap();
+    assert_eq!(
+        server.workspace_file(1, "notes.txt").as_deref(),
+        Some(&b"hi"[..])
+    );
+    client
+        .delete_workspace_file(1u64, "src/main.py")
+        .await
+        .unwrap();
+    assert_eq!(server.workspace_file(1, "src/main.py"), None);
+}
+
+#[tokio::test]
+async fn realms_and_notifications() {
+    let server = MockServer::start().await.unwrap();
+    server.set_user(UserFixture::default().build());
+    server.add_realm(RealmFixture::default().build());
+    server.add_realm_user(fixtures::realm_user(
+        1,
+        1,
+        "Test User",
+        "test@example.edu",
+        "admin",
+    ));
+    for id in 1..=2 {
+        server.add_notification(
+            NotificationFixture {
+                id,
+                ..Default::default()
+            }
+            .build(),
+        );
+    }
+    let client = server.client();
+
+    client.get_realm(1u64).await.unwrap();
+    assert_eq!(client.get_realm_users(1u64).await.unwrap().len(), 1);
+
+    client.mark_notifications_read([1u64]).await.unwrap();
+    let notifications = client.get_notifications(None).await.unwrap();
+    assert_eq!(notifications.notifications().len(), 2);
+    client.clear_notifications().await.unwrap();
+    assert!(
+        client
+            .get_notifications(None)
+            .await
+            .unwrap()
+            .notifications()
+            .is_empty()
+    );
+}
+
+#[tokio::test]
+async fn injected_failures() {
+    let server = MockServer::start().await.unwrap();
+    server.add_thread(thread(1, 1).build());
+    let client = server.client();
+
+    server.fail_next(Failure::NotFound);
+    server.fail_next(Failure::Status(503));
+    server.fail_next(Failure::RateLimited { retry_after: 1 });
+    assert_status(client.get_thread(1u64).await, 404);
+    assert_status(client.get_thread(1u64).await, 503);
+    assert_status(client.get_thread(1u64).await, 429);
+
+    server.fail_next(Failure::MalformedJson);
+    assert!(matches!(
+        client.get_thread(1u64).await,
+        Err(Error::Json(_) | Error::Schema { .. })
+    ));
+
+    // failures are used up in order, then requests succeed again
+    client.get_thread(1u64).await.unwrap();
+}
+
+#[tokio::test]
+async fn failures_matching_a_path() {
+    let server = MockServer::start().await.unwrap();
+    for n in 1..=2 {
+        server.add_thread(thread(n, n).build());
+    }
+    let client = server.client();
+
+    server.fail_matching("GET", "/api/threads/2", Failure::Status(500));
+    client.get_thread(1u64).await.unwrap();
+    assert_status(client.get_thread(2u64).await, 500);
+    assert_status(client.get_thread(2u64).await, 500);
+
+    server.clear_failures();
+    client.get_thread(2u64).await.unwrap();
+}
+
+#[tokio::test]
+async fn failures_abort_pagination() {
+    let server = MockServer::start().await.unwrap();
+    for n in 1..=150 {
+        server.add_thread(thread(n, n).build());
+    }
+    let client = server.client();
+
+    server.fail_matching("GET", "/api/courses/1/threads", Failure::Unauthorized);
+    assert_status(client.get_all_course_threads(1u64, None).await, 401);
+    server.clear_failures();
+
+    assert_eq!(
+        client
+            .get_all_course_threads(1u64, None)
+            .await
+            .unwrap()
+            .len(),
+        150
+    );
+}
+
+#[tokio::test]
+async fn expected_token() {
+    let server = MockServer::start().await.unwrap();
+    server.set_user(UserFixture::default().build());
+    server.expect_token(MOCK_TOKEN);
+
+    server.client().get_self_user().await.unwrap();
+    let other = crate::Client::new_with_opts(
+        "wrong",
+        crate::ClientOptions {
+            base_url: Some(server.base_url()),
+            ..Default::default()
+        },
+    );
+    assert_status(other.get_self_user().await, 401);
+}
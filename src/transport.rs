@@ -0,0 +1,595 @@
+//! The layer which carries requests to Ed Discussion, and middleware to wrap it.
+//!
+//! [`Client`](crate::Client) builds each request as a [`Request`] and hands it to a
+//! [`Transport`]; by default a `reqwest::Client`. A transport can be replaced outright through
+//! [`ClientOptions::transport`](crate::ClientOptions::transport), e.g. with an in-memory fake in
+//! tests, or wrapped by [`Middleware`] through
+//! [`ClientOptions::middleware`](crate::ClientOptions::middleware). Middleware runs in order, the
+//! first being outermost, and each decides whether and how to call the next.
+//!
+//! This module provides middleware for [logging](Logging), [authentication](Auth),
+//! [retries](Retry), [caching](Cache) and [metrics](Metrics).
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::{self, Debug},
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use reqwest::{
+    Method, StatusCode, Url,
+    header::{AUTHORIZATION, HeaderMap, HeaderValue, RETRY_AFTER},
+};
+
+/// A boxed future, as returned by [`Transport`] and [`Middleware`] so that they can be used as
+/// trait objects.
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// A request ready to be sent.
+#[derive(Clone, Debug)]
+pub struct Request {
+    /// The HTTP method.
+    pub method: Method,
+    /// The full URL, including query parameters.
+    pub url: Url,
+    /// Headers, including `Authorization`.
+    pub headers: HeaderMap,
+    /// The body, if any.
+    pub body: Option<Vec<u8>>,
+}
+
+/// A response, with its body fully read.
+#[derive(Clone, Debug)]
+pub struct Response {
+    /// The HTTP status.
+    pub status: StatusCode,
+    /// Response headers.
+    pub headers: HeaderMap,
+    /// The body.
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    /// A response with the given status and body and no headers.
+    pub fn new(status: StatusCode, body: impl Into<Vec<u8>>) -> Self {
+        Self {
+            status,
+            headers: HeaderMap::new(),
+            body: body.into(),
+        }
+    }
+}
+
+/// Something which can execute a [`Request`].
+pub trait Transport: Debug + Send + Sync {
+    /// Send `request`, returning the response whatever its status.
+    ///
+    /// Errors are for failures to get a response at all, e.g. connectivity.
+    fn execute(&self, request: Request) -> BoxFuture<'_, crate::Result<Response>>;
+}
+
+impl Transport for reqwest::Client {
+    fn execute(&self, request: Request) -> BoxFuture<'_, crate::Result<Response>> {
+        Box::pin(async move {
+            let mut builder = self
+                .request(request.method, request.url)
+                .headers(request.headers);
+            if let Some(body) = request.body {
+                builder = builder.body(body);
+            }
+
+            let response = builder.send().await?;
+            Ok(Response {
+                status: response.status(),
+                headers: response.headers().clone(),
+                body: response.bytes().await?.to_vec(),
+            })
+        })
+    }
+}
+
+/// The rest of the middleware chain and, at its end, the transport.
+#[derive(Clone, Copy)]
+pub struct Next<'a> {
+    middleware: &'a [Arc<dyn Middleware>],
+    transport: &'a dyn Transport,
+}
+
+impl<'a> Next<'a> {
+    /// Pass `request` on down the chain. May be called more than once, e.g. to retry.
+    pub fn run(self, request: Request) -> BoxFuture<'a, crate::Result<Response>> {
+        match self.middleware.split_first() {
+            Some((first, rest)) => first.handle(
+                request,
+                Next {
+                    middleware: rest,
+                    transport: self.transport,
+                },
+            ),
+            None => self.transport.execute(request),
+        }
+    }
+}
+
+/// Something which wraps the sending of requests.
+pub trait Middleware: Debug + Send + Sync {
+    /// Handle `request`, usually by calling [`Next::run`] and possibly changing the request on
+    /// the way in or the response on the way out.
+    fn handle<'a>(
+        &'a self,
+        request: Request,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, crate::Result<Response>>;
+}
+
+/// A transport wrapped in middleware; what [`Client`](crate::Client) actually sends through.
+#[derive(Debug)]
+pub(crate) struct Stack {
+    pub(crate) middleware: Vec<Arc<dyn Middleware>>,
+    pub(crate) transport: Arc<dyn Transport>,
+}
+
+impl Transport for Stack {
+    fn execute(&self, request: Request) -> BoxFuture<'_, crate::Result<Response>> {
+        Next {
+            middleware: &self.middleware,
+            transport: self.transport.as_ref(),
+        }
+        .run(request)
+    }
+}
+
+/// Reports every request, its outcome and how long it took to a sink, e.g. a logger.
+pub struct Logging {
+    sink: Box<dyn Fn(&str) + Send + Sync>,
+}
+
+impl Debug for Logging {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Logging").finish_non_exhaustive()
+    }
+}
+
+impl Logging {
+    /// Send a line per request to `sink`.
+    pub fn new(sink: impl Fn(&str) + Send + Sync + 'static) -> Self {
+        Self {
+            sink: Box::new(sink),
+        }
+    }
+
+    /// Print a line per request to standard error.
+    pub fn stderr() -> Self {
+        Self::new(|line| eprintln!("{line}"))
+    }
+}
+
+impl Middleware for Logging {
+    fn handle<'a>(
+        &'a self,
+        request: Request,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, crate::Result<Response>> {
+        Box::pin(async move {
+            let line = format!("{} {}", request.method, request.url);
+            let start = Instant::now();
+            let result = next.run(request).await;
+            let elapsed = start.elapsed().as_millis();
+            match &result {
+                Ok(response) => {
+                    (self.sink)(&format!("{line} -> {} in {elapsed}ms", response.status))
+                }
+                Err(e) => (self.sink)(&format!("{line} -> {e} after {elapsed}ms")),
+            }
+            result
+        })
+    }
+}
+
+/// Sets the `Authorization` header on every request from a token provider, replacing the one
+/// the client sets, e.g. to rotate tokens without rebuilding the client.
+pub struct Auth {
+    token: Box<dyn Fn() -> String + Send + Sync>,
+}
+
+impl Debug for Auth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Auth").finish_non_exhaustive()
+    }
+}
+
+impl Auth {
+    /// Ask `token` for the token to use on each request.
+    pub fn new(token: impl Fn() -> String + Send + Sync + 'static) -> Self {
+        Self {
+            token: Box::new(token),
+        }
+    }
+
+    /// Always use `token`.
+    pub fn bearer(token: &str) -> Self {
+        let token = String::from(token);
+        Self::new(move || token.clone())
+    }
+}
+
+impl Middleware for Auth {
+    fn handle<'a>(
+        &'a self,
+        mut request: Request,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, crate::Result<Response>> {
+        match HeaderValue::from_str(&format!("Bearer {}", (self.token)())) {
+            Ok(value) => {
+                request.headers.insert(AUTHORIZATION, value);
+                next.run(request)
+            }
+            Err(e) => Box::pin(async move { Err(crate::Error::Request(e.to_string())) }),
+        }
+    }
+}
+
+/// Retries requests which fail to connect or are rate limited (429) or hit a gateway error
+/// (502, 503, 504), with exponential backoff. `Retry-After` is honoured when present.
+///
+/// Only idempotent methods are retried by default. A POST which timed out or hit a gateway
+/// error may still have been carried out, so retrying it can e.g. post a reply twice; add
+/// [`Method::POST`] to [`methods`](Self::methods) to retry it anyway.
+#[derive(Clone, Debug)]
+pub struct Retry {
+    /// The methods to retry; by default GET, HEAD, PUT and DELETE.
+    pub methods: Vec<Method>,
+    /// How many times to retry after the first attempt.
+    pub max_retries: u32,
+    /// The delay before the first retry, doubled for each subsequent retry.
+    pub base_delay: Duration,
+    /// The longest to wait between attempts, including for `Retry-After`.
+    pub max_delay: Duration,
+}
+
+impl Default for Retry {
+    fn default() -> Self {
+        Self {
+            methods: vec![Method::GET, Method::HEAD, Method::PUT, Method::DELETE],
+            max_retries: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+        }
+    }
+}
+
+impl Retry {
+    fn should_retry(result: &crate::Result<Response>) -> bool {
+        match result {
+            Ok(response) => matches!(
+                response.status,
+                StatusCode::TOO_MANY_REQUESTS
+                    | StatusCode::BAD_GATEWAY
+                    | StatusCode::SERVICE_UNAVAILABLE
+                    | StatusCode::GATEWAY_TIMEOUT
+            ),
+            Err(crate::Error::Reqwest(e)) => e.is_connect() || e.is_timeout(),
+            Err(_) => false,
+        }
+    }
+
+    fn delay(&self, attempt: u32, result: &crate::Result<Response>) -> Duration {
+        let retry_after = result
+            .as_ref()
+            .ok()
+            .and_then(|r| r.headers.get(RETRY_AFTER))
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse().ok())
+            .map(Duration::from_secs);
+        retry_after
+            .unwrap_or_else(|| self.base_delay.saturating_mul(2u32.saturating_pow(attempt)))
+            .min(self.max_delay)
+    }
+}
+
+impl Middleware for Retry {
+    fn handle<'a>(
+        &'a self,
+        request: Request,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, crate::Result<Response>> {
+        Box::pin(async move {
+            if !self.methods.contains(&request.method) {
+                return next.run(request).await;
+            }
+
+            let mut attempt = 0;
+            loop {
+                let result = next.run(request.clone()).await;
+                if attempt >= self.max_retries || !Self::should_retry(&result) {
+                    return result;
+                }
+                tokio::time::sleep(self.delay(attempt, &result)).await;
+                attempt += 1;
+            }
+        })
+    }
+}
+
+/// Caches successful GET responses in memory for a fixed time, separately for each
+/// `Authorization` header so that clients sharing a cache never see each other's responses.
+///
+/// Any request other than a GET or HEAD clears the cache once sent, whatever its outcome, since
+/// it may have changed what was cached, e.g. a course re-read to update its settings.
+#[derive(Debug)]
+pub struct Cache {
+    ttl: Duration,
+    entries: Mutex<HashMap<CacheKey, (Instant, Response)>>,
+}
+
+/// The `Authorization` header and URL of a cached request.
+type CacheKey = (Option<HeaderValue>, Url);
+
+impl Cache {
+    /// Cache responses for `ttl`.
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Forget all cached responses.
+    pub fn clear(&self) {
+        self.entries.lock().expect("cache poisoned").clear();
+    }
+}
+
+impl Middleware for Cache {
+    fn handle<'a>(
+        &'a self,
+        request: Request,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, crate::Result<Response>> {
+        Box::pin(async move {
+            if request.method == Method::HEAD {
+                return next.run(request).await;
+            }
+            if request.method != Method::GET {
+                let result = next.run(request).await;
+                self.clear();
+                return result;
+            }
+
+            let key = (
+                request.headers.get(AUTHORIZATION).cloned(),
+                request.url.clone(),
+            );
+            {
+                let mut entries = self.entries.lock().expect("cache poisoned");
+                match entries.get(&key) {
+                    Some((at, response)) if at.elapsed() < self.ttl => {
+                        return Ok(response.clone());
+                    }
+                    Some(_) => {
+                        entries.remove(&key);
+                    }
+                    None => {}
+                }
+            }
+
+            let response = next.run(request).await?;
+            if response.status.is_success() {
+                self.entries
+                    .lock()
+                    .expect("cache poisoned")
+                    .insert(key, (Instant::now(), response.clone()));
+            }
+            Ok(response)
+        })
+    }
+}
+
+/// Counts of requests seen by [`Metrics`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MetricsSnapshot {
+    /// Requests sent, including those which failed without a response.
+    pub requests: u64,
+    /// Requests which failed without a response.
+    pub errors: u64,
+    /// Responses received by status.
+    pub statuses: BTreeMap<u16, u64>,
+    /// Time spent waiting for responses, in total.
+    pub total_latency: Duration,
+    /// The longest wait for a single response.
+    pub max_latency: Duration,
+}
+
+/// Collects request counts and latencies. Keep an `Arc` to read them with
+/// [`snapshot`](Self::snapshot).
+#[derive(Debug, Default)]
+pub struct Metrics {
+    inner: Mutex<MetricsSnapshot>,
+}
+
+impl Metrics {
+    /// The metrics collected so far.
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        self.inner.lock().expect("metrics poisoned").clone()
+    }
+
+    /// Reset all metrics to zero.
+    pub fn reset(&self) {
+        *self.inner.lock().expect("metrics poisoned") = MetricsSnapshot::default();
+    }
+}
+
+impl Middleware for Metrics {
+    fn handle<'a>(
+        &'a self,
+        request: Request,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, crate::Result<Response>> {
+        Box::pin(async move {
+            let start = Instant::now();
+            let result = next.run(request).await;
+            let elapsed = start.elapsed();
+
+            let mut inner = self.inner.lock().expect("metrics poisoned");
+            inner.requests += 1;
+            inner.total_latency += elapsed;
+            inner.max_latency = inner.max_latency.max(elapsed);
+            match &result {
+                Ok(response) => *inner.statuses.entry(response.status.as_u16()).or_default() += 1,
+                Err(_) => inner.errors += 1,
+            }
+            drop(inner);
+
+            result
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use super::*;
+
+    /// Responds with each of `statuses` in turn, then 200.
+    #[derive(Debug, Default)]
+    struct Statuses {
+        statuses: Vec<StatusCode>,
+        sent: AtomicU32,
+    }
+
+    impl Transport for Statuses {
+        fn execute(&self, _: Request) -> BoxFuture<'_, crate::Result<Response>> {
+            let sent = self.sent.fetch_add(1, Ordering::SeqCst) as usize;
+            let status = self.statuses.get(sent).copied().unwrap_or(StatusCode::OK);
+            Box::pin(async move { Ok(Response::new(status, Vec::new())) })
+        }
+    }
+
+    fn request(method: Method) -> Request {
+        Request {
+            method,
+            url: Url::parse("http://localhost/api/user").unwrap(),
+            headers: HeaderMap::new(),
+            body: None,
+        }
+    }
+
+    async fn send_all(middleware: impl Middleware + 'static, requests: Vec<Request>) -> u32 {
+        let transport = Statuses::default();
+        let middleware: [Arc<dyn Middleware>; 1] = [Arc::new(middleware)];
+        for request in requests {
+            Next {
+                middleware: &middleware,
+                transport: &transport,
+            }
+            .run(request)
+            .await
+            .unwrap();
+        }
+        transport.sent.load(Ordering::SeqCst)
+    }
+
+    async fn send(retry: Retry, method: Method, statuses: &[StatusCode]) -> (StatusCode, u32) {
+        let transport = Statuses {
+            statuses: statuses.to_vec(),
+            ..Default::default()
+        };
+        let middleware: [Arc<dyn Middleware>; 1] = [Arc::new(retry)];
+        let response = Next {
+            middleware: &middleware,
+            transport: &transport,
+        }
+        .run(request(method))
+        .await
+        .unwrap();
+        (response.status, transport.sent.load(Ordering::SeqCst))
+    }
+
+    fn immediate() -> Retry {
+        Retry {
+            base_delay: Duration::ZERO,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn retries_gateway_errors_until_success() {
+        let statuses = [StatusCode::BAD_GATEWAY, StatusCode::TOO_MANY_REQUESTS];
+        assert_eq!(
+            send(immediate(), Method::GET, &statuses).await,
+            (StatusCode::OK, 3)
+        );
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_max_retries() {
+        let statuses = [StatusCode::SERVICE_UNAVAILABLE; 5];
+        assert_eq!(
+            send(immediate(), Method::PUT, &statuses).await,
+            (StatusCode::SERVICE_UNAVAILABLE, 4)
+        );
+    }
+
+    #[tokio::test]
+    async fn does_not_retry_other_errors() {
+        let statuses = [StatusCode::INTERNAL_SERVER_ERROR];
+        assert_eq!(
+            send(immediate(), Method::GET, &statuses).await,
+            (StatusCode::INTERNAL_SERVER_ERROR, 1)
+        );
+    }
+
+    #[tokio::test]
+    async fn retries_post_only_when_asked() {
+        let statuses = [StatusCode::GATEWAY_TIMEOUT];
+        assert_eq!(
+            send(immediate(), Method::POST, &statuses).await,
+            (StatusCode::GATEWAY_TIMEOUT, 1)
+        );
+
+        let mut retry = immediate();
+        retry.methods.push(Method::POST);
+        assert_eq!(
+            send(retry, Method::POST, &statuses).await,
+            (StatusCode::OK, 2)
+        );
+    }
+
+    #[tokio::test]
+    async fn caches_gets() {
+        let cache = Cache::new(Duration::from_secs(60));
+        let requests = vec![request(Method::GET), request(Method::GET)];
+        assert_eq!(send_all(cache, requests).await, 1);
+    }
+
+    #[tokio::test]
+    async fn writes_evict_cached_gets() {
+        let cache = Cache::new(Duration::from_secs(60));
+        let requests = vec![
+            request(Method::GET),
+            request(Method::PUT),
+            request(Method::GET),
+        ];
+        assert_eq!(send_all(cache, requests).await, 3);
+    }
+
+    #[tokio::test]
+    async fn caches_by_authorization() {
+        let cache = Cache::new(Duration::from_secs(60));
+        let requests = ["Bearer a", "Bearer b", "Bearer a"]
+            .into_iter()
+            .map(|token| {
+                let mut request = request(Method::GET);
+                request
+                    .headers
+                    .insert(AUTHORIZATION, HeaderValue::from_static(token));
+                request
+            })
+            .collect();
+        assert_eq!(send_all(cache, requests).await, 2);
+    }
+}
@@ -0,0 +1,80 @@
+use std::{
+    env, fs,
+    io::{self, Write},
+    path::PathBuf,
+    process::Command,
+};
+
+use ratatui::{
+    DefaultTerminal,
+    crossterm::{
+        execute,
+        terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
+    },
+};
+use tempfile::NamedTempFile;
+
+const HEADER_END: &str = "-->";
+
+/// A reply written in the editor. Its file is deleted when the draft is dropped, so keep it until
+/// the reply is posted.
+pub(crate) struct Draft {
+    file: NamedTempFile,
+    /// The Markdown written below the instructions.
+    pub(crate) body: String,
+}
+
+impl Draft {
+    /// Keep the file rather than deleting it, e.g. because posting failed, returning its path.
+    pub(crate) fn keep(self) -> io::Result<PathBuf> {
+        self.file.keep().map(|(_, path)| path).map_err(|e| e.error)
+    }
+}
+
+/// Suspend the TUI and open `$VISUAL` or `$EDITOR` (falling back to `vi`) on a scratch Markdown
+/// file, returning what was written below the instructions, or `None` if nothing was.
+pub(crate) fn compose(
+    terminal: &mut DefaultTerminal,
+    instructions: &str,
+) -> io::Result<Option<Draft>> {
+    // a fresh file only we can read, deleted when `draft` is dropped
+    let mut draft = tempfile::Builder::new()
+        .prefix("edstem-reply-")
+        .suffix(".md")
+        .tempfile()?;
+    write!(
+        draft,
+        "<!--\n{instructions}\nWrite your reply in Markdown below. Leave it empty to cancel.\n{HEADER_END}\n\n"
+    )?;
+    draft.flush()?;
+    let path = draft.path().to_path_buf();
+
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .unwrap_or_else(|_| String::from("vi"));
+    let mut words = editor.split_whitespace();
+    let program = words.next().unwrap_or("vi");
+
+    disable_raw_mode()?;
+    execute!(io::stdout(), LeaveAlternateScreen)?;
+    let status = Command::new(program).args(words).arg(&path).status();
+    execute!(io::stdout(), EnterAlternateScreen)?;
+    enable_raw_mode()?;
+    terminal.clear()?;
+
+    if !status?.success() {
+        return Ok(None);
+    }
+
+    let written = fs::read_to_string(&path)?;
+    let body = match written.split_once(HEADER_END) {
+        Some((_, body)) => body,
+        None => &written,
+    }
+    .trim();
+
+    Ok((!body.is_empty()).then(|| Draft {
+        file: draft,
+        body: String::from(body),
+    }))
+}
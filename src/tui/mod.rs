@@ -0,0 +1,656 @@
+//! A terminal client for browsing courses and threads and replying to them, run by the
+//! `edstem-tui` binary.
+//!
+//! The thread list follows the user's "Thread List Style" setting. Keys are shown in the status
+//! bar of each screen; replies are composed as Markdown in `$VISUAL` or `$EDITOR`.
+
+use std::collections::HashSet;
+
+use ratatui::{
+    DefaultTerminal, Frame,
+    crossterm::event::{self, Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span, Text},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap},
+};
+
+use crate::{
+    Client,
+    document::Document,
+    model::{
+        course::{CourseID, SelfUserCourse},
+        thread::{PartialThread, Reply, ReplyType, Thread, ThreadID, ThreadType},
+        user::{ThreadListStyle, UserID},
+    },
+    opts::{GetCourseThreadsOptions, PostReplyKind, PostReplyOptions},
+    participants::ParticipantIndex,
+};
+
+mod editor;
+mod render;
+
+const PAGE_SIZE: u64 = 30;
+
+struct Feed {
+    course_id: CourseID,
+    title: String,
+    threads: Vec<PartialThread>,
+    users: ParticipantIndex,
+    /// threads opened during this session, which the server will since have marked seen
+    opened: HashSet<ThreadID>,
+    state: ListState,
+    exhausted: bool,
+}
+
+impl Feed {
+    fn is_unread(&self, thread: &PartialThread) -> bool {
+        !self.opened.contains(thread.id()) && (!thread.is_seen() || *thread.new_reply_count() > 0)
+    }
+}
+
+struct ThreadView {
+    thread: Thread,
+    lines: Vec<Line<'static>>,
+    scroll: u16,
+}
+
+enum Screen {
+    Courses,
+    Feed,
+    Thread,
+}
+
+struct App {
+    client: Client,
+    style: ThreadListStyle,
+    courses: Vec<SelfUserCourse>,
+    course_state: ListState,
+    feed: Option<Feed>,
+    thread: Option<ThreadView>,
+    screen: Screen,
+    status: String,
+}
+
+fn author_name(users: &ParticipantIndex, id: &UserID, anonymous: bool) -> String {
+    if anonymous {
+        return String::from("Anonymous");
+    }
+    users
+        .get(*id)
+        .map(|u| u.name().clone())
+        .unwrap_or_else(|| String::from("Unknown"))
+}
+
+fn parse_content(xml: &str) -> Document {
+    // fall back to showing the raw body rather than failing to open the thread
+    Document::parse(xml).unwrap_or_else(|_| Document::from_plain_text(xml))
+}
+
+fn date(timestamp: &str) -> &str {
+    timestamp.get(..10).unwrap_or(timestamp)
+}
+
+fn reply_lines(
+    lines: &mut Vec<Line<'static>>,
+    reply: &Reply,
+    users: &ParticipantIndex,
+    depth: usize,
+) {
+    let indent = "    ".repeat(depth);
+    let mut header = vec![
+        Span::raw(indent.clone()),
+        Span::styled(
+            author_name(users, reply.user_id(), *reply.is_anonymous()),
+            Style::default().add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(
+            format!(
+                " · {} · {} votes · {}",
+                match reply.type_() {
+                    ReplyType::Answer => "answer",
+                    ReplyType::Comment => "comment",
+                },
+                reply.vote_count(),
+                date(reply.created_at()),
+            ),
+            Style::default().dim(),
+        ),
+    ];
+    if *reply.is_endorsed() {
+        header.push(Span::styled(
+            " ✓ endorsed",
+            Style::default().fg(Color::Green),
+        ));
+    }
+    lines.push(Line::from(header));
+    lines.extend(render::document(
+        &parse_content(reply.content()),
+        depth * 4 + 2,
+    ));
+    lines.push(Line::default());
+
+    for comment in reply.comments() {
+        reply_lines(lines, comment, users, depth + 1);
+    }
+}
+
+fn thread_lines(thread: &Thread, users: &ParticipantIndex) -> Vec<Line<'static>> {
+    let mut category = thread.category().clone();
+    for sub in [thread.subcategory(), thread.subsubcategory()] {
+        if !sub.is_empty() {
+            category.push_str(" › ");
+            category.push_str(sub);
+        }
+    }
+
+    let mut lines = vec![
+        Line::from(Span::styled(
+            format!("#{} {}", thread.number(), thread.title()),
+            Style::default().add_modifier(Modifier::BOLD),
+        )),
+        Line::from(Span::styled(
+            format!(
+                "{} · {} · {} · {} views · {} votes",
+                author_name(users, thread.user_id(), *thread.is_anonymous()),
+                category,
+                date(thread.created_at()),
+                thread.view_count(),
+                thread.vote_count(),
+            ),
+            Style::default().dim(),
+        )),
+        Line::default(),
+    ];
+    lines.extend(render::document(&parse_content(thread.content()), 0));
+    lines.push(Line::default());
+
+    for (heading, replies) in [
+        ("Answers", thread.answers()),
+        ("Comments", thread.comments()),
+    ] {
+        if replies.is_empty() {
+            continue;
+        }
+        lines.push(Line::from(Span::styled(
+            format!("── {heading} ({}) ──", replies.len()),
+            Style::default().fg(Color::Blue),
+        )));
+        for reply in replies {
+            reply_lines(&mut lines, reply, users, 0);
+        }
+    }
+
+    lines
+}
+
+impl App {
+    fn feed_item(&self, feed: &Feed, thread: &PartialThread) -> ListItem<'static> {
+        let mut marker = vec![Span::raw(if feed.is_unread(thread) && !thread.is_seen() {
+            "● "
+        } else {
+            "  "
+        })];
+        if *thread.is_pinned() {
+            marker.push(Span::styled("⚑ ", Style::default().fg(Color::Yellow)));
+        }
+        let title = Span::styled(
+            format!("#{} {}", thread.number(), thread.title()),
+            if feed.is_unread(thread) {
+                Style::default().add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            },
+        );
+        let new_replies = if *thread.new_reply_count() > 0 && !feed.opened.contains(thread.id()) {
+            Span::styled(
+                format!(" +{}", thread.new_reply_count()),
+                Style::default().fg(Color::Green),
+            )
+        } else {
+            Span::raw("")
+        };
+        let kind = match thread.type_() {
+            ThreadType::Question => "question",
+            ThreadType::Announcement => "announcement",
+            ThreadType::Post => "post",
+        };
+        let meta = format!(
+            " · {} · {kind} · {} replies",
+            thread.category(),
+            thread.reply_count()
+        );
+
+        let mut first = marker;
+        first.extend([title, new_replies]);
+        match self.style {
+            ThreadListStyle::UltraCompact => ListItem::new(Line::from(first)),
+            ThreadListStyle::Compact => {
+                first.push(Span::styled(meta, Style::default().dim()));
+                ListItem::new(Line::from(first))
+            }
+            ThreadListStyle::Full | ThreadListStyle::Other(_) => {
+                let preview: String = thread
+                    .document()
+                    .split_whitespace()
+                    .collect::<Vec<_>>()
+                    .join(" ")
+                    .chars()
+                    .take(120)
+                    .collect();
+                ListItem::new(Text::from(vec![
+                    Line::from(first),
+                    Line::from(Span::styled(
+                        format!(
+                            "    {} · {}{meta}",
+                            author_name(&feed.users, thread.user_id(), *thread.is_anonymous()),
+                            date(thread.created_at()),
+                        ),
+                        Style::default().dim(),
+                    )),
+                    Line::from(Span::styled(
+                        format!("    {preview}"),
+                        Style::default().italic(),
+                    )),
+                ]))
+            }
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [main, status] =
+            Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(frame.area());
+        let highlight = Style::default().bg(Color::DarkGray);
+
+        let keys = match self.screen {
+            Screen::Courses => "↑/↓ move · enter open · q quit",
+            Screen::Feed => "↑/↓ move · enter open · n next unread · r refresh · q back",
+            Screen::Thread => {
+                "↑/↓ scroll · c comment · a answer · n next unread · r refresh · q back"
+            }
+        };
+        frame.render_widget(
+            Paragraph::new(Line::from(vec![
+                Span::styled(keys, Style::default().dim()),
+                Span::raw("  "),
+                Span::styled(self.status.clone(), Style::default().fg(Color::Yellow)),
+            ])),
+            status,
+        );
+
+        match self.screen {
+            Screen::Courses => {
+                let items: Vec<ListItem> = self
+                    .courses
+                    .iter()
+                    .map(|c| {
+                        let course = c.course();
+                        ListItem::new(Line::from(vec![
+                            Span::styled(
+                                format!("{:<12}", course.code()),
+                                Style::default().add_modifier(Modifier::BOLD),
+                            ),
+                            Span::raw(format!(" {}", course.name())),
+                            Span::styled(
+                                format!(" ({} {})", course.session(), course.year()),
+                                Style::default().dim(),
+                            ),
+                        ]))
+                    })
+                    .collect();
+                frame.render_stateful_widget(
+                    List::new(items)
+                        .block(Block::default().borders(Borders::ALL).title("Courses"))
+                        .highlight_style(highlight),
+                    main,
+                    &mut self.course_state,
+                );
+            }
+            Screen::Feed => {
+                let Some(feed) = self.feed.as_ref() else {
+                    return;
+                };
+                let items: Vec<ListItem> = feed
+                    .threads
+                    .iter()
+                    .map(|t| self.feed_item(feed, t))
+                    .collect();
+                let title = feed.title.clone();
+                let feed = self.feed.as_mut().expect("checked above");
+                frame.render_stateful_widget(
+                    List::new(items)
+                        .block(Block::default().borders(Borders::ALL).title(title))
+                        .highlight_style(highlight),
+                    main,
+                    &mut feed.state,
+                );
+            }
+            Screen::Thread => {
+                let Some(view) = self.thread.as_ref() else {
+                    return;
+                };
+                frame.render_widget(
+                    Paragraph::new(view.lines.clone())
+                        .block(Block::default().borders(Borders::ALL))
+                        .wrap(Wrap { trim: false })
+                        .scroll((view.scroll, 0)),
+                    main,
+                );
+            }
+        }
+    }
+
+    async fn load_more(&mut self) -> crate::Result<()> {
+        let Some(feed) = self.feed.as_mut() else {
+            return Ok(());
+        };
+        if feed.exhausted {
+            return Ok(());
+        }
+
+        let page = self
+            .client
+            .get_course_threads(
+                feed.course_id,
+                Some(GetCourseThreadsOptions {
+                    limit: PAGE_SIZE,
+                    offset: feed.threads.len() as u64,
+                    ..Default::default()
+                }),
+            )
+            .await?;
+        let (_, threads, users, _) = page.dissolve();
+
+        feed.exhausted = (threads.len() as u64) < PAGE_SIZE;
+        feed.users.extend(users);
+        feed.threads.extend(threads);
+        if feed.state.selected().is_none() && !feed.threads.is_empty() {
+            feed.state.select(Some(0));
+        }
+        Ok(())
+    }
+
+    async fn open_course(&mut self) -> crate::Result<()> {
+        let Some(course) = self
+            .course_state
+            .selected()
+            .and_then(|i| self.courses.get(i))
+        else {
+            return Ok(());
+        };
+        let course = course.course();
+        self.feed = Some(Feed {
+            course_id: *course.id(),
+            title: format!("{} · {}", course.code(), course.name()),
+            threads: Vec::new(),
+            users: ParticipantIndex::default(),
+            opened: HashSet::new(),
+            state: ListState::default(),
+            exhausted: false,
+        });
+        self.load_more().await?;
+        self.screen = Screen::Feed;
+        Ok(())
+    }
+
+    async fn open_thread(&mut self) -> crate::Result<()> {
+        let Some(feed) = self.feed.as_mut() else {
+            return Ok(());
+        };
+        let Some(id) = feed
+            .state
+            .selected()
+            .and_then(|i| feed.threads.get(i))
+            .map(|t| *t.id())
+        else {
+            return Ok(());
+        };
+
+        let (thread, users, _) = self.client.get_thread(id).await?.dissolve();
+        feed.opened.insert(id);
+        feed.users.extend(users);
+        self.thread = Some(ThreadView {
+            lines: thread_lines(&thread, &feed.users),
+            thread,
+            scroll: 0,
+        });
+        self.screen = Screen::Thread;
+        Ok(())
+    }
+
+    /// Select the next unread thread after the current selection, paging in more threads as
+    /// needed. Returns whether one was found.
+    async fn next_unread(&mut self) -> crate::Result<bool> {
+        loop {
+            let Some(feed) = self.feed.as_mut() else {
+                return Ok(false);
+            };
+            let start = feed.state.selected().map(|i| i + 1).unwrap_or(0);
+            if let Some(offset) = feed.threads[start.min(feed.threads.len())..]
+                .iter()
+                .position(|t| feed.is_unread(t))
+            {
+                feed.state.select(Some(start + offset));
+                return Ok(true);
+            }
+            if feed.exhausted {
+                return Ok(false);
+            }
+            self.load_more().await?;
+        }
+    }
+
+    async fn reply(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+        kind: PostReplyKind,
+    ) -> crate::Result<()> {
+        let Some(view) = self.thread.as_ref() else {
+            return Ok(());
+        };
+        if kind == PostReplyKind::Answer && *view.thread.type_() != ThreadType::Question {
+            self.status = String::from("only questions can be answered");
+            return Ok(());
+        }
+
+        let instructions = format!(
+            "{} to #{} {}",
+            match kind {
+                PostReplyKind::Answer => "Answer",
+                PostReplyKind::Comment => "Comment",
+            },
+            view.thread.number(),
+            view.thread.title(),
+        );
+        let draft = match editor::compose(terminal, &instructions) {
+            Ok(Some(draft)) => draft,
+            Ok(None) => {
+                self.status = String::from("reply cancelled");
+                return Ok(());
+            }
+            Err(e) => {
+                self.status = format!("could not run editor: {e}");
+                return Ok(());
+            }
+        };
+
+        let id = *view.thread.id();
+        let posted = self
+            .client
+            .post_thread_reply(
+                id,
+                &Document::from_markdown(&draft.body),
+                PostReplyOptions {
+                    kind,
+                    ..Default::default()
+                },
+            )
+            .await;
+        if let Err(e) = posted {
+            // keep what was written rather than making the user write it again
+            self.status = match draft.keep() {
+                Ok(path) => format!("{e}; draft kept at {}", path.display()),
+                Err(keep) => format!("{e}; draft lost: {keep}"),
+            };
+            return Ok(());
+        }
+        self.status = String::from("reply posted");
+        self.refresh_thread().await
+    }
+
+    async fn refresh_thread(&mut self) -> crate::Result<()> {
+        let (Some(view), Some(feed)) = (self.thread.as_mut(), self.feed.as_ref()) else {
+            return Ok(());
+        };
+        view.thread = self
+            .client
+            .get_thread(*view.thread.id())
+            .await?
+            .dissolve()
+            .0;
+        view.lines = thread_lines(&view.thread, &feed.users);
+        Ok(())
+    }
+
+    async fn refresh_feed(&mut self) -> crate::Result<()> {
+        if let Some(feed) = self.feed.as_mut() {
+            feed.threads.clear();
+            feed.opened.clear();
+            feed.state = ListState::default();
+            feed.exhausted = false;
+        }
+        self.load_more().await
+    }
+
+    /// Handle a key press, returning whether to quit.
+    async fn handle_key(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+        key: KeyCode,
+    ) -> crate::Result<bool> {
+        match self.screen {
+            Screen::Courses => match key {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(true),
+                KeyCode::Down | KeyCode::Char('j') => self.course_state.select_next(),
+                KeyCode::Up | KeyCode::Char('k') => self.course_state.select_previous(),
+                KeyCode::Enter => self.open_course().await?,
+                _ => {}
+            },
+            Screen::Feed => match key {
+                KeyCode::Char('q') | KeyCode::Esc => self.screen = Screen::Courses,
+                KeyCode::Down | KeyCode::Char('j') => {
+                    let at_end = self.feed.as_ref().is_some_and(|f| {
+                        f.state.selected().is_some_and(|i| i + 1 >= f.threads.len())
+                    });
+                    if at_end {
+                        self.load_more().await?;
+                    }
+                    if let Some(feed) = self.feed.as_mut() {
+                        feed.state.select_next();
+                    }
+                }
+                KeyCode::Up | KeyCode::Char('k') => {
+                    if let Some(feed) = self.feed.as_mut() {
+                        feed.state.select_previous();
+                    }
+                }
+                KeyCode::Char('n') => {
+                    let found = self.next_unread().await?;
+                    if !found {
+                        self.status = String::from("no unread threads");
+                    }
+                }
+                KeyCode::Char('r') => self.refresh_feed().await?,
+                KeyCode::Enter => self.open_thread().await?,
+                _ => {}
+            },
+            Screen::Thread => match key {
+                KeyCode::Char('q') | KeyCode::Esc => self.screen = Screen::Feed,
+                KeyCode::Down | KeyCode::Char('j') => {
+                    if let Some(view) = self.thread.as_mut() {
+                        view.scroll = view.scroll.saturating_add(1);
+                    }
+                }
+                KeyCode::Up | KeyCode::Char('k') => {
+                    if let Some(view) = self.thread.as_mut() {
+                        view.scroll = view.scroll.saturating_sub(1);
+                    }
+                }
+                KeyCode::PageDown | KeyCode::Char(' ') => {
+                    if let Some(view) = self.thread.as_mut() {
+                        view.scroll = view.scroll.saturating_add(20);
+                    }
+                }
+                KeyCode::PageUp => {
+                    if let Some(view) = self.thread.as_mut() {
+                        view.scroll = view.scroll.saturating_sub(20);
+                    }
+                }
+                KeyCode::Char('c') => self.reply(terminal, PostReplyKind::Comment).await?,
+                KeyCode::Char('a') => self.reply(terminal, PostReplyKind::Answer).await?,
+                KeyCode::Char('r') => self.refresh_thread().await?,
+                KeyCode::Char('n') => {
+                    if self.next_unread().await? {
+                        self.open_thread().await?;
+                    } else {
+                        self.status = String::from("no unread threads");
+                    }
+                }
+                _ => {}
+            },
+        }
+        Ok(false)
+    }
+}
+
+/// Run the terminal client until the user quits.
+///
+/// This takes over the terminal for its duration and restores it afterwards, even on error.
+pub async fn run(client: Client) -> crate::Result<()> {
+    let user = client.get_self_user().await?;
+    let (courses, _, _, _, user, _) = user.dissolve();
+    let mut app = App {
+        client,
+        style: user.settings().discuss_feed_style().clone(),
+        course_state: ListState::default().with_selected((!courses.is_empty()).then_some(0)),
+        courses,
+        feed: None,
+        thread: None,
+        screen: Screen::Courses,
+        status: String::new(),
+    };
+
+    let mut terminal = ratatui::init();
+    let result = event_loop(&mut terminal, &mut app).await;
+    ratatui::restore();
+    result
+}
+
+async fn event_loop(terminal: &mut DefaultTerminal, app: &mut App) -> crate::Result<()> {
+    loop {
+        terminal.draw(|frame| app.draw(frame))?;
+
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+
+        app.status.clear();
+        match app.handle_key(terminal, key.code).await {
+            Ok(true) => return Ok(()),
+            Ok(false) => {}
+            // show request failures rather than tearing down the whole UI
+            Err(
+                e @ (crate::Error::Reqwest(_)
+                | crate::Error::Status { .. }
+                | crate::Error::Json(_)
+                | crate::Error::Schema { .. }),
+            ) => {
+                app.status = e.to_string();
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
@@ -0,0 +1,173 @@
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+};
+
+use crate::document::{Block, CalloutKind, Document, Inline, ListStyle};
+
+/// Accumulates styled lines, each prefixed by the indent in effect when it was started.
+struct LineWriter {
+    lines: Vec<Line<'static>>,
+    current: Vec<Span<'static>>,
+    prefix: Vec<Span<'static>>,
+}
+
+impl LineWriter {
+    fn push(&mut self, span: Span<'static>) {
+        if self.current.is_empty() {
+            self.current.extend(self.prefix.iter().cloned());
+        }
+        self.current.push(span);
+    }
+
+    fn finish_line(&mut self) {
+        if self.current.is_empty() {
+            self.current.extend(self.prefix.iter().cloned());
+        }
+        self.lines
+            .push(Line::from(std::mem::take(&mut self.current)));
+    }
+
+    fn with_prefix(&mut self, prefix: Span<'static>, f: impl FnOnce(&mut Self)) {
+        self.prefix.push(prefix);
+        f(self);
+        self.prefix.pop();
+    }
+}
+
+fn inlines(w: &mut LineWriter, content: &[Inline], style: Style) {
+    for inline in content {
+        match inline {
+            Inline::Text(t) => {
+                for (i, part) in t.split('\n').enumerate() {
+                    if i > 0 {
+                        w.finish_line();
+                    }
+                    w.push(Span::styled(String::from(part), style));
+                }
+            }
+            Inline::Bold(c) => inlines(w, c, style.add_modifier(Modifier::BOLD)),
+            Inline::Italic(c) => inlines(w, c, style.add_modifier(Modifier::ITALIC)),
+            Inline::Underline(c) => inlines(w, c, style.add_modifier(Modifier::UNDERLINED)),
+            Inline::Strike(c) => inlines(w, c, style.add_modifier(Modifier::CROSSED_OUT)),
+            Inline::Code(t) => w.push(Span::styled(t.clone(), style.fg(Color::Yellow))),
+            Inline::Link { href, content } => {
+                inlines(
+                    w,
+                    content,
+                    style.fg(Color::Cyan).add_modifier(Modifier::UNDERLINED),
+                );
+                w.push(Span::styled(
+                    format!(" <{href}>"),
+                    style.fg(Color::DarkGray),
+                ));
+            }
+            Inline::Math(t) => w.push(Span::styled(format!("${t}$"), style.fg(Color::Magenta))),
+            Inline::Mention { name, .. } => w.push(Span::styled(
+                format!("@{name}"),
+                style.fg(Color::Cyan).add_modifier(Modifier::BOLD),
+            )),
+            Inline::Break => w.finish_line(),
+            Inline::Unknown(e) => w.push(Span::styled(e.text(), style)),
+        }
+    }
+}
+
+fn blocks(w: &mut LineWriter, content: &[Block]) {
+    for (i, block) in content.iter().enumerate() {
+        if i > 0 {
+            w.finish_line();
+        }
+        match block {
+            Block::Paragraph(c) => {
+                inlines(w, c, Style::default());
+                w.finish_line();
+            }
+            Block::Heading { level, content } => {
+                let style = Style::default().add_modifier(Modifier::BOLD);
+                let style = if *level <= 1 {
+                    style.add_modifier(Modifier::UNDERLINED)
+                } else {
+                    style
+                };
+                inlines(w, content, style);
+                w.finish_line();
+            }
+            Block::List { style, items } => {
+                for (n, item) in items.iter().enumerate() {
+                    let marker = match style {
+                        ListStyle::Number => format!("{}. ", n + 1),
+                        _ => String::from("• "),
+                    };
+                    let width = marker.chars().count();
+                    w.push(Span::raw(marker));
+                    // the marker stands in for the indent on the item's first line
+                    w.with_prefix(Span::raw(" ".repeat(width)), |w| blocks(w, item));
+                }
+            }
+            Block::Pre(t) | Block::Snippet { content: t, .. } => {
+                if let Block::Snippet { language, .. } = block
+                    && !language.is_empty()
+                {
+                    w.push(Span::styled(
+                        format!("[{language}]"),
+                        Style::default().dim(),
+                    ));
+                    w.finish_line();
+                }
+                w.with_prefix(Span::styled("│ ", Style::default().dim()), |w| {
+                    for line in t.lines() {
+                        w.push(Span::styled(
+                            String::from(line),
+                            Style::default().fg(Color::Green),
+                        ));
+                        w.finish_line();
+                    }
+                });
+            }
+            Block::Callout { kind, content } => {
+                let color = match kind {
+                    CalloutKind::Success => Color::Green,
+                    CalloutKind::Warning => Color::Yellow,
+                    CalloutKind::Error => Color::Red,
+                    CalloutKind::Info | CalloutKind::Other(_) => Color::Blue,
+                };
+                w.with_prefix(Span::styled("▌ ", Style::default().fg(color)), |w| {
+                    blocks(w, content)
+                });
+            }
+            Block::Image { src, .. } => {
+                w.push(Span::styled(
+                    format!("[image: {src}]"),
+                    Style::default().dim(),
+                ));
+                w.finish_line();
+            }
+            Block::Spoiler(content) => {
+                w.push(Span::styled("[spoiler]", Style::default().dim()));
+                w.finish_line();
+                w.with_prefix(Span::styled("░ ", Style::default().dim()), |w| {
+                    blocks(w, content)
+                });
+            }
+            Block::Unknown(e) => {
+                w.push(Span::raw(e.text()));
+                w.finish_line();
+            }
+        }
+    }
+}
+
+/// Render a document as styled lines, each starting with `indent`.
+pub(crate) fn document(document: &Document, indent: usize) -> Vec<Line<'static>> {
+    let mut w = LineWriter {
+        lines: Vec::new(),
+        current: Vec::new(),
+        prefix: vec![Span::raw(" ".repeat(indent))],
+    };
+    blocks(&mut w, &document.blocks);
+    if !w.current.is_empty() {
+        w.finish_line();
+    }
+    w.lines
+}